@@ -0,0 +1,281 @@
+//! Memory-mapped bus implementing [`MemoryAccess`] and [`ExecutionEnvironmentInterface`] for RV32 and RV64 harts.
+//!
+//! RAM, ROM and devices are mapped at arbitrary base addresses with [`Bus::map_ram`], [`Bus::map_rom`] and
+//! [`Bus::map_device`]. Accesses to unmapped addresses, writes to ROM and accesses crossing the end of a region return
+//! [`MemoryFault::AccessFault`]. Misaligned accesses to devices return [`MemoryFault::Misaligned`].
+
+use std::any::Any;
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::public::{ExecutionEnvironmentInterface, MemoryAccess, MemoryFault, Traps};
+
+/// A memory-mapped device. Offsets are relative to the base address of the device on the bus.
+///
+/// Only the byte accesses have to be implemented, the wider accesses default to little-endian sequences of byte accesses.
+/// Wider accesses are always naturally aligned.
+pub trait Device: Any {
+    /// Reads the byte at the given offset.
+    fn read_8(&mut self, offset: u64) -> Result<u8, MemoryFault>;
+
+    /// Writes the given byte at the given offset.
+    fn write_8(&mut self, offset: u64, data: u8) -> Result<(), MemoryFault>;
+
+    /// Reads the half-word (16 bits) at the given offset.
+    fn read_16(&mut self, offset: u64) -> Result<u16, MemoryFault> {
+        Ok((self.read_8(offset + 1)? as u16) << 8 | self.read_8(offset)? as u16)
+    }
+
+    /// Reads the word (32 bits) at the given offset.
+    fn read_32(&mut self, offset: u64) -> Result<u32, MemoryFault> {
+        Ok((self.read_16(offset + 2)? as u32) << 16 | self.read_16(offset)? as u32)
+    }
+
+    /// Reads the double-word (64 bits) at the given offset.
+    fn read_64(&mut self, offset: u64) -> Result<u64, MemoryFault> {
+        Ok((self.read_32(offset + 4)? as u64) << 32 | self.read_32(offset)? as u64)
+    }
+
+    /// Writes the given half-word (16 bits) at the given offset.
+    fn write_16(&mut self, offset: u64, data: u16) -> Result<(), MemoryFault> {
+        self.write_8(offset, data as u8)?;
+        self.write_8(offset + 1, (data >> 8) as u8)
+    }
+
+    /// Writes the given word (32 bits) at the given offset.
+    fn write_32(&mut self, offset: u64, data: u32) -> Result<(), MemoryFault> {
+        self.write_16(offset, data as u16)?;
+        self.write_16(offset + 2, (data >> 16) as u16)
+    }
+
+    /// Writes the given double-word (64 bits) at the given offset.
+    fn write_64(&mut self, offset: u64, data: u64) -> Result<(), MemoryFault> {
+        self.write_32(offset, data as u32)?;
+        self.write_32(offset + 4, (data >> 32) as u32)
+    }
+}
+
+/// Errors returned when mapping a region on the bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapError {
+    /// The region is empty or extends past the end of the 64-bits address space.
+    InvalidSize { base: u64, size: u64 },
+    /// The region overlaps the region mapped at the given base address.
+    Overlap { base: u64, mapped: u64 },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSize { base, size } => write!(f, "invalid size {:#x} for the region at {:#x}", size, base),
+            Self::Overlap { base, mapped } => write!(f, "region at {:#x} overlaps the region at {:#x}", base, mapped),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+enum Kind {
+    Ram(Vec<u8>),
+    Rom(Vec<u8>),
+    Device(Box<dyn Device>),
+}
+
+struct Region {
+    base: u64,
+    size: u64,
+    kind: Kind,
+}
+
+impl Region {
+    fn contains(&self, addr: u64, len: u64) -> bool {
+        addr >= self.base && self.size.checked_sub(len).is_some_and(|last| addr - self.base <= last)
+    }
+}
+
+/// Handler of the traps of the hart, see [`Bus::set_trap_handler`].
+type TrapHandler = Box<dyn FnMut(Traps, u64, u64)>;
+
+/// Memory-mapped bus. See the [module documentation](self).
+pub struct Bus {
+    /// Mapped regions, sorted by base address.
+    regions: Vec<Region>,
+    /// Index of the region of the last access, checked first.
+    last: usize,
+    trap_handler: Option<TrapHandler>,
+}
+
+impl Bus {
+    /// Creates an empty bus.
+    pub fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+            last: 0,
+            trap_handler: None,
+        }
+    }
+
+    /// Maps `size` bytes of zero-initialized RAM at `base`.
+    pub fn map_ram(&mut self, base: u64, size: u64) -> Result<(), MapError> {
+        self.map(base, size, Kind::Ram(vec![0; size as usize]))
+    }
+
+    /// Maps the given read-only data at `base`. Writes by the hart return an access fault.
+    pub fn map_rom(&mut self, base: u64, data: Vec<u8>) -> Result<(), MapError> {
+        self.map(base, data.len() as u64, Kind::Rom(data))
+    }
+
+    /// Maps the given device on `size` bytes at `base`.
+    pub fn map_device<D: Device>(&mut self, base: u64, size: u64, device: D) -> Result<(), MapError> {
+        self.map(base, size, Kind::Device(Box::new(device)))
+    }
+
+    fn map(&mut self, base: u64, size: u64, kind: Kind) -> Result<(), MapError> {
+        if size == 0 || base.checked_add(size - 1).is_none() {
+            return Err(MapError::InvalidSize { base, size });
+        }
+        let index = self.regions.partition_point(|region| region.base < base);
+        let overlap = index.checked_sub(1).map(|i| &self.regions[i]).filter(|prev| prev.base + (prev.size - 1) >= base)
+            .or(self.regions.get(index).filter(|next| next.base <= base + (size - 1)));
+        if let Some(region) = overlap {
+            return Err(MapError::Overlap { base, mapped: region.base });
+        }
+        self.regions.insert(index, Region { base, size, kind });
+        self.last = 0;
+        Ok(())
+    }
+
+    /// Sets the function called with the trap, `tval` and `pc` when [`ExecutionEnvironmentInterface::trap`] is called.
+    /// Traps are ignored by default, they are still returned by [`single_step`](crate::rvi::RVI::single_step).
+    pub fn set_trap_handler<F: FnMut(Traps, u64, u64) + 'static>(&mut self, handler: F) {
+        self.trap_handler = Some(Box::new(handler));
+    }
+
+    /// Returns the `len` bytes of RAM or ROM at `addr`, or None if they are not in a single RAM or ROM region.
+    pub fn memory(&self, addr: u64, len: u64) -> Option<&[u8]> {
+        let region = self.regions.iter().find(|region| region.contains(addr, len))?;
+        let offset = (addr - region.base) as usize;
+        match &region.kind {
+            Kind::Ram(memory) | Kind::Rom(memory) => Some(&memory[offset..offset + len as usize]),
+            Kind::Device(_) => None,
+        }
+    }
+
+    /// Returns the `len` bytes of RAM or ROM at `addr` to be initialized by the host, including ROM.
+    pub fn memory_mut(&mut self, addr: u64, len: u64) -> Option<&mut [u8]> {
+        let region = self.regions.iter_mut().find(|region| region.contains(addr, len))?;
+        let offset = (addr - region.base) as usize;
+        match &mut region.kind {
+            Kind::Ram(memory) | Kind::Rom(memory) => Some(&mut memory[offset..offset + len as usize]),
+            Kind::Device(_) => None,
+        }
+    }
+
+    /// Returns the device of type `D` mapped at `base`.
+    pub fn device<D: Device>(&self, base: u64) -> Option<&D> {
+        match &self.regions.iter().find(|region| region.base == base)?.kind {
+            Kind::Device(device) => (&**device as &dyn Any).downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the device of type `D` mapped at `base`.
+    pub fn device_mut<D: Device>(&mut self, base: u64) -> Option<&mut D> {
+        match &mut self.regions.iter_mut().find(|region| region.base == base)?.kind {
+            Kind::Device(device) => (&mut **device as &mut dyn Any).downcast_mut(),
+            _ => None,
+        }
+    }
+
+    /// Returns the region containing the `len` bytes at `addr` and the offset of `addr` in it.
+    fn region(&mut self, addr: u64, len: u64) -> Result<(&mut Kind, u64), MemoryFault> {
+        if !self.regions.get(self.last).is_some_and(|region| region.contains(addr, len)) {
+            self.last = self.regions.iter().position(|region| region.contains(addr, len)).ok_or(MemoryFault::AccessFault)?;
+        }
+        let region = &mut self.regions[self.last];
+        if matches!(region.kind, Kind::Device(_)) && addr & (len - 1) != 0 {
+            return Err(MemoryFault::Misaligned);
+        }
+        Ok((&mut region.kind, addr - region.base))
+    }
+
+    fn read<const L: usize>(&mut self, addr: u64, read: fn(&mut dyn Device, u64) -> Result<[u8; L], MemoryFault>) -> Result<[u8; L], MemoryFault> {
+        match self.region(addr, L as u64)? {
+            (Kind::Ram(memory), offset) | (Kind::Rom(memory), offset) => {
+                let offset = offset as usize;
+                Ok(memory[offset..offset + L].try_into().unwrap())
+            },
+            (Kind::Device(device), offset) => read(&mut **device, offset),
+        }
+    }
+
+    fn write<const L: usize>(&mut self, addr: u64, data: [u8; L], write: fn(&mut dyn Device, u64, [u8; L]) -> Result<(), MemoryFault>) -> Result<(), MemoryFault> {
+        match self.region(addr, L as u64)? {
+            (Kind::Ram(memory), offset) => {
+                let offset = offset as usize;
+                memory[offset..offset + L].copy_from_slice(&data);
+                Ok(())
+            },
+            (Kind::Rom(_), _) => Err(MemoryFault::AccessFault),
+            (Kind::Device(device), offset) => write(&mut **device, offset, data),
+        }
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<ADDR: Into<u64>> MemoryAccess<ADDR> for Bus {
+    fn get_8(&mut self, addr: ADDR) -> Result<u8, MemoryFault> {
+        self.read(addr.into(), |device, offset| Ok(device.read_8(offset)?.to_le_bytes())).map(u8::from_le_bytes)
+    }
+
+    fn get_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault> {
+        self.read(addr.into(), |device, offset| Ok(device.read_16(offset)?.to_le_bytes())).map(u16::from_le_bytes)
+    }
+
+    fn get_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.read(addr.into(), |device, offset| Ok(device.read_32(offset)?.to_le_bytes())).map(u32::from_le_bytes)
+    }
+
+    fn get_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault> {
+        self.read(addr.into(), |device, offset| Ok(device.read_64(offset)?.to_le_bytes())).map(u64::from_le_bytes)
+    }
+
+    fn set_8(&mut self, addr: ADDR, data: u8) -> Result<(), MemoryFault> {
+        self.write(addr.into(), data.to_le_bytes(), |device, offset, data| device.write_8(offset, u8::from_le_bytes(data)))
+    }
+
+    fn set_16(&mut self, addr: ADDR, data: u16) -> Result<(), MemoryFault> {
+        self.write(addr.into(), data.to_le_bytes(), |device, offset, data| device.write_16(offset, u16::from_le_bytes(data)))
+    }
+
+    fn set_32(&mut self, addr: ADDR, data: u32) -> Result<(), MemoryFault> {
+        self.write(addr.into(), data.to_le_bytes(), |device, offset, data| device.write_32(offset, u32::from_le_bytes(data)))
+    }
+
+    fn set_64(&mut self, addr: ADDR, data: u64) -> Result<(), MemoryFault> {
+        self.write(addr.into(), data.to_le_bytes(), |device, offset, data| device.write_64(offset, u64::from_le_bytes(data)))
+    }
+
+    /// 32-bits opcodes are only 16-bits aligned when the C extension is enabled, they are fetched as two half-words in this case.
+    fn get_opcode_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        let addr = addr.into();
+        if addr & 0b11 == 0 {
+            MemoryAccess::<u64>::get_32(self, addr)
+        } else {
+            Ok((MemoryAccess::<u64>::get_16(self, addr.wrapping_add(2))? as u32) << 16 | MemoryAccess::<u64>::get_16(self, addr)? as u32)
+        }
+    }
+}
+
+impl<ADDR: Into<u64>> ExecutionEnvironmentInterface<ADDR> for Bus {
+    fn trap(&mut self, trap: Traps, tval: ADDR, pc: ADDR) {
+        if let Some(handler) = &mut self.trap_handler {
+            handler(trap, tval.into(), pc.into());
+        }
+    }
+}
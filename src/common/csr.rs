@@ -0,0 +1,150 @@
+//! Control and status register numbers and names.
+
+// Unprivileged floating-point CSRs.
+pub const FFLAGS: u16 = 0x001;
+pub const FRM: u16 = 0x002;
+pub const FCSR: u16 = 0x003;
+
+// Unprivileged counters/timers.
+pub const CYCLE: u16 = 0xC00;
+pub const TIME: u16 = 0xC01;
+pub const INSTRET: u16 = 0xC02;
+pub const CYCLEH: u16 = 0xC80;
+pub const TIMEH: u16 = 0xC81;
+pub const INSTRETH: u16 = 0xC82;
+
+// Supervisor trap setup.
+pub const SSTATUS: u16 = 0x100;
+pub const SIE: u16 = 0x104;
+pub const STVEC: u16 = 0x105;
+pub const SCOUNTEREN: u16 = 0x106;
+
+// Supervisor trap handling.
+pub const SSCRATCH: u16 = 0x140;
+pub const SEPC: u16 = 0x141;
+pub const SCAUSE: u16 = 0x142;
+pub const STVAL: u16 = 0x143;
+pub const SIP: u16 = 0x144;
+
+// Supervisor protection and translation.
+pub const SATP: u16 = 0x180;
+
+// Machine information registers.
+pub const MVENDORID: u16 = 0xF11;
+pub const MARCHID: u16 = 0xF12;
+pub const MIMPID: u16 = 0xF13;
+pub const MHARTID: u16 = 0xF14;
+
+// Machine trap setup.
+pub const MSTATUS: u16 = 0x300;
+pub const MISA: u16 = 0x301;
+pub const MEDELEG: u16 = 0x302;
+pub const MIDELEG: u16 = 0x303;
+pub const MIE: u16 = 0x304;
+pub const MTVEC: u16 = 0x305;
+pub const MCOUNTEREN: u16 = 0x306;
+pub const MSTATUSH: u16 = 0x310;
+
+// Machine trap handling.
+pub const MSCRATCH: u16 = 0x340;
+pub const MEPC: u16 = 0x341;
+pub const MCAUSE: u16 = 0x342;
+pub const MTVAL: u16 = 0x343;
+pub const MIP: u16 = 0x344;
+
+// Machine memory protection.
+pub const PMPCFG0: u16 = 0x3A0;
+pub const PMPADDR0: u16 = 0x3B0;
+
+// Machine counters/timers.
+pub const MCYCLE: u16 = 0xB00;
+pub const MINSTRET: u16 = 0xB02;
+pub const MCYCLEH: u16 = 0xB80;
+pub const MINSTRETH: u16 = 0xB82;
+
+/// Returns true if the given CSR is read-only (`csr[11:10]` is `0b11`).
+pub fn is_read_only(csr: u16) -> bool {
+    csr >> 10 & 0b11 == 0b11
+}
+
+/// Returns the lowest privilege level that can access the given CSR (`csr[9:8]`).
+pub fn get_privilege_level(csr: u16) -> u8 {
+    (csr >> 8 & 0b11) as u8
+}
+
+const CSR_NAMES: [(u16, &str); 40] = [
+    (FFLAGS, "fflags"),
+    (FRM, "frm"),
+    (FCSR, "fcsr"),
+    (CYCLE, "cycle"),
+    (TIME, "time"),
+    (INSTRET, "instret"),
+    (CYCLEH, "cycleh"),
+    (TIMEH, "timeh"),
+    (INSTRETH, "instreth"),
+    (SSTATUS, "sstatus"),
+    (SIE, "sie"),
+    (STVEC, "stvec"),
+    (SCOUNTEREN, "scounteren"),
+    (SSCRATCH, "sscratch"),
+    (SEPC, "sepc"),
+    (SCAUSE, "scause"),
+    (STVAL, "stval"),
+    (SIP, "sip"),
+    (SATP, "satp"),
+    (MVENDORID, "mvendorid"),
+    (MARCHID, "marchid"),
+    (MIMPID, "mimpid"),
+    (MHARTID, "mhartid"),
+    (MSTATUS, "mstatus"),
+    (MISA, "misa"),
+    (MEDELEG, "medeleg"),
+    (MIDELEG, "mideleg"),
+    (MIE, "mie"),
+    (MTVEC, "mtvec"),
+    (MCOUNTEREN, "mcounteren"),
+    (MSTATUSH, "mstatush"),
+    (MSCRATCH, "mscratch"),
+    (MEPC, "mepc"),
+    (MCAUSE, "mcause"),
+    (MTVAL, "mtval"),
+    (MIP, "mip"),
+    (MCYCLE, "mcycle"),
+    (MINSTRET, "minstret"),
+    (MCYCLEH, "mcycleh"),
+    (MINSTRETH, "minstreth"),
+];
+
+/// Get the name of the given CSR, or its number in hexadecimal if it has no standard name.
+pub fn get_csr_name(csr: u16) -> String {
+    match CSR_NAMES.iter().find(|(num, _)| *num == csr) {
+        Some((_, name)) => String::from(*name),
+        None if (PMPCFG0..PMPCFG0 + 16).contains(&csr) => format!("pmpcfg{}", csr - PMPCFG0),
+        None if (PMPADDR0..PMPADDR0 + 64).contains(&csr) => format!("pmpaddr{}", csr - PMPADDR0),
+        None => format!("{:#X}", csr),
+    }
+}
+
+/// Get the number of the CSR with the given name, or `None` if the name is unknown.
+/// Numbers written in decimal or hexadecimal (`0x` prefix) are also accepted.
+pub fn get_csr_number(name: &str) -> Option<u16> {
+    if let Some((num, _)) = CSR_NAMES.iter().find(|(_, n)| *n == name) {
+        return Some(*num);
+    }
+
+    if let Some(Ok(n)) = name.strip_prefix("pmpcfg").map(str::parse::<u16>) {
+        return if n < 16 { Some(PMPCFG0 + n) } else { None };
+    }
+
+    if let Some(Ok(n)) = name.strip_prefix("pmpaddr").map(str::parse::<u16>) {
+        return if n < 64 { Some(PMPADDR0 + n) } else { None };
+    }
+
+    let csr = if let Some(hex) = name.strip_prefix("0x") {
+        u16::from_str_radix(hex, 16).ok()?
+    } else {
+        name.parse().ok()?
+    };
+
+    if csr <= 0xFFF { Some(csr) } else { None }
+}
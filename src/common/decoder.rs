@@ -12,6 +12,31 @@ pub trait Format<U: Unsigned<S>, S: Signed<U>> {
 
         Instruction::decode_type_i, Instruction::decode_type_r, Instruction::decode_type_i, Instruction::decode_type_i, Instruction::decode_type_s, Instruction::decode_type_i, Instruction::decode_type_r, Instruction::decode_type_i,
         Instruction::decode_type_r, Instruction::decode_type_i, Instruction::decode_type_r, Instruction::decode_type_r,
+
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_i, Instruction::decode_type_r4, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r4, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r4, Instruction::decode_type_r4, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_s,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_i, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r4, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r4, Instruction::decode_type_r, Instruction::decode_type_r4, Instruction::decode_type_r4, Instruction::decode_type_s, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r,
+        Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r, Instruction::decode_type_r,
+
+        Instruction::decode_type_csr, Instruction::decode_type_csr, Instruction::decode_type_csr, Instruction::decode_type_csr, Instruction::decode_type_csr, Instruction::decode_type_csr,
+
+        Instruction::decode_type_i,
+
+        Instruction::empty, Instruction::decode_type_r, Instruction::empty, Instruction::empty,
     ];
 }
 
@@ -22,24 +47,58 @@ impl ISA {
     const I32_BRANCH: [ISA; 8] = [ISA::BEQ, ISA::BNE, ISA::UNKNOWN, ISA::UNKNOWN, ISA::BLT, ISA::BGE, ISA::BLTU, ISA::BGEU];
     const I32_IMMEDIATE: [ISA; 8] = [ISA::ADDI, ISA::SLLI, ISA::SLTI, ISA::SLTIU, ISA::XORI, ISA::SRLI, ISA::ORI, ISA::ANDI];
     const I_LOAD: [ISA; 8] = [ISA::LB, ISA::LH, ISA::LW, ISA::LD, ISA::LBU, ISA::LHU, ISA::LWU, ISA::UNKNOWN];
-    const I_STORE: [ISA; 4] = [ISA::SB, ISA::SH, ISA::SW, ISA::SD];
+    const I_STORE: [ISA; 8] = [ISA::SB, ISA::SH, ISA::SW, ISA::SD, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN];
 
     const I64_IMMEDIATE: [ISA; 8] = [ISA::ADDIW, ISA::SLLIW, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::SRLIW, ISA::UNKNOWN, ISA::UNKNOWN];
     const I64_ARITHMETIC: [ISA; 8] = [ISA::ADDW, ISA::SLLW, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::SRLW, ISA::UNKNOWN, ISA::UNKNOWN];
 
-    fn get_immediate_32(opcode: u32) -> ISA {
-        if opcode & 0xFC00_707F == 0x4000_5013 {
-            ISA::SRAI
-        } else {
-            Self::I32_IMMEDIATE[opcode as usize >> 12 & 0b111]
+    const M32_ARITHMETIC: [ISA; 8] = [ISA::MUL, ISA::MULH, ISA::MULHSU, ISA::MULHU, ISA::DIV, ISA::DIVU, ISA::REM, ISA::REMU];
+    const M64_ARITHMETIC: [ISA; 8] = [ISA::MULW, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::DIVW, ISA::DIVUW, ISA::REMW, ISA::REMUW];
+
+    const A32_ATOMIC: [ISA; 32] = [
+        ISA::AMOADD_W, ISA::AMOSWAP_W, ISA::LR_W, ISA::SC_W,
+        ISA::AMOXOR_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOOR_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOAND_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMIN_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMAX_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMINU_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMAXU_W, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+    ];
+    const A64_ATOMIC: [ISA; 32] = [
+        ISA::AMOADD_D, ISA::AMOSWAP_D, ISA::LR_D, ISA::SC_D,
+        ISA::AMOXOR_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOOR_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOAND_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMIN_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMAX_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMINU_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+        ISA::AMOMAXU_D, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN,
+    ];
+
+    const I_FENCE: [ISA; 8] = [ISA::FENCE, ISA::FENCE_I, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN, ISA::UNKNOWN];
+
+    const ZICSR: [ISA; 8] = [ISA::UNKNOWN, ISA::CSRRW, ISA::CSRRS, ISA::CSRRC, ISA::UNKNOWN, ISA::CSRRWI, ISA::CSRRSI, ISA::CSRRCI];
+
+    /// Shift amounts are 5 bits on RV32 and 6 bits on RV64, the upper bits of the immediate must be zero.
+    fn get_immediate_32(opcode: u32, xlen: usize) -> ISA {
+        let funct = if xlen == 32 { opcode >> 25 } else { opcode >> 26 << 1 };
+        match opcode >> 12 & 0b111 {
+            0b001 if funct == 0 => ISA::SLLI,
+            0b101 if funct == 0 => ISA::SRLI,
+            0b101 if funct == 0b010_0000 => ISA::SRAI,
+            0b001 | 0b101 => ISA::UNKNOWN,
+            funct3 => Self::I32_IMMEDIATE[funct3 as usize],
         }
     }
 
     fn get_immediate_64(opcode: u32) -> ISA {
-        if opcode & 0xFE00_707F == 0x4000_501B {
-            ISA::SRAIW
-        } else {
-            Self::I64_IMMEDIATE[opcode as usize >> 12 & 0b111]
+        match (opcode >> 12 & 0b111, opcode >> 25) {
+            (0b001, 0) => ISA::SLLIW,
+            (0b101, 0) => ISA::SRLIW,
+            (0b101, 0b010_0000) => ISA::SRAIW,
+            (0b001, _) | (0b101, _) => ISA::UNKNOWN,
+            (funct3, _) => Self::I64_IMMEDIATE[funct3 as usize],
         }
     }
 
@@ -48,6 +107,8 @@ impl ISA {
             ISA::SUB
         } else if opcode & 0xFE00_707F == 0x4000_5033 {
             ISA::SRA
+        } else if opcode & 0xFE00_0000 == 0x0200_0000 {
+            Self::M32_ARITHMETIC[opcode as usize >> 12 & 0b111]
         } else if opcode & 0xFE00_0000 != 0 {
             ISA::UNKNOWN
         } else {
@@ -60,6 +121,8 @@ impl ISA {
             ISA::SUBW
         } else if opcode & 0xFE00_707F == 0x4000_503B {
             ISA::SRAW
+        } else if opcode & 0xFE00_0000 == 0x0200_0000 {
+            Self::M64_ARITHMETIC[opcode as usize >> 12 & 0b111]
         } else if opcode & 0xFE00_0000 != 0 {
             ISA::UNKNOWN
         } else {
@@ -67,22 +130,320 @@ impl ISA {
         }
     }
 
-    pub fn from_opcode_32(opcode: u32) -> ISA {
+    fn get_atomic(opcode: u32) -> ISA {
+        let inst = match opcode >> 12 & 0b111 {
+            0b010 => Self::A32_ATOMIC[opcode as usize >> 27],
+            0b011 => Self::A64_ATOMIC[opcode as usize >> 27],
+            _ => ISA::UNKNOWN,
+        };
+
+        if (inst == ISA::LR_W || inst == ISA::LR_D) && opcode >> 20 & 0b1_1111 != 0 {
+            ISA::UNKNOWN
+        } else {
+            inst
+        }
+    }
+
+    fn get_fp_load(opcode: u32) -> ISA {
+        match opcode >> 12 & 0b111 {
+            0b010 => ISA::FLW,
+            0b011 => ISA::FLD,
+            _ => ISA::UNKNOWN,
+        }
+    }
+
+    fn get_fp_store(opcode: u32) -> ISA {
+        match opcode >> 12 & 0b111 {
+            0b010 => ISA::FSW,
+            0b011 => ISA::FSD,
+            _ => ISA::UNKNOWN,
+        }
+    }
+
+    /// Fused multiply-add instructions. `fmt` selects the single or double-precision variant.
+    fn get_fp_fused(opcode: u32, single: ISA, double: ISA) -> ISA {
+        match opcode >> 25 & 0b11 {
+            0b00 => single,
+            0b01 => double,
+            _ => ISA::UNKNOWN,
+        }
+    }
+
+    fn get_fp(opcode: u32) -> ISA {
+        let funct3 = opcode >> 12 & 0b111;
+        let rs2 = opcode >> 20 & 0b1_1111;
+        match (opcode >> 25, funct3, rs2) {
+            (0b000_0000, _, _) => ISA::FADD_S,
+            (0b000_0001, _, _) => ISA::FADD_D,
+            (0b000_0100, _, _) => ISA::FSUB_S,
+            (0b000_0101, _, _) => ISA::FSUB_D,
+            (0b000_1000, _, _) => ISA::FMUL_S,
+            (0b000_1001, _, _) => ISA::FMUL_D,
+            (0b000_1100, _, _) => ISA::FDIV_S,
+            (0b000_1101, _, _) => ISA::FDIV_D,
+            (0b010_1100, _, 0) => ISA::FSQRT_S,
+            (0b010_1101, _, 0) => ISA::FSQRT_D,
+            (0b001_0000, 0b000, _) => ISA::FSGNJ_S,
+            (0b001_0000, 0b001, _) => ISA::FSGNJN_S,
+            (0b001_0000, 0b010, _) => ISA::FSGNJX_S,
+            (0b001_0001, 0b000, _) => ISA::FSGNJ_D,
+            (0b001_0001, 0b001, _) => ISA::FSGNJN_D,
+            (0b001_0001, 0b010, _) => ISA::FSGNJX_D,
+            (0b001_0100, 0b000, _) => ISA::FMIN_S,
+            (0b001_0100, 0b001, _) => ISA::FMAX_S,
+            (0b001_0101, 0b000, _) => ISA::FMIN_D,
+            (0b001_0101, 0b001, _) => ISA::FMAX_D,
+            (0b010_0000, _, 1) => ISA::FCVT_S_D,
+            (0b010_0001, _, 0) => ISA::FCVT_D_S,
+            (0b101_0000, 0b000, _) => ISA::FLE_S,
+            (0b101_0000, 0b001, _) => ISA::FLT_S,
+            (0b101_0000, 0b010, _) => ISA::FEQ_S,
+            (0b101_0001, 0b000, _) => ISA::FLE_D,
+            (0b101_0001, 0b001, _) => ISA::FLT_D,
+            (0b101_0001, 0b010, _) => ISA::FEQ_D,
+            (0b110_0000, _, 0) => ISA::FCVT_W_S,
+            (0b110_0000, _, 1) => ISA::FCVT_WU_S,
+            (0b110_0000, _, 2) => ISA::FCVT_L_S,
+            (0b110_0000, _, 3) => ISA::FCVT_LU_S,
+            (0b110_0001, _, 0) => ISA::FCVT_W_D,
+            (0b110_0001, _, 1) => ISA::FCVT_WU_D,
+            (0b110_0001, _, 2) => ISA::FCVT_L_D,
+            (0b110_0001, _, 3) => ISA::FCVT_LU_D,
+            (0b110_1000, _, 0) => ISA::FCVT_S_W,
+            (0b110_1000, _, 1) => ISA::FCVT_S_WU,
+            (0b110_1000, _, 2) => ISA::FCVT_S_L,
+            (0b110_1000, _, 3) => ISA::FCVT_S_LU,
+            (0b110_1001, _, 0) => ISA::FCVT_D_W,
+            (0b110_1001, _, 1) => ISA::FCVT_D_WU,
+            (0b110_1001, _, 2) => ISA::FCVT_D_L,
+            (0b110_1001, _, 3) => ISA::FCVT_D_LU,
+            (0b111_0000, 0b000, 0) => ISA::FMV_X_W,
+            (0b111_0000, 0b001, 0) => ISA::FCLASS_S,
+            (0b111_0001, 0b000, 0) => ISA::FMV_X_D,
+            (0b111_0001, 0b001, 0) => ISA::FCLASS_D,
+            (0b111_1000, 0b000, 0) => ISA::FMV_W_X,
+            (0b111_1001, 0b000, 0) => ISA::FMV_D_X,
+            _ => ISA::UNKNOWN,
+        }
+    }
+
+    fn get_system(opcode: u32) -> ISA {
+        if opcode == 0x0000_0073 {
+            ISA::ECALL
+        } else if opcode == 0x0010_0073 {
+            ISA::EBREAK
+        } else if opcode == 0x3020_0073 {
+            ISA::MRET
+        } else if opcode == 0x1020_0073 {
+            ISA::SRET
+        } else if opcode == 0x1050_0073 {
+            ISA::WFI
+        } else if opcode & 0xFE00_7FFF == 0x1200_0073 {
+            ISA::SFENCE_VMA
+        } else {
+            Self::ZICSR[opcode as usize >> 12 & 0b111]
+        }
+    }
+
+    /// FENCE only allows `fm = 0000`, and `fm = 1000` with `pred = succ = RW` (FENCE.TSO).
+    fn get_fence(opcode: u32) -> ISA {
+        let inst = Self::I_FENCE[opcode as usize >> 12 & 0b111];
+        if inst == ISA::FENCE && opcode >> 28 != 0 && opcode >> 20 != 0x833 {
+            ISA::UNKNOWN
+        } else {
+            inst
+        }
+    }
+
+    fn get_jalr(opcode: u32) -> ISA {
+        if opcode >> 12 & 0b111 == 0 {
+            ISA::JALR
+        } else {
+            ISA::UNKNOWN
+        }
+    }
+
+    /// Returns true if the instruction only exists on RV64.
+    pub fn is_rv64_only(self) -> bool {
+        matches!(self,
+            ISA::ADDIW | ISA::ADDW | ISA::LD | ISA::LWU | ISA::SD | ISA::SLLIW | ISA::SLLW |
+            ISA::SRAIW | ISA::SRAW | ISA::SRLIW | ISA::SRLW | ISA::SUBW |
+            ISA::DIVUW | ISA::DIVW | ISA::MULW | ISA::REMUW | ISA::REMW |
+            ISA::AMOADD_D | ISA::AMOAND_D | ISA::AMOMAX_D | ISA::AMOMAXU_D | ISA::AMOMIN_D | ISA::AMOMINU_D |
+            ISA::AMOOR_D | ISA::AMOSWAP_D | ISA::AMOXOR_D | ISA::LR_D | ISA::SC_D |
+            ISA::FCVT_L_S | ISA::FCVT_LU_S | ISA::FCVT_S_L | ISA::FCVT_S_LU |
+            ISA::FCVT_D_L | ISA::FCVT_D_LU | ISA::FCVT_L_D | ISA::FCVT_LU_D | ISA::FMV_D_X | ISA::FMV_X_D)
+    }
+
+    /// Decodes a 32-bit instruction for the given XLEN (32 or 64).
+    /// Reserved encodings and instructions that are not defined on `xlen` return [`ISA::UNKNOWN`].
+    pub fn from_opcode_32(opcode: u32, xlen: usize) -> ISA {
+        let inst = Self::decode_32(opcode, xlen);
+        if xlen == 32 && inst.is_rv64_only() {
+            ISA::UNKNOWN
+        } else {
+            inst
+        }
+    }
+
+    fn decode_32(opcode: u32, xlen: usize) -> ISA {
         match opcode & 0b111_1111 {
             0b000_0011 => Self::I_LOAD[opcode as usize >> 12 & 0b111],
-            0b000_1111 => ISA::FENCE,
-            0b001_0011 => Self::get_immediate_32(opcode),
+            0b000_0111 => Self::get_fp_load(opcode),
+            0b000_1111 => Self::get_fence(opcode),
+            0b001_0011 => Self::get_immediate_32(opcode, xlen),
             0b001_0111 => ISA::AUIPC,
             0b001_1011 => Self::get_immediate_64(opcode),
-            0b010_0011 => Self::I_STORE[opcode as usize >> 12 & 0b11],
+            0b010_0011 => Self::I_STORE[opcode as usize >> 12 & 0b111],
+            0b010_0111 => Self::get_fp_store(opcode),
+            0b010_1111 => Self::get_atomic(opcode),
             0b011_0011 => Self::get_arithmetic_32(opcode),
             0b011_0111 => ISA::LUI,
             0b011_1011 => Self::get_arithmetic_64(opcode),
+            0b100_0011 => Self::get_fp_fused(opcode, ISA::FMADD_S, ISA::FMADD_D),
+            0b100_0111 => Self::get_fp_fused(opcode, ISA::FMSUB_S, ISA::FMSUB_D),
+            0b100_1011 => Self::get_fp_fused(opcode, ISA::FNMSUB_S, ISA::FNMSUB_D),
+            0b100_1111 => Self::get_fp_fused(opcode, ISA::FNMADD_S, ISA::FNMADD_D),
+            0b101_0011 => Self::get_fp(opcode),
             0b110_0011 => Self::I32_BRANCH[opcode as usize >> 12 & 0b111],
-            0b110_0111 => ISA::JALR,
+            0b110_0111 => Self::get_jalr(opcode),
             0b110_1111 => ISA::JAL,
-            0b111_0011 => if opcode == 0x0000_0073 { ISA::ECALL } else if opcode == 0x0010_0073 { ISA::EBREAK } else { ISA::UNKNOWN },
+            0b111_0011 => Self::get_system(opcode),
             _ => ISA::UNKNOWN,
         }
     }
 }
+
+/// Expands a compressed instruction into the equivalent 32-bit instruction for the given XLEN (32 or 64).
+///
+/// Returns `None` for reserved encodings and for encodings that are not defined on `xlen`.
+pub fn expand_compressed(opcode: u16, xlen: usize) -> Option<u32> {
+    let c = opcode as u32;
+    let rd = (c >> 7 & 0b1_1111) as u8;
+    let rs2 = (c >> 2 & 0b1_1111) as u8;
+    let rd_ = 8 + (c >> 2 & 0b111) as u8;
+    let rs1_ = 8 + (c >> 7 & 0b111) as u8;
+    let imm6 = ((c >> 7 & 0x20 | c >> 2 & 0x1F) << 26) as i32 >> 26;
+    let shamt = c >> 7 & 0x20 | c >> 2 & 0x1F;
+    let rv32 = xlen == 32;
+
+    let expanded = match (c & 0b11, c >> 13) {
+        // Quadrant 0
+        (0b00, 0b000) => {
+            let imm = c >> 7 & 0x30 | c >> 1 & 0x3C0 | c >> 4 & 0x4 | c >> 2 & 0x8;
+            if imm == 0 {
+                return None;
+            }
+            encode_type_i(0b0010011, rd_, 0, 2, imm)
+        },
+        (0b00, 0b001) => encode_type_i(0b0000111, rd_, 3, rs1_, c >> 7 & 0x38 | c << 1 & 0xC0),
+        (0b00, 0b010) => encode_type_i(0b0000011, rd_, 2, rs1_, c >> 7 & 0x38 | c >> 4 & 0x4 | c << 1 & 0x40),
+        (0b00, 0b011) if rv32 => encode_type_i(0b0000111, rd_, 2, rs1_, c >> 7 & 0x38 | c >> 4 & 0x4 | c << 1 & 0x40),
+        (0b00, 0b011) => encode_type_i(0b0000011, rd_, 3, rs1_, c >> 7 & 0x38 | c << 1 & 0xC0),
+        (0b00, 0b101) => encode_type_s(0b0100111, 3, rs1_, rd_, c >> 7 & 0x38 | c << 1 & 0xC0),
+        (0b00, 0b110) => encode_type_s(0b0100011, 2, rs1_, rd_, c >> 7 & 0x38 | c >> 4 & 0x4 | c << 1 & 0x40),
+        (0b00, 0b111) if rv32 => encode_type_s(0b0100111, 2, rs1_, rd_, c >> 7 & 0x38 | c >> 4 & 0x4 | c << 1 & 0x40),
+        (0b00, 0b111) => encode_type_s(0b0100011, 3, rs1_, rd_, c >> 7 & 0x38 | c << 1 & 0xC0),
+
+        // Quadrant 1
+        (0b01, 0b000) => encode_type_i(0b0010011, rd, 0, rd, imm6 as u32),
+        (0b01, 0b001) if rv32 => encode_type_j(0b1101111, 1, compressed_jump_offset(c)),
+        (0b01, 0b001) => {
+            if rd == 0 {
+                return None;
+            }
+            encode_type_i(0b0011011, rd, 0, rd, imm6 as u32)
+        },
+        (0b01, 0b010) => encode_type_i(0b0010011, rd, 0, 0, imm6 as u32),
+        (0b01, 0b011) if rd == 2 => {
+            let imm = ((c >> 3 & 0x200 | c >> 2 & 0x10 | c << 1 & 0x40 | c << 4 & 0x180 | c << 3 & 0x20) << 22) as i32 >> 22;
+            if imm == 0 {
+                return None;
+            }
+            encode_type_i(0b0010011, 2, 0, 2, imm as u32)
+        },
+        (0b01, 0b011) => {
+            if imm6 == 0 {
+                return None;
+            }
+            encode_type_u(0b0110111, rd, (imm6 as u32) << 12)
+        },
+        (0b01, 0b100) => match (c >> 10 & 0b11, c >> 12 & 1, c >> 5 & 0b11) {
+            (0b00, _, _) if rv32 && shamt & 0x20 != 0 => return None,
+            (0b00, _, _) => encode_type_i(0b0010011, rs1_, 5, rs1_, shamt),
+            (0b01, _, _) if rv32 && shamt & 0x20 != 0 => return None,
+            (0b01, _, _) => encode_type_i(0b0010011, rs1_, 5, rs1_, 0x400 | shamt),
+            (0b10, _, _) => encode_type_i(0b0010011, rs1_, 7, rs1_, imm6 as u32),
+            (0b11, 0, 0b00) => encode_type_r(0b0110011, rs1_, 0, rs1_, rd_, 0b0100000),
+            (0b11, 0, 0b01) => encode_type_r(0b0110011, rs1_, 4, rs1_, rd_, 0),
+            (0b11, 0, 0b10) => encode_type_r(0b0110011, rs1_, 6, rs1_, rd_, 0),
+            (0b11, 0, 0b11) => encode_type_r(0b0110011, rs1_, 7, rs1_, rd_, 0),
+            (0b11, 1, 0b00) if !rv32 => encode_type_r(0b0111011, rs1_, 0, rs1_, rd_, 0b0100000),
+            (0b11, 1, 0b01) if !rv32 => encode_type_r(0b0111011, rs1_, 0, rs1_, rd_, 0),
+            _ => return None,
+        },
+        (0b01, 0b101) => encode_type_j(0b1101111, 0, compressed_jump_offset(c)),
+        (0b01, 0b110) => encode_type_b(0b1100011, 0, rs1_, 0, compressed_branch_offset(c)),
+        (0b01, 0b111) => encode_type_b(0b1100011, 1, rs1_, 0, compressed_branch_offset(c)),
+
+        // Quadrant 2
+        (0b10, 0b000) if rv32 && shamt & 0x20 != 0 => return None,
+        (0b10, 0b000) => encode_type_i(0b0010011, rd, 1, rd, shamt),
+        (0b10, 0b001) => encode_type_i(0b0000111, rd, 3, 2, c >> 7 & 0x20 | c >> 2 & 0x18 | c << 4 & 0x1C0),
+        (0b10, 0b010) => {
+            if rd == 0 {
+                return None;
+            }
+            encode_type_i(0b0000011, rd, 2, 2, c >> 7 & 0x20 | c >> 2 & 0x1C | c << 4 & 0xC0)
+        },
+        (0b10, 0b011) if rv32 => encode_type_i(0b0000111, rd, 2, 2, c >> 7 & 0x20 | c >> 2 & 0x1C | c << 4 & 0xC0),
+        (0b10, 0b011) => {
+            if rd == 0 {
+                return None;
+            }
+            encode_type_i(0b0000011, rd, 3, 2, c >> 7 & 0x20 | c >> 2 & 0x18 | c << 4 & 0x1C0)
+        },
+        (0b10, 0b100) => match (c >> 12 & 1, rd, rs2) {
+            (0, 0, 0) => return None,
+            (0, _, 0) => encode_type_i(0b1100111, 0, 0, rd, 0),
+            (0, _, _) => encode_type_r(0b0110011, rd, 0, 0, rs2, 0),
+            (1, 0, 0) => 0x0010_0073,
+            (1, _, 0) => encode_type_i(0b1100111, 1, 0, rd, 0),
+            _ => encode_type_r(0b0110011, rd, 0, rd, rs2, 0),
+        },
+        (0b10, 0b101) => encode_type_s(0b0100111, 3, 2, rs2, c >> 7 & 0x38 | c >> 1 & 0x1C0),
+        (0b10, 0b110) => encode_type_s(0b0100011, 2, 2, rs2, c >> 7 & 0x3C | c >> 1 & 0xC0),
+        (0b10, 0b111) if rv32 => encode_type_s(0b0100111, 2, 2, rs2, c >> 7 & 0x3C | c >> 1 & 0xC0),
+        (0b10, 0b111) => encode_type_s(0b0100011, 3, 2, rs2, c >> 7 & 0x38 | c >> 1 & 0x1C0),
+        _ => return None,
+    };
+
+    Some(expanded)
+}
+
+/// Returns true if the compressed instruction is a HINT whose 32-bit expansion is not one:
+/// C.ADDI with a zero immediate, and C.SLLI, C.SRLI and C.SRAI with a zero shift amount.
+pub fn is_compressed_hint(opcode: u16) -> bool {
+    let c = opcode as u32;
+    let rd = c >> 7 & 0b1_1111;
+    let imm6 = c >> 7 & 0x20 | c >> 2 & 0x1F;
+
+    match (c & 0b11, c >> 13) {
+        (0b01, 0b000) => rd != 0 && imm6 == 0,
+        (0b01, 0b100) => c >> 11 & 1 == 0 && imm6 == 0,
+        (0b10, 0b000) => imm6 == 0,
+        _ => false,
+    }
+}
+
+/// Immediate of the CJ format (C.J and C.JAL), sign-extended.
+fn compressed_jump_offset(c: u32) -> u32 {
+    let imm = c >> 1 & 0xB40 | c >> 7 & 0x10 | c << 2 & 0x400 | c << 1 & 0x80 | c >> 2 & 0xE | c << 3 & 0x20;
+    ((imm << 20) as i32 >> 20) as u32
+}
+
+/// Immediate of the CB format branches (C.BEQZ and C.BNEZ), sign-extended.
+fn compressed_branch_offset(c: u32) -> u32 {
+    let imm = c >> 4 & 0x100 | c >> 7 & 0x18 | c << 1 & 0xC0 | c >> 2 & 0x6 | c << 3 & 0x20;
+    ((imm << 23) as i32 >> 23) as u32
+}
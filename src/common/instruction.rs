@@ -1,13 +1,15 @@
-use crate::common::{isa::*, decoder::*, types::*};
+use crate::common::{get_instruction_length, isa::*, decoder::*, types::*};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Instruction<U: Unsigned<S>, S: Signed<U>> {
     pub inst: ISA,
     pub pc: U,
+    pub opcode: u32,
 
     pub rd: u8,
     pub rs1: u8,
     pub rs2: u8,
+    pub rs3: u8,
     pub imm: S,
 }
 
@@ -15,53 +17,164 @@ pub type Instruction32 = Instruction<u32, i32>;
 pub type Instruction64 = Instruction<u64, i64>;
 
 impl<U: Unsigned<S>, S: Signed<U>> Instruction<U, S> {
+    /// Decodes a 32-bit instruction. The XLEN is the width of `U`.
     pub fn from_opcode_32(pc: U, opcode: u32) -> Instruction<U, S> {
-        let isa = ISA::from_opcode_32(opcode);
+        let isa = ISA::from_opcode_32(opcode, std::mem::size_of::<U>() * 8);
         ISA::FORMAT[isa as usize](isa, pc, opcode)
     }
 
-    pub fn empty(inst: ISA, pc: U, _: u32) -> Self {
-        Self { inst, pc, rd: 0, rs1: 0, rs2: 0, imm: 0.into() }
+    /// Decodes a compressed instruction from its 32-bit expansion. `opcode` keeps the 16-bit encoding.
+    pub fn from_opcode_16(pc: U, opcode: u16) -> Instruction<U, S> {
+        match expand_compressed(opcode, std::mem::size_of::<U>() * 8) {
+            Some(expanded) => Instruction { opcode: opcode as u32, ..Self::from_opcode_32(pc, expanded) },
+            None => Self::empty(ISA::UNKNOWN, pc, opcode as u32),
+        }
+    }
+
+    /// Returns true if the instruction is a HINT: an encoding without architectural effect
+    /// that is reserved for performance or debugging hints, and executed as a no-op.
+    pub fn is_hint(&self) -> bool {
+        if get_instruction_length(self.opcode as u16) == 2 && is_compressed_hint(self.opcode as u16) {
+            return true;
+        }
+
+        match self.inst {
+            ISA::ADDI => self.rd == 0 && (self.rs1 != 0 || self.imm != 0.into()),
+            ISA::LUI | ISA::AUIPC | ISA::ANDI | ISA::ORI | ISA::XORI | ISA::SLTI | ISA::SLTIU | ISA::SLLI | ISA::SRLI | ISA::SRAI |
+            ISA::ADD | ISA::SUB | ISA::AND | ISA::OR | ISA::XOR | ISA::SLL | ISA::SRL | ISA::SRA | ISA::SLT | ISA::SLTU |
+            ISA::ADDIW | ISA::SLLIW | ISA::SRLIW | ISA::SRAIW | ISA::ADDW | ISA::SUBW | ISA::SLLW | ISA::SRLW | ISA::SRAW => self.rd == 0,
+            // FENCE with fm = 0 and an empty predecessor or successor set, PAUSE included.
+            ISA::FENCE => self.opcode >> 28 == 0 && (self.opcode >> 24 & 0xF == 0 || self.opcode >> 20 & 0xF == 0),
+            _ => false,
+        }
+    }
+
+    /// Returns the highest integer register number used by the instruction, checked against the 16 registers of RVE harts.
+    /// The reserved `rd` and `rs1` fields of FENCE and FENCE.I are ignored.
+    pub fn max_x_register(&self) -> u8 {
+        match self.inst {
+            ISA::FENCE | ISA::FENCE_I => 0,
+            ISA::FLW | ISA::FLD | ISA::FSW | ISA::FSD |
+            ISA::FCVT_S_W | ISA::FCVT_S_WU | ISA::FCVT_S_L | ISA::FCVT_S_LU | ISA::FMV_W_X |
+            ISA::FCVT_D_W | ISA::FCVT_D_WU | ISA::FCVT_D_L | ISA::FCVT_D_LU | ISA::FMV_D_X => self.rs1,
+            ISA::FCVT_W_S | ISA::FCVT_WU_S | ISA::FCVT_L_S | ISA::FCVT_LU_S | ISA::FMV_X_W | ISA::FCLASS_S |
+            ISA::FEQ_S | ISA::FLT_S | ISA::FLE_S |
+            ISA::FCVT_W_D | ISA::FCVT_WU_D | ISA::FCVT_L_D | ISA::FCVT_LU_D | ISA::FMV_X_D | ISA::FCLASS_D |
+            ISA::FEQ_D | ISA::FLT_D | ISA::FLE_D |
+            ISA::CSRRWI | ISA::CSRRSI | ISA::CSRRCI => self.rd,
+            inst if Self::is_float(inst) => 0,
+            _ => self.rd.max(self.rs1).max(self.rs2),
+        }
+    }
+
+    /// Returns true if the instruction writes the integer register `rd` when it does not trap.
+    pub fn writes_x_register(&self) -> bool {
+        match self.inst {
+            ISA::UNKNOWN | ISA::BEQ | ISA::BGE | ISA::BGEU | ISA::BLT | ISA::BLTU | ISA::BNE |
+            ISA::SB | ISA::SH | ISA::SW | ISA::SD | ISA::FENCE | ISA::FENCE_I |
+            ISA::ECALL | ISA::EBREAK | ISA::MRET | ISA::SRET | ISA::WFI | ISA::SFENCE_VMA => false,
+            ISA::FCVT_W_S | ISA::FCVT_WU_S | ISA::FCVT_L_S | ISA::FCVT_LU_S | ISA::FMV_X_W | ISA::FCLASS_S |
+            ISA::FEQ_S | ISA::FLT_S | ISA::FLE_S |
+            ISA::FCVT_W_D | ISA::FCVT_WU_D | ISA::FCVT_L_D | ISA::FCVT_LU_D | ISA::FMV_X_D | ISA::FCLASS_D |
+            ISA::FEQ_D | ISA::FLT_D | ISA::FLE_D => true,
+            inst => !Self::is_float(inst),
+        }
+    }
+
+    /// Returns true if the instruction writes the floating-point register `rd` when it does not trap.
+    pub fn writes_f_register(&self) -> bool {
+        match self.inst {
+            ISA::FSW | ISA::FSD => false,
+            inst => Self::is_float(inst) && !self.writes_x_register(),
+        }
+    }
+
+    /// Returns the integer registers `rs1` and `rs2` read by the instruction, 0 for the operands it does not have
+    /// or that are not integer registers.
+    pub fn x_sources(&self) -> (u8, u8) {
+        match self.inst {
+            ISA::UNKNOWN | ISA::FENCE | ISA::FENCE_I | ISA::CSRRWI | ISA::CSRRSI | ISA::CSRRCI => (0, 0),
+            ISA::FLW | ISA::FLD | ISA::FSW | ISA::FSD |
+            ISA::FCVT_S_W | ISA::FCVT_S_WU | ISA::FCVT_S_L | ISA::FCVT_S_LU | ISA::FMV_W_X |
+            ISA::FCVT_D_W | ISA::FCVT_D_WU | ISA::FCVT_D_L | ISA::FCVT_D_LU | ISA::FMV_D_X => (self.rs1, 0),
+            inst if Self::is_float(inst) => (0, 0),
+            _ => (self.rs1, self.rs2),
+        }
+    }
+
+    /// Returns true for the instructions of the F and D extensions.
+    fn is_float(inst: ISA) -> bool {
+        matches!(inst,
+            ISA::FADD_S | ISA::FCLASS_S | ISA::FCVT_S_W | ISA::FCVT_S_WU | ISA::FCVT_W_S | ISA::FCVT_WU_S |
+            ISA::FDIV_S | ISA::FEQ_S | ISA::FLE_S | ISA::FLT_S | ISA::FLW | ISA::FMADD_S | ISA::FMAX_S | ISA::FMIN_S |
+            ISA::FMSUB_S | ISA::FMUL_S | ISA::FMV_W_X | ISA::FMV_X_W | ISA::FNMADD_S | ISA::FNMSUB_S |
+            ISA::FSGNJ_S | ISA::FSGNJN_S | ISA::FSGNJX_S | ISA::FSQRT_S | ISA::FSUB_S | ISA::FSW |
+            ISA::FCVT_L_S | ISA::FCVT_LU_S | ISA::FCVT_S_L | ISA::FCVT_S_LU |
+            ISA::FADD_D | ISA::FCLASS_D | ISA::FCVT_D_S | ISA::FCVT_D_W | ISA::FCVT_D_WU | ISA::FCVT_S_D |
+            ISA::FCVT_W_D | ISA::FCVT_WU_D | ISA::FDIV_D | ISA::FEQ_D | ISA::FLD | ISA::FLE_D | ISA::FLT_D |
+            ISA::FMADD_D | ISA::FMAX_D | ISA::FMIN_D | ISA::FMSUB_D | ISA::FMUL_D | ISA::FNMADD_D | ISA::FNMSUB_D |
+            ISA::FSD | ISA::FSGNJ_D | ISA::FSGNJN_D | ISA::FSGNJX_D | ISA::FSQRT_D | ISA::FSUB_D |
+            ISA::FCVT_D_L | ISA::FCVT_D_LU | ISA::FCVT_L_D | ISA::FCVT_LU_D | ISA::FMV_D_X | ISA::FMV_X_D)
+    }
+
+    pub fn empty(inst: ISA, pc: U, opcode: u32) -> Self {
+        Self { inst, pc, opcode, rd: 0, rs1: 0, rs2: 0, rs3: 0, imm: 0.into() }
     }
 
     pub fn decode_type_r(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rd = (opcode >> 7 & 0b1_1111) as u8;
         let rs1 = (opcode >> 15 & 0b1_1111) as u8;
         let rs2 = (opcode >> 20 & 0b1_1111) as u8;
-        Instruction { inst, pc, rd, rs1, rs2, imm: 0.into() }
+        Instruction { inst, pc, opcode, rd, rs1, rs2, rs3: 0, imm: 0.into() }
+    }
+
+    pub fn decode_type_r4(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
+        let rd = (opcode >> 7 & 0b1_1111) as u8;
+        let rs1 = (opcode >> 15 & 0b1_1111) as u8;
+        let rs2 = (opcode >> 20 & 0b1_1111) as u8;
+        let rs3 = (opcode >> 27) as u8;
+        Instruction { inst, pc, opcode, rd, rs1, rs2, rs3, imm: 0.into() }
     }
 
     pub fn decode_type_i(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rd = (opcode >> 7 & 0b1_1111) as u8;
         let rs1 = (opcode >> 15 & 0b1_1111) as u8;
         let imm = opcode as i32 >> 20;
-        Instruction { inst, pc, rd, rs1, rs2: 0, imm: imm.into() }
+        Instruction { inst, pc, opcode, rd, rs1, rs2: 0, rs3: 0, imm: imm.into() }
+    }
+
+    /// Same as type I, except that `imm` is the zero-extended CSR number.
+    pub fn decode_type_csr(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
+        let rd = (opcode >> 7 & 0b1_1111) as u8;
+        let rs1 = (opcode >> 15 & 0b1_1111) as u8;
+        let imm = (opcode >> 20) as i32;
+        Instruction { inst, pc, opcode, rd, rs1, rs2: 0, rs3: 0, imm: imm.into() }
     }
 
     pub fn decode_type_s(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rs1 = (opcode >> 15 & 0b1_1111) as u8;
         let rs2 = (opcode >> 20 & 0b1_1111) as u8;
         let imm = opcode as i32 >> 20 & 0xFFFF_FFE0 | opcode as i32 >> 7 & 0b1_1111;
-        Instruction { inst, pc, rd: 0, rs1, rs2, imm: imm.into() }
+        Instruction { inst, pc, opcode, rd: 0, rs1, rs2, rs3: 0, imm: imm.into() }
     }
 
     pub fn decode_type_b(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rs1 = (opcode >> 15 & 0b1_1111) as u8;
         let rs2 = (opcode >> 20 & 0b1_1111) as u8;
         let imm = opcode as i32 >> 19 & 0xFFFF_F000 | (opcode as i32) << 4 & 0x0800 | opcode as i32 >> 20 & 0x07E0 | opcode as i32 >> 7 & 0x001E;
-        Instruction { inst, pc, rd: 0, rs1, rs2, imm: imm.into() }
+        Instruction { inst, pc, opcode, rd: 0, rs1, rs2, rs3: 0, imm: imm.into() }
     }
 
     pub fn decode_type_u(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rd = (opcode >> 7 & 0b1_1111) as u8;
         let imm = opcode as i32 & 0xFFFF_F000;
-        Instruction { inst, pc, rd, rs1: 0, rs2: 0, imm: imm.into() }
+        Instruction { inst, pc, opcode, rd, rs1: 0, rs2: 0, rs3: 0, imm: imm.into() }
     }
 
     pub fn decode_type_j(inst: ISA, pc: U, opcode: u32) -> Instruction<U, S> {
         let rd = (opcode >> 7 & 0b1_1111) as u8;
         let imm = opcode as i32 >> 11 & 0xFFF0_0000 | opcode as i32 & 0xF_F000 | opcode as i32 >> 9 & 0x0800 | opcode as i32 >> 20 & 0x7FE;
-        Instruction { inst, pc, rd, rs1: 0, rs2: 0, imm: imm.into() }
+        Instruction { inst, pc, opcode, rd, rs1: 0, rs2: 0, rs3: 0, imm: imm.into() }
     }
 }
 
@@ -70,6 +183,11 @@ pub fn encode_type_r(opcode: u8, rd: u8, funct3: u8, rs1: u8, rs2: u8, funct7: u
     (funct3 as u32) << 12 & 0x7000 | (rd as u32) << 7 & 0xF80 | (opcode as u32) & 0x7F
 }
 
+pub fn encode_type_r4(opcode: u8, rd: u8, funct3: u8, rs1: u8, rs2: u8, rs3: u8, funct2: u8) -> u32 {
+    (rs3 as u32) << 27 & 0xF800_0000 | (funct2 as u32) << 25 & 0x0600_0000 | (rs2 as u32) << 20 & 0x1F0_0000 | (rs1 as u32) << 15 & 0xF_8000 |
+    (funct3 as u32) << 12 & 0x7000 | (rd as u32) << 7 & 0xF80 | (opcode as u32) & 0x7F
+}
+
 pub fn encode_type_i(opcode: u8, rd: u8, funct3: u8, rs1: u8, imm: u32) -> u32 {
     imm << 20 & 0xFFF0_0000 | (rs1 as u32) << 15 & 0x000F_8000 | (funct3 as u32) << 12 & 0x7000 | (rd as u32) << 7 & 0xF80 | opcode as u32 & 0x7F
 }
@@ -91,3 +209,28 @@ pub fn encode_type_u(opcode: u8, rd: u8, imm: u32) -> u32 {
 pub fn encode_type_j(opcode: u8, rd: u8, imm: u32) -> u32 {
     (imm << 11 & 0x8000_0000) | (imm << 20 & 0x7FE0_0000) | (imm << 9 & 0x0010_0000) | (imm & 0x000F_F000) | (rd as u32) << 7 & 0x0F80 | opcode as u32 & 0x7F
 }
+
+pub fn encode_type_cr(opcode: u8, funct4: u8, rd: u8, rs2: u8) -> u16 {
+    (funct4 as u16) << 12 | (rd as u16) << 7 & 0xF80 | (rs2 as u16) << 2 & 0x7C | opcode as u16 & 0b11
+}
+
+/// `imm` is the 6-bits immediate, `imm[5]` is placed in bit 12 and `imm[4:0]` in bits 6:2.
+pub fn encode_type_ci(opcode: u8, funct3: u8, rd: u8, imm: u32) -> u16 {
+    ((funct3 as u32) << 13 | imm << 7 & 0x1000 | (rd as u32) << 7 & 0xF80 | imm << 2 & 0x7C | opcode as u32 & 0b11) as u16
+}
+
+/// `rd` and `rs2` are full register numbers (8 to 15).
+pub fn encode_type_ca(opcode: u8, funct6: u8, rd: u8, funct2: u8, rs2: u8) -> u16 {
+    (funct6 as u16) << 10 | (rd as u16 & 0b111) << 7 | (funct2 as u16 & 0b11) << 5 | (rs2 as u16 & 0b111) << 2 | opcode as u16 & 0b11
+}
+
+/// Branch format of C.BEQZ and C.BNEZ. `rs1` is a full register number (8 to 15).
+pub fn encode_type_cb(opcode: u8, funct3: u8, rs1: u8, imm: u32) -> u16 {
+    ((funct3 as u32) << 13 | imm << 4 & 0x1000 | imm << 7 & 0xC00 | (rs1 as u32 & 0b111) << 7 |
+    imm >> 1 & 0x60 | imm << 2 & 0x18 | imm >> 3 & 0x4 | opcode as u32 & 0b11) as u16
+}
+
+pub fn encode_type_cj(opcode: u8, funct3: u8, imm: u32) -> u16 {
+    ((funct3 as u32) << 13 | imm << 1 & 0x1000 | imm << 7 & 0x800 | imm << 1 & 0x600 | imm >> 2 & 0x100 |
+    imm << 1 & 0x80 | imm >> 1 & 0x40 | imm << 2 & 0x38 | imm >> 3 & 0x4 | opcode as u32 & 0b11) as u16
+}
@@ -2,6 +2,12 @@ use crate::common::{instruction::*, types::*};
 use crate::public::*;
 use crate::rvi::*;
 
+/// Function executing the current instruction of a hart.
+pub type ExecuteFn<U, S, EEI, const N: usize> = fn(&mut RVI<U, S, EEI, N>);
+/// Function disassembling an instruction, with the ABI names of the registers if the flag is true.
+pub type DisassembleFn<U, S> = fn(Instruction<U, S>, bool) -> String;
+
+#[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ISA {
     UNKNOWN,
@@ -60,11 +66,130 @@ pub enum ISA {
     SRLW,
     SUBW,
 
+    DIV, // M32
+    DIVU,
+    MUL,
+    MULH,
+    MULHSU,
+    MULHU,
+    REM,
+    REMU,
+
+    DIVUW, // M64
+    DIVW,
+    MULW,
+    REMUW,
+    REMW,
+
+    AMOADD_W, // A32
+    AMOAND_W,
+    AMOMAX_W,
+    AMOMAXU_W,
+    AMOMIN_W,
+    AMOMINU_W,
+    AMOOR_W,
+    AMOSWAP_W,
+    AMOXOR_W,
+    LR_W,
+    SC_W,
+
+    AMOADD_D, // A64
+    AMOAND_D,
+    AMOMAX_D,
+    AMOMAXU_D,
+    AMOMIN_D,
+    AMOMINU_D,
+    AMOOR_D,
+    AMOSWAP_D,
+    AMOXOR_D,
+    LR_D,
+    SC_D,
+
+    FADD_S, // F32
+    FCLASS_S,
+    FCVT_S_W,
+    FCVT_S_WU,
+    FCVT_W_S,
+    FCVT_WU_S,
+    FDIV_S,
+    FEQ_S,
+    FLE_S,
+    FLT_S,
+    FLW,
+    FMADD_S,
+    FMAX_S,
+    FMIN_S,
+    FMSUB_S,
+    FMUL_S,
+    FMV_W_X,
+    FMV_X_W,
+    FNMADD_S,
+    FNMSUB_S,
+    FSGNJ_S,
+    FSGNJN_S,
+    FSGNJX_S,
+    FSQRT_S,
+    FSUB_S,
+    FSW,
+
+    FCVT_L_S, // F64
+    FCVT_LU_S,
+    FCVT_S_L,
+    FCVT_S_LU,
+
+    FADD_D, // D32
+    FCLASS_D,
+    FCVT_D_S,
+    FCVT_D_W,
+    FCVT_D_WU,
+    FCVT_S_D,
+    FCVT_W_D,
+    FCVT_WU_D,
+    FDIV_D,
+    FEQ_D,
+    FLD,
+    FLE_D,
+    FLT_D,
+    FMADD_D,
+    FMAX_D,
+    FMIN_D,
+    FMSUB_D,
+    FMUL_D,
+    FNMADD_D,
+    FNMSUB_D,
+    FSD,
+    FSGNJ_D,
+    FSGNJN_D,
+    FSGNJX_D,
+    FSQRT_D,
+    FSUB_D,
+
+    FCVT_D_L, // D64
+    FCVT_D_LU,
+    FCVT_L_D,
+    FCVT_LU_D,
+    FMV_D_X,
+    FMV_X_D,
+
+    CSRRC, // Zicsr
+    CSRRCI,
+    CSRRS,
+    CSRRSI,
+    CSRRW,
+    CSRRWI,
+
+    FENCE_I, // Zifencei
+
+    MRET, // Privileged
+    SFENCE_VMA,
+    SRET,
+    WFI,
+
     _SIZE, // used internally by dyriscvic, not a real instruction
 }
 
 pub trait I32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
-    const EXECUTE_I32: [fn(&mut RVI<U, S, EEI, N>); 40] = [
+    const EXECUTE_I32: [ExecuteFn<U, S, EEI, N>; 40] = [
         RVI::<U, S, EEI, N>::ADD,
         RVI::<U, S, EEI, N>::ADDI,
         RVI::<U, S, EEI, N>::AND,
@@ -151,7 +276,7 @@ pub trait I32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U
 }
 
 pub trait DisassembleI32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
-    const DISASSEMBLE_I32: [fn(Instruction<U, S>, bool) -> String; 40] = [
+    const DISASSEMBLE_I32: [DisassembleFn<U, S>; 40] = [
         RVI::<U, S, EEI, N>::disassemble_ADD,
         RVI::<U, S, EEI, N>::disassemble_ADDI,
         RVI::<U, S, EEI, N>::disassemble_AND,
@@ -269,8 +394,504 @@ pub trait DisassembleI64<U: Unsigned<S>, S: Signed<U>> {
     fn disassemble_SUBW(inst: Instruction<U, S>, abi_name: bool) -> String;
 }
 
-impl<EEI: ExecutionEnvironmentInterface<u64>> RV64I<EEI> {
-    pub const EXECUTE_I64: [fn(&mut RV64I<EEI>); 12] = [
+pub trait M32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_M32: [ExecuteFn<U, S, EEI, N>; 8] = [
+        RVI::<U, S, EEI, N>::DIV,
+        RVI::<U, S, EEI, N>::DIVU,
+        RVI::<U, S, EEI, N>::MUL,
+        RVI::<U, S, EEI, N>::MULH,
+        RVI::<U, S, EEI, N>::MULHSU,
+        RVI::<U, S, EEI, N>::MULHU,
+        RVI::<U, S, EEI, N>::REM,
+        RVI::<U, S, EEI, N>::REMU,
+    ];
+    fn load_execute_m32(&mut self);
+    fn DIV(&mut self);
+    fn DIVU(&mut self);
+    fn MUL(&mut self);
+    fn MULH(&mut self);
+    fn MULHSU(&mut self);
+    fn MULHU(&mut self);
+    fn REM(&mut self);
+    fn REMU(&mut self);
+}
+
+pub trait DisassembleM32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_M32: [DisassembleFn<U, S>; 8] = [
+        RVI::<U, S, EEI, N>::disassemble_DIV,
+        RVI::<U, S, EEI, N>::disassemble_DIVU,
+        RVI::<U, S, EEI, N>::disassemble_MUL,
+        RVI::<U, S, EEI, N>::disassemble_MULH,
+        RVI::<U, S, EEI, N>::disassemble_MULHSU,
+        RVI::<U, S, EEI, N>::disassemble_MULHU,
+        RVI::<U, S, EEI, N>::disassemble_REM,
+        RVI::<U, S, EEI, N>::disassemble_REMU,
+    ];
+    fn load_disassemble_m32(&mut self);
+    fn disassemble_DIV(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_DIVU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_MUL(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_MULH(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_MULHSU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_MULHU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_REM(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_REMU(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait M64 {
+    fn load_execute_m64(&mut self);
+    fn DIVUW(&mut self);
+    fn DIVW(&mut self);
+    fn MULW(&mut self);
+    fn REMUW(&mut self);
+    fn REMW(&mut self);
+}
+
+pub trait DisassembleM64<U: Unsigned<S>, S: Signed<U>> {
+    fn load_disassemble_m64(&mut self);
+    fn disassemble_DIVUW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_DIVW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_MULW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_REMUW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_REMW(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait A32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_A32: [ExecuteFn<U, S, EEI, N>; 11] = [
+        RVI::<U, S, EEI, N>::AMOADD_W,
+        RVI::<U, S, EEI, N>::AMOAND_W,
+        RVI::<U, S, EEI, N>::AMOMAX_W,
+        RVI::<U, S, EEI, N>::AMOMAXU_W,
+        RVI::<U, S, EEI, N>::AMOMIN_W,
+        RVI::<U, S, EEI, N>::AMOMINU_W,
+        RVI::<U, S, EEI, N>::AMOOR_W,
+        RVI::<U, S, EEI, N>::AMOSWAP_W,
+        RVI::<U, S, EEI, N>::AMOXOR_W,
+        RVI::<U, S, EEI, N>::LR_W,
+        RVI::<U, S, EEI, N>::SC_W,
+    ];
+    fn load_execute_a32(&mut self);
+    fn AMOADD_W(&mut self);
+    fn AMOAND_W(&mut self);
+    fn AMOMAX_W(&mut self);
+    fn AMOMAXU_W(&mut self);
+    fn AMOMIN_W(&mut self);
+    fn AMOMINU_W(&mut self);
+    fn AMOOR_W(&mut self);
+    fn AMOSWAP_W(&mut self);
+    fn AMOXOR_W(&mut self);
+    fn LR_W(&mut self);
+    fn SC_W(&mut self);
+}
+
+pub trait DisassembleA32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_A32: [DisassembleFn<U, S>; 11] = [
+        RVI::<U, S, EEI, N>::disassemble_AMOADD_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOAND_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOMAX_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOMAXU_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOMIN_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOMINU_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOOR_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOSWAP_W,
+        RVI::<U, S, EEI, N>::disassemble_AMOXOR_W,
+        RVI::<U, S, EEI, N>::disassemble_LR_W,
+        RVI::<U, S, EEI, N>::disassemble_SC_W,
+    ];
+    fn load_disassemble_a32(&mut self);
+    fn disassemble_AMOADD_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOAND_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMAX_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMAXU_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMIN_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMINU_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOOR_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOSWAP_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOXOR_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_LR_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_SC_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait A64 {
+    fn load_execute_a64(&mut self);
+    fn AMOADD_D(&mut self);
+    fn AMOAND_D(&mut self);
+    fn AMOMAX_D(&mut self);
+    fn AMOMAXU_D(&mut self);
+    fn AMOMIN_D(&mut self);
+    fn AMOMINU_D(&mut self);
+    fn AMOOR_D(&mut self);
+    fn AMOSWAP_D(&mut self);
+    fn AMOXOR_D(&mut self);
+    fn LR_D(&mut self);
+    fn SC_D(&mut self);
+}
+
+pub trait DisassembleA64<U: Unsigned<S>, S: Signed<U>> {
+    fn load_disassemble_a64(&mut self);
+    fn disassemble_AMOADD_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOAND_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMAX_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMAXU_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMIN_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOMINU_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOOR_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOSWAP_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_AMOXOR_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_LR_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_SC_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait F32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_F32: [ExecuteFn<U, S, EEI, N>; 26] = [
+        RVI::<U, S, EEI, N>::FADD_S,
+        RVI::<U, S, EEI, N>::FCLASS_S,
+        RVI::<U, S, EEI, N>::FCVT_S_W,
+        RVI::<U, S, EEI, N>::FCVT_S_WU,
+        RVI::<U, S, EEI, N>::FCVT_W_S,
+        RVI::<U, S, EEI, N>::FCVT_WU_S,
+        RVI::<U, S, EEI, N>::FDIV_S,
+        RVI::<U, S, EEI, N>::FEQ_S,
+        RVI::<U, S, EEI, N>::FLE_S,
+        RVI::<U, S, EEI, N>::FLT_S,
+        RVI::<U, S, EEI, N>::FLW,
+        RVI::<U, S, EEI, N>::FMADD_S,
+        RVI::<U, S, EEI, N>::FMAX_S,
+        RVI::<U, S, EEI, N>::FMIN_S,
+        RVI::<U, S, EEI, N>::FMSUB_S,
+        RVI::<U, S, EEI, N>::FMUL_S,
+        RVI::<U, S, EEI, N>::FMV_W_X,
+        RVI::<U, S, EEI, N>::FMV_X_W,
+        RVI::<U, S, EEI, N>::FNMADD_S,
+        RVI::<U, S, EEI, N>::FNMSUB_S,
+        RVI::<U, S, EEI, N>::FSGNJ_S,
+        RVI::<U, S, EEI, N>::FSGNJN_S,
+        RVI::<U, S, EEI, N>::FSGNJX_S,
+        RVI::<U, S, EEI, N>::FSQRT_S,
+        RVI::<U, S, EEI, N>::FSUB_S,
+        RVI::<U, S, EEI, N>::FSW,
+    ];
+    fn load_execute_f32(&mut self);
+    fn FADD_S(&mut self);
+    fn FCLASS_S(&mut self);
+    fn FCVT_S_W(&mut self);
+    fn FCVT_S_WU(&mut self);
+    fn FCVT_W_S(&mut self);
+    fn FCVT_WU_S(&mut self);
+    fn FDIV_S(&mut self);
+    fn FEQ_S(&mut self);
+    fn FLE_S(&mut self);
+    fn FLT_S(&mut self);
+    fn FLW(&mut self);
+    fn FMADD_S(&mut self);
+    fn FMAX_S(&mut self);
+    fn FMIN_S(&mut self);
+    fn FMSUB_S(&mut self);
+    fn FMUL_S(&mut self);
+    fn FMV_W_X(&mut self);
+    fn FMV_X_W(&mut self);
+    fn FNMADD_S(&mut self);
+    fn FNMSUB_S(&mut self);
+    fn FSGNJ_S(&mut self);
+    fn FSGNJN_S(&mut self);
+    fn FSGNJX_S(&mut self);
+    fn FSQRT_S(&mut self);
+    fn FSUB_S(&mut self);
+    fn FSW(&mut self);
+}
+
+pub trait DisassembleF32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_F32: [DisassembleFn<U, S>; 26] = [
+        RVI::<U, S, EEI, N>::disassemble_FADD_S,
+        RVI::<U, S, EEI, N>::disassemble_FCLASS_S,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_S_W,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_S_WU,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_W_S,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_WU_S,
+        RVI::<U, S, EEI, N>::disassemble_FDIV_S,
+        RVI::<U, S, EEI, N>::disassemble_FEQ_S,
+        RVI::<U, S, EEI, N>::disassemble_FLE_S,
+        RVI::<U, S, EEI, N>::disassemble_FLT_S,
+        RVI::<U, S, EEI, N>::disassemble_FLW,
+        RVI::<U, S, EEI, N>::disassemble_FMADD_S,
+        RVI::<U, S, EEI, N>::disassemble_FMAX_S,
+        RVI::<U, S, EEI, N>::disassemble_FMIN_S,
+        RVI::<U, S, EEI, N>::disassemble_FMSUB_S,
+        RVI::<U, S, EEI, N>::disassemble_FMUL_S,
+        RVI::<U, S, EEI, N>::disassemble_FMV_W_X,
+        RVI::<U, S, EEI, N>::disassemble_FMV_X_W,
+        RVI::<U, S, EEI, N>::disassemble_FNMADD_S,
+        RVI::<U, S, EEI, N>::disassemble_FNMSUB_S,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJ_S,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJN_S,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJX_S,
+        RVI::<U, S, EEI, N>::disassemble_FSQRT_S,
+        RVI::<U, S, EEI, N>::disassemble_FSUB_S,
+        RVI::<U, S, EEI, N>::disassemble_FSW,
+    ];
+    fn load_disassemble_f32(&mut self);
+    fn disassemble_FADD_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCLASS_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_S_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_S_WU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_W_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_WU_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FDIV_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FEQ_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLE_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLT_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMADD_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMAX_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMIN_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMUL_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMV_W_X(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMV_X_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FNMADD_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FNMSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJ_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJN_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJX_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSQRT_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSW(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait F64 {
+    fn load_execute_f64(&mut self);
+    fn FCVT_L_S(&mut self);
+    fn FCVT_LU_S(&mut self);
+    fn FCVT_S_L(&mut self);
+    fn FCVT_S_LU(&mut self);
+}
+
+pub trait DisassembleF64<U: Unsigned<S>, S: Signed<U>> {
+    fn load_disassemble_f64(&mut self);
+    fn disassemble_FCVT_L_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_LU_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_S_L(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_S_LU(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait D32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_D32: [ExecuteFn<U, S, EEI, N>; 26] = [
+        RVI::<U, S, EEI, N>::FADD_D,
+        RVI::<U, S, EEI, N>::FCLASS_D,
+        RVI::<U, S, EEI, N>::FCVT_D_S,
+        RVI::<U, S, EEI, N>::FCVT_D_W,
+        RVI::<U, S, EEI, N>::FCVT_D_WU,
+        RVI::<U, S, EEI, N>::FCVT_S_D,
+        RVI::<U, S, EEI, N>::FCVT_W_D,
+        RVI::<U, S, EEI, N>::FCVT_WU_D,
+        RVI::<U, S, EEI, N>::FDIV_D,
+        RVI::<U, S, EEI, N>::FEQ_D,
+        RVI::<U, S, EEI, N>::FLD,
+        RVI::<U, S, EEI, N>::FLE_D,
+        RVI::<U, S, EEI, N>::FLT_D,
+        RVI::<U, S, EEI, N>::FMADD_D,
+        RVI::<U, S, EEI, N>::FMAX_D,
+        RVI::<U, S, EEI, N>::FMIN_D,
+        RVI::<U, S, EEI, N>::FMSUB_D,
+        RVI::<U, S, EEI, N>::FMUL_D,
+        RVI::<U, S, EEI, N>::FNMADD_D,
+        RVI::<U, S, EEI, N>::FNMSUB_D,
+        RVI::<U, S, EEI, N>::FSD,
+        RVI::<U, S, EEI, N>::FSGNJ_D,
+        RVI::<U, S, EEI, N>::FSGNJN_D,
+        RVI::<U, S, EEI, N>::FSGNJX_D,
+        RVI::<U, S, EEI, N>::FSQRT_D,
+        RVI::<U, S, EEI, N>::FSUB_D,
+    ];
+    fn load_execute_d32(&mut self);
+    fn FADD_D(&mut self);
+    fn FCLASS_D(&mut self);
+    fn FCVT_D_S(&mut self);
+    fn FCVT_D_W(&mut self);
+    fn FCVT_D_WU(&mut self);
+    fn FCVT_S_D(&mut self);
+    fn FCVT_W_D(&mut self);
+    fn FCVT_WU_D(&mut self);
+    fn FDIV_D(&mut self);
+    fn FEQ_D(&mut self);
+    fn FLD(&mut self);
+    fn FLE_D(&mut self);
+    fn FLT_D(&mut self);
+    fn FMADD_D(&mut self);
+    fn FMAX_D(&mut self);
+    fn FMIN_D(&mut self);
+    fn FMSUB_D(&mut self);
+    fn FMUL_D(&mut self);
+    fn FNMADD_D(&mut self);
+    fn FNMSUB_D(&mut self);
+    fn FSD(&mut self);
+    fn FSGNJ_D(&mut self);
+    fn FSGNJN_D(&mut self);
+    fn FSGNJX_D(&mut self);
+    fn FSQRT_D(&mut self);
+    fn FSUB_D(&mut self);
+}
+
+pub trait DisassembleD32<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_D32: [DisassembleFn<U, S>; 26] = [
+        RVI::<U, S, EEI, N>::disassemble_FADD_D,
+        RVI::<U, S, EEI, N>::disassemble_FCLASS_D,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_D_S,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_D_W,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_D_WU,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_S_D,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_W_D,
+        RVI::<U, S, EEI, N>::disassemble_FCVT_WU_D,
+        RVI::<U, S, EEI, N>::disassemble_FDIV_D,
+        RVI::<U, S, EEI, N>::disassemble_FEQ_D,
+        RVI::<U, S, EEI, N>::disassemble_FLD,
+        RVI::<U, S, EEI, N>::disassemble_FLE_D,
+        RVI::<U, S, EEI, N>::disassemble_FLT_D,
+        RVI::<U, S, EEI, N>::disassemble_FMADD_D,
+        RVI::<U, S, EEI, N>::disassemble_FMAX_D,
+        RVI::<U, S, EEI, N>::disassemble_FMIN_D,
+        RVI::<U, S, EEI, N>::disassemble_FMSUB_D,
+        RVI::<U, S, EEI, N>::disassemble_FMUL_D,
+        RVI::<U, S, EEI, N>::disassemble_FNMADD_D,
+        RVI::<U, S, EEI, N>::disassemble_FNMSUB_D,
+        RVI::<U, S, EEI, N>::disassemble_FSD,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJ_D,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJN_D,
+        RVI::<U, S, EEI, N>::disassemble_FSGNJX_D,
+        RVI::<U, S, EEI, N>::disassemble_FSQRT_D,
+        RVI::<U, S, EEI, N>::disassemble_FSUB_D,
+    ];
+    fn load_disassemble_d32(&mut self);
+    fn disassemble_FADD_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCLASS_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_D_S(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_D_W(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_D_WU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_S_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_W_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_WU_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FDIV_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FEQ_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLD(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLE_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FLT_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMADD_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMAX_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMIN_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMUL_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FNMADD_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FNMSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSD(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJ_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJN_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSGNJX_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSQRT_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait D64 {
+    fn load_execute_d64(&mut self);
+    fn FCVT_D_L(&mut self);
+    fn FCVT_D_LU(&mut self);
+    fn FCVT_L_D(&mut self);
+    fn FCVT_LU_D(&mut self);
+    fn FMV_D_X(&mut self);
+    fn FMV_X_D(&mut self);
+}
+
+pub trait DisassembleD64<U: Unsigned<S>, S: Signed<U>> {
+    fn load_disassemble_d64(&mut self);
+    fn disassemble_FCVT_D_L(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_D_LU(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_L_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FCVT_LU_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMV_D_X(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_FMV_X_D(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait Zicsr<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_ZICSR: [ExecuteFn<U, S, EEI, N>; 6] = [
+        RVI::<U, S, EEI, N>::CSRRC,
+        RVI::<U, S, EEI, N>::CSRRCI,
+        RVI::<U, S, EEI, N>::CSRRS,
+        RVI::<U, S, EEI, N>::CSRRSI,
+        RVI::<U, S, EEI, N>::CSRRW,
+        RVI::<U, S, EEI, N>::CSRRWI,
+    ];
+    fn load_execute_zicsr(&mut self);
+    fn CSRRC(&mut self);
+    fn CSRRCI(&mut self);
+    fn CSRRS(&mut self);
+    fn CSRRSI(&mut self);
+    fn CSRRW(&mut self);
+    fn CSRRWI(&mut self);
+}
+
+pub trait DisassembleZicsr<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_ZICSR: [DisassembleFn<U, S>; 6] = [
+        RVI::<U, S, EEI, N>::disassemble_CSRRC,
+        RVI::<U, S, EEI, N>::disassemble_CSRRCI,
+        RVI::<U, S, EEI, N>::disassemble_CSRRS,
+        RVI::<U, S, EEI, N>::disassemble_CSRRSI,
+        RVI::<U, S, EEI, N>::disassemble_CSRRW,
+        RVI::<U, S, EEI, N>::disassemble_CSRRWI,
+    ];
+    fn load_disassemble_zicsr(&mut self);
+    fn disassemble_CSRRC(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_CSRRCI(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_CSRRS(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_CSRRSI(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_CSRRW(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_CSRRWI(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait Zifencei<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_ZIFENCEI: [ExecuteFn<U, S, EEI, N>; 1] = [
+        RVI::<U, S, EEI, N>::FENCE_I,
+    ];
+    fn load_execute_zifencei(&mut self);
+    fn FENCE_I(&mut self);
+}
+
+pub trait DisassembleZifencei<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_ZIFENCEI: [DisassembleFn<U, S>; 1] = [
+        RVI::<U, S, EEI, N>::disassemble_FENCE_I,
+    ];
+    fn load_disassemble_zifencei(&mut self);
+    fn disassemble_FENCE_I(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+pub trait Privileged<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const EXECUTE_PRIVILEGED: [ExecuteFn<U, S, EEI, N>; 4] = [
+        RVI::<U, S, EEI, N>::MRET,
+        RVI::<U, S, EEI, N>::SFENCE_VMA,
+        RVI::<U, S, EEI, N>::SRET,
+        RVI::<U, S, EEI, N>::WFI,
+    ];
+    fn load_execute_privileged(&mut self);
+    fn MRET(&mut self);
+    fn SFENCE_VMA(&mut self);
+    fn SRET(&mut self);
+    fn WFI(&mut self);
+}
+
+pub trait DisassemblePrivileged<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    const DISASSEMBLE_PRIVILEGED: [DisassembleFn<U, S>; 4] = [
+        RVI::<U, S, EEI, N>::disassemble_MRET,
+        RVI::<U, S, EEI, N>::disassemble_SFENCE_VMA,
+        RVI::<U, S, EEI, N>::disassemble_SRET,
+        RVI::<U, S, EEI, N>::disassemble_WFI,
+    ];
+    fn load_disassemble_privileged(&mut self);
+    fn disassemble_MRET(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_SFENCE_VMA(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_SRET(inst: Instruction<U, S>, abi_name: bool) -> String;
+    fn disassemble_WFI(inst: Instruction<U, S>, abi_name: bool) -> String;
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> RV64<EEI, N> {
+    pub const EXECUTE_I64: [fn(&mut RV64<EEI, N>); 12] = [
         Self::ADDIW,
         Self::ADDW,
         Self::LD,
@@ -299,4 +920,80 @@ impl<EEI: ExecutionEnvironmentInterface<u64>> RV64I<EEI> {
         Self::disassemble_SRLW,
         Self::disassemble_SUBW,
     ];
+
+    pub const EXECUTE_M64: [fn(&mut RV64<EEI, N>); 5] = [
+        Self::DIVUW,
+        Self::DIVW,
+        Self::MULW,
+        Self::REMUW,
+        Self::REMW,
+    ];
+
+    pub const DISASSEMBLE_M64: [fn(inst: Instruction64, abi_name: bool) -> String; 5] = [
+        Self::disassemble_DIVUW,
+        Self::disassemble_DIVW,
+        Self::disassemble_MULW,
+        Self::disassemble_REMUW,
+        Self::disassemble_REMW,
+    ];
+
+    pub const EXECUTE_A64: [fn(&mut RV64<EEI, N>); 11] = [
+        Self::AMOADD_D,
+        Self::AMOAND_D,
+        Self::AMOMAX_D,
+        Self::AMOMAXU_D,
+        Self::AMOMIN_D,
+        Self::AMOMINU_D,
+        Self::AMOOR_D,
+        Self::AMOSWAP_D,
+        Self::AMOXOR_D,
+        Self::LR_D,
+        Self::SC_D,
+    ];
+
+    pub const DISASSEMBLE_A64: [fn(inst: Instruction64, abi_name: bool) -> String; 11] = [
+        Self::disassemble_AMOADD_D,
+        Self::disassemble_AMOAND_D,
+        Self::disassemble_AMOMAX_D,
+        Self::disassemble_AMOMAXU_D,
+        Self::disassemble_AMOMIN_D,
+        Self::disassemble_AMOMINU_D,
+        Self::disassemble_AMOOR_D,
+        Self::disassemble_AMOSWAP_D,
+        Self::disassemble_AMOXOR_D,
+        Self::disassemble_LR_D,
+        Self::disassemble_SC_D,
+    ];
+
+    pub const EXECUTE_F64: [fn(&mut RV64<EEI, N>); 4] = [
+        Self::FCVT_L_S,
+        Self::FCVT_LU_S,
+        Self::FCVT_S_L,
+        Self::FCVT_S_LU,
+    ];
+
+    pub const DISASSEMBLE_F64: [fn(inst: Instruction64, abi_name: bool) -> String; 4] = [
+        Self::disassemble_FCVT_L_S,
+        Self::disassemble_FCVT_LU_S,
+        Self::disassemble_FCVT_S_L,
+        Self::disassemble_FCVT_S_LU,
+    ];
+
+    pub const EXECUTE_D64: [fn(&mut RV64<EEI, N>); 6] = [
+        Self::FCVT_D_L,
+        Self::FCVT_D_LU,
+        Self::FCVT_L_D,
+        Self::FCVT_LU_D,
+        Self::FMV_D_X,
+        Self::FMV_X_D,
+    ];
+
+    pub const DISASSEMBLE_D64: [fn(inst: Instruction64, abi_name: bool) -> String; 6] = [
+        Self::disassemble_FCVT_D_L,
+        Self::disassemble_FCVT_D_LU,
+        Self::disassemble_FCVT_L_D,
+        Self::disassemble_FCVT_LU_D,
+        Self::disassemble_FMV_D_X,
+        Self::disassemble_FMV_X_D,
+    ];
 }
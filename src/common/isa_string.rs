@@ -0,0 +1,269 @@
+//! Parsing and validation of RISC-V ISA strings, such as `rv64imafdc_zicsr_zifencei`.
+//!
+//! Strings are case-insensitive. The `rv32`/`rv64` prefix and the base ISA letter are optional,
+//! so a string like `MZicsr` only lists extensions and takes its base from the hart it is used on.
+//! Multi-letter extensions start with `Z` or `X`, or with `S` after an underscore.
+
+use std::fmt;
+
+/// Single-letter extensions supported by dyriscvic, in canonical order. `S` and `U` enable the privilege modes.
+const SINGLE_LETTER: &str = "IEMAFDCSU";
+/// Multi-letter extensions supported by dyriscvic, in canonical order.
+const MULTI_LETTER: [&str; 2] = ["Zicsr", "Zifencei"];
+/// Extensions implied by others, as (extension, implied extension).
+const IMPLIED: [(&str, &str); 2] = [("D", "F"), ("F", "Zicsr")];
+
+/// An extension of an ISA string with its optional version (major, minor).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Extension {
+    pub name: String,
+    pub version: Option<(u32, u32)>,
+}
+
+/// A parsed and validated ISA string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ISAString {
+    /// The XLEN given by the `rv32`/`rv64` prefix.
+    pub xlen: Option<usize>,
+    /// The base integer ISA, `'I'` or `'E'`.
+    pub base: Option<char>,
+    /// The extensions, including the implied ones, in canonical order.
+    pub extensions: Vec<Extension>,
+}
+
+/// Errors returned when parsing an ISA string or when it does not match the hart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ISAStringError {
+    /// The XLEN after `rv` is not 32 or 64.
+    InvalidXlen(String),
+    /// The `rv32`/`rv64` prefix is not followed by `I`, `E` or `G`.
+    MissingBase,
+    /// The character at the given position is not valid here.
+    UnexpectedCharacter(char, usize),
+    /// The extension is not implemented.
+    UnsupportedExtension(String),
+    /// The extension is given several times.
+    DuplicateExtension(String),
+    /// The ISA string is for another XLEN than the hart.
+    XlenMismatch { isa: usize, hart: usize },
+    /// The base ISA cannot be used with the number of integer registers of the hart.
+    BaseMismatch { base: char, registers: usize },
+    /// The extension cannot be used with the given base ISA.
+    IncompatibleBase { extension: String, base: char },
+}
+
+impl fmt::Display for ISAStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidXlen(xlen) => write!(f, "invalid XLEN \"{}\"", xlen),
+            Self::MissingBase => write!(f, "missing base ISA (I, E or G)"),
+            Self::UnexpectedCharacter(c, pos) => write!(f, "unexpected character '{}' at position {}", c, pos),
+            Self::UnsupportedExtension(ext) => write!(f, "unsupported extension {}", ext),
+            Self::DuplicateExtension(ext) => write!(f, "duplicate extension {}", ext),
+            Self::XlenMismatch { isa, hart } => write!(f, "ISA string is for RV{} but the hart is RV{}", isa, hart),
+            Self::BaseMismatch { base, registers } => write!(f, "base {} cannot be used with {} integer registers", base, registers),
+            Self::IncompatibleBase { extension, base } => write!(f, "extension {} cannot be used with base {}", extension, base),
+        }
+    }
+}
+
+impl std::error::Error for ISAStringError {}
+
+impl ISAString {
+    /// Parses the given ISA string, adding the extensions implied by `G`, `D` and `F`.
+    pub fn parse(isa: &str) -> Result<Self, ISAStringError> {
+        let chars: Vec<char> = isa.to_ascii_lowercase().chars().collect();
+        let mut pos = 0;
+        let mut xlen = None;
+        let mut base = None;
+        let mut general = false;
+        let mut extensions: Vec<Extension> = Vec::new();
+
+        if chars.starts_with(&['r', 'v']) {
+            let digits: String = chars[2..].iter().take_while(|c| c.is_ascii_digit()).collect();
+            xlen = match digits.as_str() {
+                "32" => Some(32),
+                "64" => Some(64),
+                _ => return Err(ISAStringError::InvalidXlen(digits)),
+            };
+            pos = 2 + digits.len();
+            if !matches!(chars.get(pos), Some('i') | Some('e') | Some('g')) {
+                return Err(ISAStringError::MissingBase);
+            }
+        }
+        let base_pos = pos;
+
+        while pos < chars.len() {
+            let c = chars[pos];
+            if c == '_' {
+                pos += 1;
+            } else if c == 'z' || c == 'x' || c == 's' && pos > 0 && chars[pos - 1] == '_' {
+                let end = chars[pos..].iter().position(|&c| c == '_').map_or(chars.len(), |len| pos + len);
+                let token: String = chars[pos..end].iter().collect();
+                let (name, version) = split_version(&token);
+                let name = MULTI_LETTER.iter().find(|ext| ext.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| ISAStringError::UnsupportedExtension(name[..1].to_ascii_uppercase() + &name[1..]))?;
+                add_extension(&mut extensions, name, version)?;
+                pos = end;
+            } else if c.is_ascii_alphabetic() {
+                let (version, len) = parse_version(&chars[pos + 1..]);
+                match c.to_ascii_uppercase() {
+                    'I' | 'E' | 'G' if pos != base_pos => return Err(ISAStringError::UnexpectedCharacter(c, pos)),
+                    'G' => {
+                        base = Some('I');
+                        general = true;
+                    },
+                    'I' | 'E' => base = Some(c.to_ascii_uppercase()),
+                    c if SINGLE_LETTER.contains(c) => add_extension(&mut extensions, &String::from(c), version)?,
+                    c => return Err(ISAStringError::UnsupportedExtension(String::from(c))),
+                }
+                pos += 1 + len;
+            } else {
+                return Err(ISAStringError::UnexpectedCharacter(c, pos));
+            }
+        }
+
+        if general {
+            for ext in ["M", "A", "F", "D", "Zicsr", "Zifencei"] {
+                if !extensions.iter().any(|e| e.name == ext) {
+                    extensions.push(Extension { name: String::from(ext), version: None });
+                }
+            }
+        }
+        for (ext, implied) in IMPLIED {
+            if extensions.iter().any(|e| e.name == ext) && !extensions.iter().any(|e| e.name == implied) {
+                extensions.push(Extension { name: String::from(implied), version: None });
+            }
+        }
+        extensions.sort_by_key(|e| canonical_order(&e.name));
+
+        Self { xlen, base, extensions }.check_base()
+    }
+
+    /// Checks that the ISA string can be used on a hart with the given XLEN and number of integer registers,
+    /// and fills the XLEN and the base if they are not given.
+    pub fn for_hart(mut self, xlen: usize, registers: usize) -> Result<Self, ISAStringError> {
+        if let Some(isa) = self.xlen {
+            if isa != xlen {
+                return Err(ISAStringError::XlenMismatch { isa, hart: xlen });
+            }
+        }
+
+        let expected = if registers == 16 { 'E' } else { 'I' };
+        match self.base {
+            Some(base) if base != expected => return Err(ISAStringError::BaseMismatch { base, registers }),
+            _ => (),
+        }
+
+        self.xlen = Some(xlen);
+        self.base = Some(expected);
+        self.check_base()
+    }
+
+    /// RVE does not support the D extension.
+    fn check_base(self) -> Result<Self, ISAStringError> {
+        if self.base == Some('E') && self.has("D") {
+            Err(ISAStringError::IncompatibleBase { extension: String::from("D"), base: 'E' })
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns true if the given extension is enabled. The name is case-insensitive and can be the base ISA.
+    pub fn has(&self, name: &str) -> bool {
+        self.base.is_some_and(|base| name.len() == 1 && name.as_bytes()[0].eq_ignore_ascii_case(&(base as u8))) ||
+        self.extensions.iter().any(|ext| ext.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the Extensions field of the `misa` CSR.
+    pub fn misa_extensions(&self) -> u64 {
+        self.base.into_iter()
+            .chain(self.extensions.iter().filter(|ext| ext.name.len() == 1).map(|ext| ext.name.as_bytes()[0] as char))
+            .fold(0, |misa, c| misa | 1 << (c as u64 - 'A' as u64))
+    }
+}
+
+impl std::str::FromStr for ISAString {
+    type Err = ISAStringError;
+
+    fn from_str(isa: &str) -> Result<Self, Self::Err> {
+        Self::parse(isa)
+    }
+}
+
+/// Formats the ISA string in its canonical lower-case form, such as `rv64imafd_zicsr_zifencei`.
+impl fmt::Display for ISAString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(xlen) = self.xlen {
+            write!(f, "rv{}", xlen)?;
+        }
+        if let Some(base) = self.base {
+            write!(f, "{}", base.to_ascii_lowercase())?;
+        }
+
+        let mut empty = self.xlen.is_none() && self.base.is_none();
+        for ext in &self.extensions {
+            if ext.name.len() > 1 && !empty {
+                write!(f, "_")?;
+            }
+            write!(f, "{}", ext.name.to_ascii_lowercase())?;
+            if let Some((major, minor)) = ext.version {
+                write!(f, "{}p{}", major, minor)?;
+            }
+            empty = false;
+        }
+        Ok(())
+    }
+}
+
+fn add_extension(extensions: &mut Vec<Extension>, name: &str, version: Option<(u32, u32)>) -> Result<(), ISAStringError> {
+    if extensions.iter().any(|ext| ext.name == name) {
+        Err(ISAStringError::DuplicateExtension(String::from(name)))
+    } else {
+        extensions.push(Extension { name: String::from(name), version });
+        Ok(())
+    }
+}
+
+/// Parses a version `<major>[p<minor>]` at the beginning of `chars`. Returns it and its length.
+fn parse_version(chars: &[char]) -> (Option<(u32, u32)>, usize) {
+    let number = |chars: &[char]| -> (u32, usize) {
+        let len = chars.iter().take_while(|c| c.is_ascii_digit()).count();
+        (chars[..len].iter().fold(0u32, |n, c| n.saturating_mul(10).saturating_add(c.to_digit(10).unwrap())), len)
+    };
+
+    let (major, len) = number(chars);
+    if len == 0 {
+        return (None, 0);
+    }
+    if chars.get(len) == Some(&'p') && chars.get(len + 1).is_some_and(char::is_ascii_digit) {
+        let (minor, minor_len) = number(&chars[len + 1..]);
+        (Some((major, minor)), len + 1 + minor_len)
+    } else {
+        (Some((major, 0)), len)
+    }
+}
+
+/// Splits the version at the end of a multi-letter extension, such as `zicsr2p0`.
+fn split_version(token: &str) -> (&str, Option<(u32, u32)>) {
+    let digits = |s: &str| s.len() - s.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let last = digits(token);
+    if last == 0 {
+        return (token, None);
+    }
+
+    let head = &token[..token.len() - last];
+    let chars: Vec<char> = match head.strip_suffix('p') {
+        Some(major) if digits(major) != 0 => token[major.len() - digits(major)..].chars().collect(),
+        _ => token[head.len()..].chars().collect(),
+    };
+    (&token[..token.len() - chars.len()], parse_version(&chars).0)
+}
+
+fn canonical_order(name: &str) -> usize {
+    if name.len() == 1 {
+        SINGLE_LETTER.find(name).unwrap()
+    } else {
+        SINGLE_LETTER.len() + MULTI_LETTER.iter().position(|&ext| ext == name).unwrap()
+    }
+}
@@ -1,8 +1,11 @@
 //! Traits, enums and structs shared betwenn the library's components and that can be useful to the user.
 
+pub mod csr;
 pub mod decoder;
 pub mod instruction;
 pub mod isa;
+pub mod isa_string;
+pub mod softfloat;
 pub mod types;
 
 pub use instruction::{Instruction, Instruction32, Instruction64};
@@ -51,6 +54,7 @@ impl AsSlice<4> for u32 {
 
 /// Get the integer register ABI name associated with its number.
 /// If `abi_name` is true, returns the ABI name as in table 25.1. Otherwise returns `x0`, `x1`, etc.
+/// The ILP32E and LP64E ABIs of RV32E and RV64E harts keep the names of `x0` to `x15`, which are the only registers they have.
 pub fn get_x_register_name(reg: u8, abi_name: bool) -> String {
     if abi_name {
         String::from(match reg {
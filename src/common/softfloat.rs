@@ -0,0 +1,567 @@
+//! Software implementation of the IEEE 754 binary32 and binary64 arithmetic.
+//!
+//! Values are handled as their bit patterns. The RISC-V conventions are used: NaN results are always the canonical NaN,
+//! and tininess is detected after rounding.
+
+/// Rounding modes, in the order of the `frm` field encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even.
+    NearestEven = 0,
+    /// Round towards zero.
+    TowardZero = 1,
+    /// Round down (towards negative infinity).
+    Down = 2,
+    /// Round up (towards positive infinity).
+    Up = 3,
+    /// Round to nearest, ties to max magnitude.
+    NearestMaxMagnitude = 4,
+}
+
+impl RoundingMode {
+    /// Returns the rounding mode encoded by the given value, or `None` if it is reserved.
+    pub fn from_u8(rm: u8) -> Option<Self> {
+        match rm {
+            0 => Some(RoundingMode::NearestEven),
+            1 => Some(RoundingMode::TowardZero),
+            2 => Some(RoundingMode::Down),
+            3 => Some(RoundingMode::Up),
+            4 => Some(RoundingMode::NearestMaxMagnitude),
+            _ => None,
+        }
+    }
+}
+
+/// Inexact exception flag.
+pub const FLAG_NX: u8 = 1 << 0;
+/// Underflow exception flag.
+pub const FLAG_UF: u8 = 1 << 1;
+/// Overflow exception flag.
+pub const FLAG_OF: u8 = 1 << 2;
+/// Divide by zero exception flag.
+pub const FLAG_DZ: u8 = 1 << 3;
+/// Invalid operation exception flag.
+pub const FLAG_NV: u8 = 1 << 4;
+
+/// A binary floating-point format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Format {
+    exp_bits: u32,
+    frac_bits: u32,
+}
+
+impl Format {
+    /// The binary32 (single-precision) format.
+    pub const SINGLE: Format = Format { exp_bits: 8, frac_bits: 23 };
+    /// The binary64 (double-precision) format.
+    pub const DOUBLE: Format = Format { exp_bits: 11, frac_bits: 52 };
+
+    fn bias(self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    fn max_exp(self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    fn sign_bit(self) -> u64 {
+        1 << (self.exp_bits + self.frac_bits)
+    }
+
+    fn frac_mask(self) -> u64 {
+        (1 << self.frac_bits) - 1
+    }
+
+    fn quiet_bit(self) -> u64 {
+        1 << (self.frac_bits - 1)
+    }
+
+    /// Returns true if the sign bit of the given value is set.
+    pub fn sign(self, a: u64) -> bool {
+        a & self.sign_bit() != 0
+    }
+
+    /// Returns the given value with its sign bit replaced.
+    pub fn with_sign(self, a: u64, sign: bool) -> u64 {
+        a & !self.sign_bit() | self.zero(sign)
+    }
+
+    fn exp(self, a: u64) -> u64 {
+        a >> self.frac_bits & self.max_exp()
+    }
+
+    fn frac(self, a: u64) -> u64 {
+        a & self.frac_mask()
+    }
+
+    /// Returns the canonical NaN of the format.
+    pub fn canonical_nan(self) -> u64 {
+        self.max_exp() << self.frac_bits | self.quiet_bit()
+    }
+
+    fn infinity(self, sign: bool) -> u64 {
+        self.zero(sign) | self.max_exp() << self.frac_bits
+    }
+
+    fn zero(self, sign: bool) -> u64 {
+        if sign { self.sign_bit() } else { 0 }
+    }
+
+    fn largest(self, sign: bool) -> u64 {
+        self.zero(sign) | (self.max_exp() - 1) << self.frac_bits | self.frac_mask()
+    }
+
+    /// Returns true if the given value is a NaN.
+    pub fn is_nan(self, a: u64) -> bool {
+        self.exp(a) == self.max_exp() && self.frac(a) != 0
+    }
+
+    /// Returns true if the given value is a signaling NaN.
+    pub fn is_signaling_nan(self, a: u64) -> bool {
+        self.is_nan(a) && a & self.quiet_bit() == 0
+    }
+
+    fn is_inf(self, a: u64) -> bool {
+        self.exp(a) == self.max_exp() && self.frac(a) == 0
+    }
+
+    fn is_zero(self, a: u64) -> bool {
+        a & !self.sign_bit() == 0
+    }
+
+    /// Returns the sign, exponent and significand of a finite value, such that the value is `sig * 2^exp`.
+    fn unpack(self, a: u64) -> (bool, i32, u128) {
+        let exp = self.exp(a);
+        let frac = self.frac(a) as u128;
+        if exp == 0 {
+            (self.sign(a), 1 - self.bias() - self.frac_bits as i32, frac)
+        } else {
+            (self.sign(a), exp as i32 - self.bias() - self.frac_bits as i32, frac | 1 << self.frac_bits)
+        }
+    }
+
+    /// Returns the class of the given value, as the one-hot mask written by the FCLASS instructions.
+    pub fn classify(self, a: u64) -> u16 {
+        let sign = self.sign(a);
+        let exp = self.exp(a);
+        let bit = if self.is_nan(a) {
+            if self.is_signaling_nan(a) { 8 } else { 9 }
+        } else if self.is_inf(a) {
+            if sign { 0 } else { 7 }
+        } else if exp == 0 && self.frac(a) == 0 {
+            if sign { 3 } else { 4 }
+        } else if exp == 0 {
+            if sign { 2 } else { 5 }
+        } else if sign { 1 } else { 6 };
+        1 << bit
+    }
+}
+
+/// Returns the number of significant bits of `a`.
+fn bit_length(a: u128) -> i32 {
+    128 - a.leading_zeros() as i32
+}
+
+/// Returns the integer square root of `a` and true if it is exact.
+fn isqrt(a: u128) -> (u128, bool) {
+    let mut rem = a;
+    let mut root = 0;
+    let mut bit = 1u128 << 126;
+    while bit > a {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    (root, rem == 0)
+}
+
+/// Floating-point environment: the rounding mode used by the operations and the accrued exception flags.
+#[derive(Clone, Copy, Debug)]
+pub struct Softfloat {
+    pub rounding_mode: RoundingMode,
+    pub flags: u8,
+}
+
+impl Softfloat {
+    pub fn new(rounding_mode: RoundingMode) -> Self {
+        Self { rounding_mode, flags: 0 }
+    }
+
+    /// Shifts `sig` right by `shift` bits and rounds the result. `sticky` indicates that nonzero bits are below `sig`.
+    /// Returns the rounded value and true if it is inexact.
+    fn round_shift(&self, sign: bool, sig: u128, sticky: bool, shift: i32) -> (u128, bool) {
+        let (value, round, rest) = if shift <= 0 {
+            (sig << -shift, false, sticky)
+        } else if shift > 128 {
+            (0, false, sig != 0 || sticky)
+        } else {
+            let value = sig.checked_shr(shift as u32).unwrap_or(0);
+            let round = sig >> (shift - 1) & 1 != 0;
+            let rest = sig & ((1 << (shift - 1)) - 1) != 0 || sticky;
+            (value, round, rest)
+        };
+
+        let increment = match self.rounding_mode {
+            RoundingMode::NearestEven => round && (rest || value & 1 != 0),
+            RoundingMode::TowardZero => false,
+            RoundingMode::Down => sign && (round || rest),
+            RoundingMode::Up => !sign && (round || rest),
+            RoundingMode::NearestMaxMagnitude => round,
+        };
+        (value + increment as u128, round || rest)
+    }
+
+    /// Rounds the nonzero value `sig * 2^exp` to the given format. `sticky` indicates that the exact value is slightly greater in magnitude.
+    fn round_pack(&mut self, f: Format, sign: bool, exp: i32, sig: u128, sticky: bool) -> u64 {
+        let frac_bits = f.frac_bits as i32;
+        let emin = 1 - f.bias();
+        let top = exp + bit_length(sig) - 1;
+        let mut lsb = (top - frac_bits).max(emin - frac_bits);
+
+        let (mut value, inexact) = self.round_shift(sign, sig, sticky, lsb - exp);
+        if value >> (frac_bits + 1) != 0 {
+            value >>= 1;
+            lsb += 1;
+        }
+
+        if inexact {
+            self.flags |= FLAG_NX;
+            if top < emin {
+                // Tininess is detected after rounding, as if the exponent range was unbounded.
+                let (unbounded, _) = self.round_shift(sign, sig, sticky, top - frac_bits - exp);
+                if top < emin - 1 || unbounded >> (frac_bits + 1) == 0 {
+                    self.flags |= FLAG_UF;
+                }
+            }
+        }
+
+        let biased = if value >> frac_bits != 0 { (lsb + frac_bits + f.bias()) as u64 } else { 0 };
+        if biased >= f.max_exp() {
+            self.flags |= FLAG_OF | FLAG_NX;
+            let infinite = match self.rounding_mode {
+                RoundingMode::NearestEven | RoundingMode::NearestMaxMagnitude => true,
+                RoundingMode::TowardZero => false,
+                RoundingMode::Down => sign,
+                RoundingMode::Up => !sign,
+            };
+            return if infinite { f.infinity(sign) } else { f.largest(sign) };
+        }
+
+        f.zero(sign) | biased << f.frac_bits | value as u64 & f.frac_mask()
+    }
+
+    /// Returns the canonical NaN, raising the invalid flag if one of the operands is a signaling NaN.
+    fn propagate_nan(&mut self, f: Format, operands: &[u64]) -> u64 {
+        if operands.iter().any(|&a| f.is_signaling_nan(a)) {
+            self.flags |= FLAG_NV;
+        }
+        f.canonical_nan()
+    }
+
+    fn invalid(&mut self, f: Format) -> u64 {
+        self.flags |= FLAG_NV;
+        f.canonical_nan()
+    }
+
+    /// Adds two finite values given as `(sign, exp, sig)`, with significands of at most 106 bits.
+    fn add_finite(&mut self, f: Format, a: (bool, i32, u128), b: (bool, i32, u128)) -> u64 {
+        let (a, b) = if a.1 >= b.1 { (a, b) } else { (b, a) };
+        let ((sign_a, exp_a, sig_a), (sign_b, exp_b, sig_b)) = (a, b);
+
+        if sig_a == 0 && sig_b == 0 {
+            let sign = if sign_a == sign_b { sign_a } else { self.rounding_mode == RoundingMode::Down };
+            return f.zero(sign);
+        } else if sig_a == 0 {
+            return self.round_pack(f, sign_b, exp_b, sig_b, false);
+        } else if sig_b == 0 {
+            return self.round_pack(f, sign_a, exp_a, sig_a, false);
+        }
+
+        // Align the operands by shifting the larger one left. Bits of the smaller one that are still out of range become sticky.
+        let diff = exp_a - exp_b;
+        let shift = diff.min(125 - bit_length(sig_a));
+        let big = sig_a << shift;
+        let exp = exp_a - shift;
+        let rest = diff - shift;
+        let (small, sticky) = if rest == 0 {
+            (sig_b, false)
+        } else if rest >= 128 {
+            (0, true)
+        } else {
+            (sig_b >> rest, sig_b & ((1 << rest) - 1) != 0)
+        };
+
+        if sign_a == sign_b {
+            self.round_pack(f, sign_a, exp, big + small, sticky)
+        } else if sticky {
+            self.round_pack(f, sign_a, exp, big - small - 1, true)
+        } else if big > small {
+            self.round_pack(f, sign_a, exp, big - small, false)
+        } else if small > big {
+            self.round_pack(f, sign_b, exp, small - big, false)
+        } else {
+            f.zero(self.rounding_mode == RoundingMode::Down)
+        }
+    }
+
+    pub fn add(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        if f.is_nan(a) || f.is_nan(b) {
+            return self.propagate_nan(f, &[a, b]);
+        }
+        if f.is_inf(a) && f.is_inf(b) && f.sign(a) != f.sign(b) {
+            return self.invalid(f);
+        }
+        if f.is_inf(a) {
+            return a;
+        }
+        if f.is_inf(b) {
+            return b;
+        }
+        self.add_finite(f, f.unpack(a), f.unpack(b))
+    }
+
+    pub fn sub(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        self.add(f, a, b ^ f.sign_bit())
+    }
+
+    pub fn mul(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        if f.is_nan(a) || f.is_nan(b) {
+            return self.propagate_nan(f, &[a, b]);
+        }
+
+        let sign = f.sign(a) != f.sign(b);
+        if f.is_inf(a) || f.is_inf(b) {
+            return if f.is_zero(a) || f.is_zero(b) { self.invalid(f) } else { f.infinity(sign) };
+        }
+        if f.is_zero(a) || f.is_zero(b) {
+            return f.zero(sign);
+        }
+
+        let (_, exp_a, sig_a) = f.unpack(a);
+        let (_, exp_b, sig_b) = f.unpack(b);
+        self.round_pack(f, sign, exp_a + exp_b, sig_a * sig_b, false)
+    }
+
+    pub fn div(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        if f.is_nan(a) || f.is_nan(b) {
+            return self.propagate_nan(f, &[a, b]);
+        }
+
+        let sign = f.sign(a) != f.sign(b);
+        if f.is_inf(a) {
+            return if f.is_inf(b) { self.invalid(f) } else { f.infinity(sign) };
+        }
+        if f.is_inf(b) {
+            return f.zero(sign);
+        }
+        if f.is_zero(b) {
+            if f.is_zero(a) {
+                return self.invalid(f);
+            }
+            self.flags |= FLAG_DZ;
+            return f.infinity(sign);
+        }
+        if f.is_zero(a) {
+            return f.zero(sign);
+        }
+
+        let (_, exp_a, sig_a) = f.unpack(a);
+        let (_, exp_b, sig_b) = f.unpack(b);
+        let shift_a = 127 - bit_length(sig_a);
+        let shift_b = 64 - bit_length(sig_b);
+        let num = sig_a << shift_a;
+        let den = sig_b << shift_b;
+        self.round_pack(f, sign, exp_a - shift_a - exp_b + shift_b, num / den, !num.is_multiple_of(den))
+    }
+
+    pub fn sqrt(&mut self, f: Format, a: u64) -> u64 {
+        if f.is_nan(a) {
+            return self.propagate_nan(f, &[a]);
+        }
+        if f.is_zero(a) {
+            return a;
+        }
+        if f.sign(a) {
+            return self.invalid(f);
+        }
+        if f.is_inf(a) {
+            return a;
+        }
+
+        let (_, mut exp, mut sig) = f.unpack(a);
+        if exp & 1 != 0 {
+            sig <<= 1;
+            exp -= 1;
+        }
+        let shift = (126 - bit_length(sig)) & !1;
+        let (root, exact) = isqrt(sig << shift);
+        self.round_pack(f, false, (exp - shift) / 2, root, !exact)
+    }
+
+    /// Computes `a * b + c` with a single rounding.
+    pub fn fused_mul_add(&mut self, f: Format, a: u64, b: u64, c: u64) -> u64 {
+        if f.is_nan(a) || f.is_nan(b) {
+            return self.propagate_nan(f, &[a, b, c]);
+        }
+
+        let sign = f.sign(a) != f.sign(b);
+        let infinite = f.is_inf(a) || f.is_inf(b);
+        if infinite && (f.is_zero(a) || f.is_zero(b)) {
+            return self.invalid(f);
+        }
+        if f.is_nan(c) {
+            return self.propagate_nan(f, &[c]);
+        }
+        if infinite {
+            return if f.is_inf(c) && f.sign(c) != sign { self.invalid(f) } else { f.infinity(sign) };
+        }
+        if f.is_inf(c) {
+            return c;
+        }
+
+        let (_, exp_a, sig_a) = f.unpack(a);
+        let (_, exp_b, sig_b) = f.unpack(b);
+        self.add_finite(f, (sign, exp_a + exp_b, sig_a * sig_b), f.unpack(c))
+    }
+
+    /// Returns the minimum of the two values. -0 is less than +0, and NaNs are ignored unless both values are NaNs.
+    pub fn min(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        self.min_max(f, a, b, true)
+    }
+
+    /// Returns the maximum of the two values. -0 is less than +0, and NaNs are ignored unless both values are NaNs.
+    pub fn max(&mut self, f: Format, a: u64, b: u64) -> u64 {
+        self.min_max(f, a, b, false)
+    }
+
+    fn min_max(&mut self, f: Format, a: u64, b: u64, min: bool) -> u64 {
+        if f.is_signaling_nan(a) || f.is_signaling_nan(b) {
+            self.flags |= FLAG_NV;
+        }
+
+        match (f.is_nan(a), f.is_nan(b)) {
+            (true, true) => f.canonical_nan(),
+            (true, false) => b,
+            (false, true) => a,
+            _ => {
+                let less = if f.is_zero(a) && f.is_zero(b) { f.sign(a) && !f.sign(b) } else { Self::less(f, a, b) };
+                if less == min { a } else { b }
+            },
+        }
+    }
+
+    /// Returns true if `a < b`, for non-NaN values.
+    fn less(f: Format, a: u64, b: u64) -> bool {
+        if f.is_zero(a) && f.is_zero(b) {
+            false
+        } else if f.sign(a) != f.sign(b) {
+            f.sign(a)
+        } else if f.sign(a) {
+            a > b
+        } else {
+            a < b
+        }
+    }
+
+    /// Quiet equality comparison: only signaling NaNs raise the invalid flag.
+    pub fn eq(&mut self, f: Format, a: u64, b: u64) -> bool {
+        if f.is_nan(a) || f.is_nan(b) {
+            self.propagate_nan(f, &[a, b]);
+            return false;
+        }
+        a == b || f.is_zero(a) && f.is_zero(b)
+    }
+
+    /// Signaling less-than comparison: NaNs raise the invalid flag.
+    pub fn lt(&mut self, f: Format, a: u64, b: u64) -> bool {
+        if f.is_nan(a) || f.is_nan(b) {
+            self.flags |= FLAG_NV;
+            return false;
+        }
+        Self::less(f, a, b)
+    }
+
+    /// Signaling less-than-or-equal comparison: NaNs raise the invalid flag.
+    pub fn le(&mut self, f: Format, a: u64, b: u64) -> bool {
+        if f.is_nan(a) || f.is_nan(b) {
+            self.flags |= FLAG_NV;
+            return false;
+        }
+        !Self::less(f, b, a)
+    }
+
+    /// Converts a value between two formats.
+    pub fn convert(&mut self, from: Format, to: Format, a: u64) -> u64 {
+        if from.is_nan(a) {
+            self.propagate_nan(from, &[a]);
+            return to.canonical_nan();
+        }
+        if from.is_inf(a) {
+            return to.infinity(from.sign(a));
+        }
+        if from.is_zero(a) {
+            return to.zero(from.sign(a));
+        }
+
+        let (sign, exp, sig) = from.unpack(a);
+        self.round_pack(to, sign, exp, sig, false)
+    }
+
+    /// Converts a value to an integer of `bits` bits, returned zero-extended.
+    /// Out-of-range values and NaNs raise the invalid flag and are saturated.
+    pub fn to_int(&mut self, f: Format, a: u64, signed: bool, bits: u32) -> u64 {
+        let mask = u64::MAX >> (64 - bits);
+        let max = if signed { mask >> 1 } else { mask };
+        let min = if signed { !(mask >> 1) & mask } else { 0 };
+
+        let sign = f.sign(a);
+        if f.is_nan(a) {
+            self.flags |= FLAG_NV;
+            return max;
+        }
+        if f.is_inf(a) {
+            self.flags |= FLAG_NV;
+            return if sign { min } else { max };
+        }
+        if f.is_zero(a) {
+            return 0;
+        }
+
+        let (_, exp, sig) = f.unpack(a);
+        if exp + bit_length(sig) > 65 {
+            self.flags |= FLAG_NV;
+            return if sign { min } else { max };
+        }
+
+        let (value, inexact) = if exp >= 0 { (sig << exp, false) } else { self.round_shift(sign, sig, false, -exp) };
+        let limit = if sign { if signed { max as u128 + 1 } else { 0 } } else { max as u128 };
+        if value > limit {
+            self.flags |= FLAG_NV;
+            return if sign { min } else { max };
+        }
+
+        if inexact {
+            self.flags |= FLAG_NX;
+        }
+        let value = value as u64;
+        if sign { value.wrapping_neg() & mask } else { value }
+    }
+
+    /// Converts the integer in the `bits` lower bits of `a` to the given format.
+    pub fn from_int(&mut self, f: Format, a: u64, signed: bool, bits: u32) -> u64 {
+        let a = a & u64::MAX >> (64 - bits);
+        let sign = signed && a >> (bits - 1) != 0;
+        let magnitude = if sign { a.wrapping_neg() & u64::MAX >> (64 - bits) } else { a };
+        if magnitude == 0 {
+            return f.zero(false);
+        }
+        self.round_pack(f, sign, 0, magnitude as u128, false)
+    }
+}
@@ -2,9 +2,9 @@ use std::ops::*;
 use std::fmt::*;
 
 pub trait Int = Copy + Sized + Debug + Display + From<bool> + From<u8> + From<u16> + Ord + UpperHex +
-    Add<Output = Self> + AddAssign + Sub<Output = Self> + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Shl<Output = Self> + Shr<Output = Self>;
-pub trait Signed<U> = Int + From<i8> + From<i16> + From<i32> + AsUnsigned<Unsigned = U> + As;
-pub trait Unsigned<S> = Int + From<u32> + AsSigned<Signed = S> + As;
+    Add<Output = Self> + AddAssign + Sub<Output = Self> + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self> + Shl<Output = Self> + Shr<Output = Self>;
+pub trait Signed<U> = Int + From<i8> + From<i16> + From<i32> + AsUnsigned<Unsigned = U> + As + MulDiv;
+pub trait Unsigned<S> = Int + From<u32> + AsSigned<Signed = S> + As + FromU64;
 
 pub trait AsSigned {
     type Signed;
@@ -44,6 +44,19 @@ impl AsUnsigned for i64 {
     }
 }
 
+/// Truncating conversion from a u64.
+pub trait FromU64 {
+    fn from_u64(val: u64) -> Self;
+}
+
+impl FromU64 for u32 {
+    fn from_u64(val: u64) -> Self { val as u32 }
+}
+
+impl FromU64 for u64 {
+    fn from_u64(val: u64) -> Self { val }
+}
+
 pub trait As {
     fn as_u8(self) -> u8;
     fn as_u16(self) -> u16;
@@ -78,3 +91,38 @@ impl As for i64 {
     fn as_u32(self) -> u32 { self as u32 }
     fn as_u64(self) -> u64 { self as u64 }
 }
+
+/// Multiplication and division with the results defined by the M extension.
+/// Division by zero and signed overflow never panic.
+pub trait MulDiv {
+    fn mul(self, rhs: Self) -> Self;
+    fn mulh(self, rhs: Self) -> Self;
+    fn mulhsu(self, rhs: Self) -> Self;
+    fn mulhu(self, rhs: Self) -> Self;
+    fn div(self, rhs: Self) -> Self;
+    fn divu(self, rhs: Self) -> Self;
+    fn rem(self, rhs: Self) -> Self;
+    fn remu(self, rhs: Self) -> Self;
+}
+
+impl MulDiv for i32 {
+    fn mul(self, rhs: Self) -> Self { self.wrapping_mul(rhs) }
+    fn mulh(self, rhs: Self) -> Self { ((self as i64 * rhs as i64) >> 32) as i32 }
+    fn mulhsu(self, rhs: Self) -> Self { ((self as i64).wrapping_mul(rhs as u32 as i64) >> 32) as i32 }
+    fn mulhu(self, rhs: Self) -> Self { ((self as u32 as u64 * rhs as u32 as u64) >> 32) as i32 }
+    fn div(self, rhs: Self) -> Self { if rhs == 0 { -1 } else { self.wrapping_div(rhs) } }
+    fn divu(self, rhs: Self) -> Self { if rhs == 0 { -1 } else { (self as u32 / rhs as u32) as i32 } }
+    fn rem(self, rhs: Self) -> Self { if rhs == 0 { self } else { self.wrapping_rem(rhs) } }
+    fn remu(self, rhs: Self) -> Self { if rhs == 0 { self } else { (self as u32 % rhs as u32) as i32 } }
+}
+
+impl MulDiv for i64 {
+    fn mul(self, rhs: Self) -> Self { self.wrapping_mul(rhs) }
+    fn mulh(self, rhs: Self) -> Self { ((self as i128 * rhs as i128) >> 64) as i64 }
+    fn mulhsu(self, rhs: Self) -> Self { ((self as i128).wrapping_mul(rhs as u64 as i128) >> 64) as i64 }
+    fn mulhu(self, rhs: Self) -> Self { ((self as u64 as u128 * rhs as u64 as u128) >> 64) as i64 }
+    fn div(self, rhs: Self) -> Self { if rhs == 0 { -1 } else { self.wrapping_div(rhs) } }
+    fn divu(self, rhs: Self) -> Self { if rhs == 0 { -1 } else { (self as u64 / rhs as u64) as i64 } }
+    fn rem(self, rhs: Self) -> Self { if rhs == 0 { self } else { self.wrapping_rem(rhs) } }
+    fn remu(self, rhs: Self) -> Self { if rhs == 0 { self } else { (self as u64 % rhs as u64) as i64 } }
+}
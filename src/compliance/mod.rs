@@ -0,0 +1,149 @@
+//! Runner of the RISC-V architectural tests (riscv-arch-test).
+//!
+//! A test is loaded by [`load`] in RAM mapped on a [`Bus`] covering its segments, run until it writes to `tohost`
+//! (`RVMODEL_HALT`), then the memory between the `begin_signature` and `end_signature` symbols is dumped by [`signature`]
+//! and formatted by [`format_signature`] like the reference signatures of the test suite.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::bus::{Bus, MapError};
+use crate::elf::{ElfError, ElfFile, EF_RISCV_RVE};
+use crate::htif::{self, Htif};
+use crate::public::{MemoryAccess, MemoryFault};
+use crate::rvi::{MisalignedAccess, RVConfig, RV32I, RV32E, RV64I, RV64E};
+
+/// Number of bytes of RAM mapped after the last segment of the file, for the stack and the heap.
+pub const RAM_MARGIN: u64 = 0x10_0000;
+
+/// Errors returned when running a test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ComplianceError {
+    /// The file is invalid or incompatible with the configuration.
+    Elf(ElfError),
+    /// The RAM could not be mapped.
+    Map(MapError),
+    /// The file has no symbol with the given name.
+    MissingSymbol(&'static str),
+    /// The test has not written to `tohost` after the maximum number of instructions.
+    Timeout,
+    /// The signature could not be read.
+    MemoryFault(u64, MemoryFault),
+}
+
+impl fmt::Display for ComplianceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Elf(err) => write!(f, "{}", err),
+            Self::Map(err) => write!(f, "{}", err),
+            Self::MissingSymbol(name) => write!(f, "missing symbol {}", name),
+            Self::Timeout => write!(f, "the test did not halt"),
+            Self::MemoryFault(addr, fault) => write!(f, "{:?} at {:#x}", fault, addr),
+        }
+    }
+}
+
+impl std::error::Error for ComplianceError {}
+
+impl From<ElfError> for ComplianceError {
+    fn from(err: ElfError) -> Self {
+        Self::Elf(err)
+    }
+}
+
+impl From<MapError> for ComplianceError {
+    fn from(err: MapError) -> Self {
+        Self::Map(err)
+    }
+}
+
+/// Returns the configuration used to run the given file: privileged, with misaligned accesses emulated,
+/// and with the ISA `ext` or `rv32gc`/`rv64gc` (`rv32ec`/`rv64ec` for RVE files) if None.
+pub fn config(elf: &ElfFile, ext: Option<&str>) -> RVConfig {
+    let base = if elf.flags & EF_RISCV_RVE != 0 { "ec" } else { "gc" };
+    RVConfig {
+        ext: ext.map_or_else(|| format!("rv{}{}", elf.xlen, base), String::from),
+        abi_name: true,
+        privileged: true,
+        pmp_regions: 0,
+        misaligned_access: MisalignedAccess::Emulate,
+    }
+}
+
+/// Maps RAM covering the pages of the segments of the file and [`RAM_MARGIN`] bytes after the last one, and loads the file in it.
+pub fn load(elf: &ElfFile) -> Result<Htif<Bus>, ComplianceError> {
+    let mut ranges = Vec::new();
+    for segment in elf.segments.iter().filter(|segment| segment.memsz != 0) {
+        let end = segment.paddr.checked_add(segment.memsz).and_then(|end| end.checked_add(0xFFF))
+            .ok_or(ElfError::AddressOutOfRange(segment.paddr))?;
+        ranges.push((segment.paddr & !0xFFF, end & !0xFFF));
+    }
+    let last = ranges.iter().map(|&(_, end)| end).max().unwrap_or(0);
+    ranges.push((last, last.checked_add(RAM_MARGIN).ok_or(ElfError::AddressOutOfRange(last))?));
+    ranges.sort_unstable();
+
+    // Segments sharing pages are mapped in a single region.
+    let mut regions: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in ranges {
+        match regions.last_mut() {
+            Some((_, region_end)) if start <= *region_end => *region_end = (*region_end).max(end),
+            _ => regions.push((start, end)),
+        }
+    }
+    let mut bus = Bus::new();
+    for (start, end) in regions {
+        bus.map_ram(start, end - start)?;
+    }
+
+    let mut eei = Htif::from_elf(bus, elf).ok_or(ComplianceError::MissingSymbol("tohost"))?;
+    elf.load::<u64, _>(&mut eei.memory)?;
+    Ok(eei)
+}
+
+/// Returns the words (32 bits) between `begin` and `end`.
+pub fn signature<ADDR: TryFrom<u64>, M: MemoryAccess<ADDR>>(memory: &mut M, begin: u64, end: u64) -> Result<Vec<u32>, ComplianceError> {
+    (begin..end).step_by(4).map(|addr| {
+        let fault = |fault| ComplianceError::MemoryFault(addr, fault);
+        memory.get_32(ADDR::try_from(addr).map_err(|_| fault(MemoryFault::AccessFault))?).map_err(fault)
+    }).collect()
+}
+
+/// Formats the signature like the reference signatures, one word in lowercase hexadecimal per line.
+pub fn format_signature(signature: &[u32]) -> String {
+    signature.iter().map(|word| format!("{:08x}\n", word)).collect()
+}
+
+/// Runs the given test for at most `max_instructions` and returns its signature.
+///
+/// The hart is configured by [`config`] with the ISA `ext`, and is RV32 or RV64 depending on the class of the file.
+pub fn run_test(data: &[u8], ext: Option<&str>, max_instructions: usize) -> Result<Vec<u32>, ComplianceError> {
+    let elf = ElfFile::parse(data)?;
+    let config = config(&elf, ext);
+    elf.check_config(elf.xlen, &config)?;
+    let begin = elf.symbol("begin_signature").ok_or(ComplianceError::MissingSymbol("begin_signature"))?.value;
+    let end = elf.symbol("end_signature").ok_or(ComplianceError::MissingSymbol("end_signature"))?.value;
+
+    let eei = load(&elf)?;
+    let rve = elf.flags & EF_RISCV_RVE != 0;
+    let (exit_code, mut eei) = match (elf.xlen, rve) {
+        (32, false) => {
+            let mut rv32 = RV32I::try_new([0; 32], elf.entry as u32, config, eei).map_err(ElfError::from)?;
+            (htif::run(&mut rv32, max_instructions), rv32.into_eei())
+        },
+        (32, true) => {
+            let mut rv32 = RV32E::try_new([0; 16], elf.entry as u32, config, eei).map_err(ElfError::from)?;
+            (htif::run(&mut rv32, max_instructions), rv32.into_eei())
+        },
+        (_, false) => {
+            let mut rv64 = RV64I::try_new([0; 32], elf.entry, config, eei).map_err(ElfError::from)?;
+            (htif::run(&mut rv64, max_instructions), rv64.into_eei())
+        },
+        (_, true) => {
+            let mut rv64 = RV64E::try_new([0; 16], elf.entry, config, eei).map_err(ElfError::from)?;
+            (htif::run(&mut rv64, max_instructions), rv64.into_eei())
+        },
+    };
+
+    exit_code.ok_or(ComplianceError::Timeout)?;
+    signature::<u64, _>(&mut eei.memory, begin, end)
+}
@@ -0,0 +1,339 @@
+//! Loader of RISC-V ELF32 and ELF64 executables.
+//!
+//! The file is parsed by [`ElfFile::parse`], checked against the configuration of the hart by [`ElfFile::check_config`]
+//! and its `PT_LOAD` segments are copied to memory by [`ElfFile::load`]. The entry point is then given to the hart's `new()`.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::common::isa_string::{ISAString, ISAStringError};
+use crate::public::{MemoryAccess, MemoryFault};
+use crate::rvi::RVConfig;
+
+/// `e_machine` of RISC-V files.
+pub const EM_RISCV: u16 = 243;
+/// The file uses compressed instructions.
+pub const EF_RISCV_RVC: u32 = 0x0001;
+/// Mask of the floating-point ABI in `e_flags`.
+pub const EF_RISCV_FLOAT_ABI: u32 = 0x0006;
+pub const EF_RISCV_FLOAT_ABI_SOFT: u32 = 0x0000;
+pub const EF_RISCV_FLOAT_ABI_SINGLE: u32 = 0x0002;
+pub const EF_RISCV_FLOAT_ABI_DOUBLE: u32 = 0x0004;
+pub const EF_RISCV_FLOAT_ABI_QUAD: u32 = 0x0006;
+/// The file uses the ILP32E or LP64E ABI.
+pub const EF_RISCV_RVE: u32 = 0x0008;
+
+const PT_LOAD: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+
+/// A loadable segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// Offset of the segment in the file.
+    pub offset: u64,
+    pub vaddr: u64,
+    /// Address where the segment is loaded.
+    pub paddr: u64,
+    /// Number of bytes copied from the file.
+    pub filesz: u64,
+    /// Size in memory. The bytes after `filesz` are zero-filled (BSS).
+    pub memsz: u64,
+    /// Permissions (`PF_X = 1`, `PF_W = 2`, `PF_R = 4`).
+    pub flags: u32,
+}
+
+/// Types of the symbols of the symbol table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolType {
+    NoType,
+    Object,
+    Function,
+    Section,
+    File,
+    Other(u8),
+}
+
+/// An entry of the symbol table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    pub kind: SymbolType,
+}
+
+/// A parsed RISC-V executable.
+#[derive(Clone, Debug)]
+pub struct ElfFile {
+    /// 32 for ELF32 files, 64 for ELF64 files.
+    pub xlen: usize,
+    pub entry: u64,
+    /// `e_flags`, see the `EF_RISCV_*` constants.
+    pub flags: u32,
+    pub segments: Vec<Segment>,
+    /// The symbol table, empty if the file is stripped.
+    pub symbols: Vec<Symbol>,
+    data: Vec<u8>,
+}
+
+/// Errors returned when parsing or loading an ELF file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ElfError {
+    /// The file does not start with the ELF magic number.
+    InvalidMagic,
+    /// The file is not ELF32 nor ELF64.
+    InvalidClass(u8),
+    /// The file is big-endian.
+    BigEndian,
+    /// `e_machine` is not [`EM_RISCV`].
+    NotRiscV(u16),
+    /// A header, segment or table lies outside of the file.
+    Truncated,
+    /// The ISA string of the configuration is invalid.
+    InvalidISA(ISAStringError),
+    /// The file is for another XLEN than the hart.
+    XlenMismatch { elf: usize, hart: usize },
+    /// The file requires an extension that is not enabled in the configuration.
+    MissingExtension(String),
+    /// The file uses the RVE ABI but the hart is not RVE, or the reverse.
+    BaseMismatch { rve: bool },
+    /// The file uses the quad-precision floating-point ABI.
+    UnsupportedFloatABI,
+    /// A segment does not fit in the address space of the memory.
+    AddressOutOfRange(u64),
+    /// The memory returned a fault while loading a segment.
+    MemoryFault(u64, MemoryFault),
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not an ELF file"),
+            Self::InvalidClass(class) => write!(f, "invalid ELF class {}", class),
+            Self::BigEndian => write!(f, "big-endian files are not supported"),
+            Self::NotRiscV(machine) => write!(f, "e_machine is {}, not RISC-V", machine),
+            Self::Truncated => write!(f, "truncated file"),
+            Self::InvalidISA(err) => write!(f, "invalid ISA string: {}", err),
+            Self::XlenMismatch { elf, hart } => write!(f, "file is ELF{} but the hart is RV{}", elf, hart),
+            Self::MissingExtension(ext) => write!(f, "file requires the {} extension", ext),
+            Self::BaseMismatch { rve: true } => write!(f, "file uses the RVE ABI but the hart is not RVE"),
+            Self::BaseMismatch { rve: false } => write!(f, "hart is RVE but the file does not use the RVE ABI"),
+            Self::UnsupportedFloatABI => write!(f, "quad-precision floating-point ABI is not supported"),
+            Self::AddressOutOfRange(addr) => write!(f, "address {:#x} is out of range", addr),
+            Self::MemoryFault(addr, fault) => write!(f, "{:?} at {:#x}", fault, addr),
+        }
+    }
+}
+
+impl std::error::Error for ElfError {}
+
+impl From<ISAStringError> for ElfError {
+    fn from(err: ISAStringError) -> Self {
+        Self::InvalidISA(err)
+    }
+}
+
+/// Little-endian reader with bounds checking.
+struct Reader<'a> {
+    data: &'a [u8],
+    elf64: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&self, offset: u64, len: u64) -> Result<&'a [u8], ElfError> {
+        let start = usize::try_from(offset).map_err(|_| ElfError::Truncated)?;
+        let end = start.checked_add(usize::try_from(len).map_err(|_| ElfError::Truncated)?).ok_or(ElfError::Truncated)?;
+        self.data.get(start..end).ok_or(ElfError::Truncated)
+    }
+
+    /// Returns a reader of the first `len` bytes of the `index`-th entry of `entsize` bytes of the table at `offset`.
+    fn entry(&self, offset: u64, index: u64, entsize: u64, len: u64) -> Result<Reader<'a>, ElfError> {
+        let start = index.checked_mul(entsize).and_then(|start| start.checked_add(offset)).ok_or(ElfError::Truncated)?;
+        Ok(Reader { data: self.bytes(start, len)?, elf64: self.elf64 })
+    }
+
+    fn uint(&self, offset: u64, len: u64) -> Result<u64, ElfError> {
+        Ok(self.bytes(offset, len)?.iter().rev().fold(0, |value, &byte| value << 8 | byte as u64))
+    }
+
+    fn u8(&self, offset: u64) -> Result<u8, ElfError> {
+        Ok(self.uint(offset, 1)? as u8)
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, ElfError> {
+        Ok(self.uint(offset, 2)? as u16)
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, ElfError> {
+        Ok(self.uint(offset, 4)? as u32)
+    }
+
+    /// Reads a field of the native size of the file (`Elf32_Addr` or `Elf64_Addr`).
+    fn addr(&self, offset: u64) -> Result<u64, ElfError> {
+        self.uint(offset, if self.elf64 { 8 } else { 4 })
+    }
+
+    /// Reads the null-terminated string at the given offset.
+    fn string(&self, offset: u64) -> Result<String, ElfError> {
+        let start = usize::try_from(offset).map_err(|_| ElfError::Truncated)?;
+        let bytes = self.data.get(start..).ok_or(ElfError::Truncated)?;
+        let len = bytes.iter().position(|&b| b == 0).ok_or(ElfError::Truncated)?;
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+impl ElfFile {
+    /// Parses the given little-endian RISC-V executable.
+    pub fn parse(data: &[u8]) -> Result<Self, ElfError> {
+        if !data.starts_with(b"\x7FELF") {
+            return Err(ElfError::InvalidMagic);
+        }
+
+        let mut reader = Reader { data, elf64: false };
+        let xlen = match reader.u8(4)? {
+            1 => 32,
+            2 => 64,
+            class => return Err(ElfError::InvalidClass(class)),
+        };
+        reader.elf64 = xlen == 64;
+        if reader.u8(5)? != 1 {
+            return Err(ElfError::BigEndian);
+        }
+        let machine = reader.u16(18)?;
+        if machine != EM_RISCV {
+            return Err(ElfError::NotRiscV(machine));
+        }
+
+        let word = if reader.elf64 { 8 } else { 4 };
+        let entry = reader.addr(24)?;
+        let phoff = reader.addr(24 + word)?;
+        let shoff = reader.addr(24 + 2 * word)?;
+        let flags = reader.u32(24 + 3 * word)?;
+        let phentsize = reader.u16(24 + 3 * word + 6)? as u64;
+        let phnum = reader.u16(24 + 3 * word + 8)? as u64;
+        let shentsize = reader.u16(24 + 3 * word + 10)? as u64;
+        let shnum = reader.u16(24 + 3 * word + 12)? as u64;
+
+        let mut segments = Vec::new();
+        for i in 0..phnum {
+            let ph = reader.entry(phoff, i, phentsize, 8 + 6 * word)?;
+            if ph.u32(0)? != PT_LOAD {
+                continue;
+            }
+            let segment = if reader.elf64 {
+                Segment {
+                    flags: ph.u32(4)?,
+                    offset: ph.addr(8)?,
+                    vaddr: ph.addr(16)?,
+                    paddr: ph.addr(24)?,
+                    filesz: ph.addr(32)?,
+                    memsz: ph.addr(40)?,
+                }
+            } else {
+                Segment {
+                    offset: ph.addr(4)?,
+                    vaddr: ph.addr(8)?,
+                    paddr: ph.addr(12)?,
+                    filesz: ph.addr(16)?,
+                    memsz: ph.addr(20)?,
+                    flags: ph.u32(24)?,
+                }
+            };
+            reader.bytes(segment.offset, segment.filesz)?;
+            segments.push(segment);
+        }
+
+        let mut symbols = Vec::new();
+        let shsize = 16 + 6 * word;
+        for i in 0..shnum {
+            let sh = reader.entry(shoff, i, shentsize, shsize)?;
+            if sh.u32(4)? != SHT_SYMTAB {
+                continue;
+            }
+            let offset = sh.addr(8 + 2 * word)?;
+            let size = sh.addr(8 + 3 * word)?;
+            let link = sh.u32(8 + 4 * word)? as u64;
+            let entsize = sh.addr(16 + 5 * word)?;
+            let strtab = reader.entry(shoff, link, shentsize, shsize)?.addr(8 + 2 * word)?;
+            if entsize == 0 {
+                return Err(ElfError::Truncated);
+            }
+
+            // The first entry is the undefined symbol.
+            for n in 1..size / entsize {
+                let sym = reader.entry(offset, n, entsize, 8 + 2 * word)?;
+                let (value, size, info) = if reader.elf64 {
+                    (sym.addr(8)?, sym.addr(16)?, sym.u8(4)?)
+                } else {
+                    (sym.addr(4)?, sym.addr(8)?, sym.u8(12)?)
+                };
+                let kind = match info & 0xF {
+                    0 => SymbolType::NoType,
+                    1 => SymbolType::Object,
+                    2 => SymbolType::Function,
+                    3 => SymbolType::Section,
+                    4 => SymbolType::File,
+                    kind => SymbolType::Other(kind),
+                };
+                let name = strtab.checked_add(sym.u32(0)? as u64).ok_or(ElfError::Truncated)?;
+                symbols.push(Symbol { name: reader.string(name)?, value, size, kind });
+            }
+        }
+
+        Ok(Self { xlen, entry, flags, segments, symbols, data: data.to_vec() })
+    }
+
+    /// Returns the first symbol with the given name.
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|sym| sym.name == name)
+    }
+
+    /// Checks that the file can run on a hart of the given XLEN with the given configuration:
+    /// the XLEN, the C extension, the floating-point ABI and the RVE ABI.
+    ///
+    /// The RVE ABI is only checked if the ISA string of the configuration gives the base.
+    pub fn check_config(&self, xlen: usize, config: &RVConfig) -> Result<(), ElfError> {
+        if self.xlen != xlen {
+            return Err(ElfError::XlenMismatch { elf: self.xlen, hart: xlen });
+        }
+
+        let isa = ISAString::parse(&config.ext)?;
+        if let Some(isa) = isa.xlen {
+            if isa != xlen {
+                return Err(ISAStringError::XlenMismatch { isa, hart: xlen }.into());
+            }
+        }
+
+        if self.flags & EF_RISCV_RVC != 0 && !isa.has("C") {
+            return Err(ElfError::MissingExtension(String::from("C")));
+        }
+        match self.flags & EF_RISCV_FLOAT_ABI {
+            EF_RISCV_FLOAT_ABI_SINGLE if !isa.has("F") => return Err(ElfError::MissingExtension(String::from("F"))),
+            EF_RISCV_FLOAT_ABI_DOUBLE if !isa.has("D") => return Err(ElfError::MissingExtension(String::from("D"))),
+            EF_RISCV_FLOAT_ABI_QUAD => return Err(ElfError::UnsupportedFloatABI),
+            _ => (),
+        }
+
+        let rve = self.flags & EF_RISCV_RVE != 0;
+        match isa.base {
+            Some(base) if (base == 'E') != rve => Err(ElfError::BaseMismatch { rve }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Copies the `PT_LOAD` segments to their physical address with [`MemoryAccess::set_8`] and zero-fills their BSS.
+    /// Returns the entry point.
+    pub fn load<ADDR: TryFrom<u64>, M: MemoryAccess<ADDR>>(&self, memory: &mut M) -> Result<u64, ElfError> {
+        let reader = Reader { data: &self.data, elf64: self.xlen == 64 };
+        for segment in &self.segments {
+            let bytes = reader.bytes(segment.offset, segment.filesz)?;
+            for i in 0..segment.memsz {
+                let addr = segment.paddr.checked_add(i).ok_or(ElfError::AddressOutOfRange(segment.paddr))?;
+                let byte = bytes.get(i as usize).copied().unwrap_or(0);
+                let target = ADDR::try_from(addr).map_err(|_| ElfError::AddressOutOfRange(addr))?;
+                memory.set_8(target, byte).map_err(|fault| ElfError::MemoryFault(addr, fault))?;
+            }
+        }
+        Ok(self.entry)
+    }
+}
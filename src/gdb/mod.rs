@@ -0,0 +1,526 @@
+//! GDB remote serial protocol stub.
+//!
+//! [`GdbStub`] drives a hart with [`RVI::single_step`] on behalf of a GDB client connected over TCP ([`GdbStub::listen`])
+//! or any byte stream such as a pipe ([`GdbStub::serve`]). It exposes the integer registers, `pc`, the floating-point
+//! registers and the implemented CSRs with a matching target description, reads and writes memory through
+//! [`MemoryAccess`](crate::public::MemoryAccess), and supports software and hardware breakpoints, watchpoints,
+//! continue, step and interrupt (Ctrl-C). Breakpoints and watchpoints are the ones of the hart, see [`crate::rvi::debug`].
+//!
+//! Register numbers follow GDB: `x0` to `x31` are 0 to 31, `pc` is 32, `f0` to `f31` are 33 to 64 and CSR `n` is `65 + n`.
+//! Memory addresses are given directly to the execution environment, without address translation,
+//! but watchpoints watch virtual addresses.
+//!
+//! When [`RVConfig::privileged`](crate::rvi::RVConfig::privileged) is false, traps stop the hart with the matching signal.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::common::{csr::get_csr_name, get_f_register_name, get_x_register_name, types::*};
+use crate::public::{ExecutionEnvironmentInterface, Traps};
+use crate::rvi::{debug::{StopReason, WatchKind}, RVI};
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+const SIGBUS: u8 = 7;
+const SIGSEGV: u8 = 11;
+
+/// Maximum size in bytes of the packets sent to the client, reported in `qSupported`.
+const PACKET_SIZE: usize = 0x4000;
+
+/// Number of the first CSR register in GDB.
+const CSR_FIRST_REGNUM: usize = 65;
+
+/// Number of steps between two checks for an interrupt request from the client.
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
+
+/// Returns the kind of watchpoint of the given `Z` packet type.
+fn watch_kind(kind: u64) -> Option<WatchKind> {
+    match kind {
+        2 => Some(WatchKind::Write),
+        3 => Some(WatchKind::Read),
+        4 => Some(WatchKind::Access),
+        _ => None,
+    }
+}
+
+/// Name of the stop reason of a watchpoint in stop reply packets.
+fn watch_reason(kind: WatchKind) -> &'static str {
+    match kind {
+        WatchKind::Write => "watch",
+        WatchKind::Read => "rwatch",
+        WatchKind::Access => "awatch",
+    }
+}
+
+/// Reasons for the hart to stop, reported to the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stop {
+    Signal(u8),
+    SoftwareBreakpoint,
+    HardwareBreakpoint,
+    Watchpoint(WatchKind, u64),
+    /// The client has disconnected while the hart was running.
+    Disconnected,
+}
+
+/// GDB stub controlling a hart. See the [module documentation](self).
+pub struct GdbStub<'a, U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    hart: &'a mut RVI<U, S, EEI, N>,
+    software_breakpoints: HashSet<u64>,
+    hardware_breakpoints: HashSet<u64>,
+    /// Watchpoints set by the client, with their address, length and kind.
+    watchpoints: Vec<(u64, u64, WatchKind)>,
+    target_xml: String,
+    no_ack: bool,
+}
+
+impl<'a, U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> GdbStub<'a, U, S, EEI, N> {
+    /// Creates a stub controlling the given hart.
+    pub fn new(hart: &'a mut RVI<U, S, EEI, N>) -> Self {
+        let mut stub = Self {
+            hart,
+            software_breakpoints: HashSet::new(),
+            hardware_breakpoints: HashSet::new(),
+            watchpoints: Vec::new(),
+            target_xml: String::new(),
+            no_ack: false,
+        };
+        stub.target_xml = stub.build_target_xml();
+        stub
+    }
+
+    /// Returns the target description sent to the client.
+    pub fn target_xml(&self) -> &str {
+        &self.target_xml
+    }
+
+    /// Waits for a client on the given address and serves it until it detaches or disconnects.
+    pub fn listen<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<()> {
+        let (stream, _) = TcpListener::bind(addr)?.accept()?;
+        stream.set_nodelay(true)?;
+        self.serve(stream.try_clone()?, stream)
+    }
+
+    /// Serves the client connected to the given streams until it detaches or disconnects.
+    ///
+    /// `reader` is read from a separate thread so the client can interrupt the hart while it is running.
+    pub fn serve<R: Read + Send + 'static, W: Write>(&mut self, reader: R, mut writer: W) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in BufReader::new(reader).bytes() {
+                match byte {
+                    Ok(byte) if sender.send(byte).is_ok() => (),
+                    _ => break,
+                }
+            }
+        });
+
+        self.no_ack = false;
+        while let Some(packet) = self.read_packet(&receiver, &mut writer)? {
+            let reply = match packet.first() {
+                Some(b'c') | Some(b's') => {
+                    let step = packet[0] == b's';
+                    if let Some(addr) = packet.get(1..).filter(|addr| !addr.is_empty()).and_then(parse_hex) {
+                        self.hart.pc = U::from_u64(addr);
+                    }
+                    match self.resume(step, &receiver) {
+                        Stop::Disconnected => return Ok(()),
+                        stop => self.stop_reply(stop),
+                    }
+                },
+                Some(b'D') => {
+                    self.write_packet(&mut writer, b"OK")?;
+                    return Ok(());
+                },
+                Some(b'k') => return Ok(()),
+                _ => self.handle(&packet),
+            };
+            self.write_packet(&mut writer, reply.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Handles the packets that do not resume the hart, and returns the reply.
+    fn handle(&mut self, packet: &[u8]) -> String {
+        let packet = String::from_utf8_lossy(packet);
+        if packet.is_empty() || !packet.is_char_boundary(1) {
+            return String::new();
+        }
+        let (command, args) = packet.split_at(1);
+        match command {
+            "?" => self.stop_reply(Stop::Signal(SIGTRAP)),
+            "g" => (0..N).chain(std::iter::once(32)).map(|reg| self.encode_register(reg).unwrap()).collect(),
+            "G" => {
+                let size = Self::XLEN / 4;
+                for (i, reg) in (0..N).chain(std::iter::once(32)).enumerate() {
+                    match args.get(i * size..(i + 1) * size).and_then(decode_le) {
+                        Some(value) => self.write_register(reg, value),
+                        None => return String::from("E01"),
+                    };
+                }
+                String::from("OK")
+            },
+            "p" => parse_hex(args.as_bytes()).and_then(|reg| self.encode_register(reg as usize)).unwrap_or_else(|| String::from("E01")),
+            "P" => {
+                let value = args.split_once('=').and_then(|(reg, value)| Some((parse_hex(reg.as_bytes())?, decode_le(value)?)));
+                match value {
+                    Some((reg, value)) if self.write_register(reg as usize, value) => String::from("OK"),
+                    _ => String::from("E01"),
+                }
+            },
+            "m" => {
+                let range = args.split_once(',').and_then(|(addr, len)| Some((parse_hex(addr.as_bytes())?, parse_hex(len.as_bytes())?)));
+                match range {
+                    // The reply has two hexadecimal digits per byte.
+                    Some((_, len)) if len > (PACKET_SIZE / 2) as u64 => String::from("E01"),
+                    Some((addr, len)) => self.read_memory(addr, len).unwrap_or_else(|| String::from("E14")),
+                    None => String::from("E01"),
+                }
+            },
+            "M" => {
+                let write = args.split_once(':').and_then(|(range, data)| {
+                    let (addr, _) = range.split_once(',')?;
+                    Some((parse_hex(addr.as_bytes())?, decode_hex(data)?))
+                });
+                match write {
+                    Some((addr, data)) => match self.write_memory(addr, &data) {
+                        true => String::from("OK"),
+                        false => String::from("E14"),
+                    },
+                    None => String::from("E01"),
+                }
+            },
+            "Z" | "z" => self.breakpoint(command == "Z", args),
+            "H" | "T" => String::from("OK"),
+            "q" | "Q" => self.query(&packet),
+            _ => String::new(),
+        }
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            format!("PacketSize={:x};qXfer:features:read+;QStartNoAckMode+;swbreak+;hwbreak+", PACKET_SIZE)
+        } else if packet == "QStartNoAckMode" {
+            self.no_ack = true;
+            String::from("OK")
+        } else if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            let range = range.split_once(',').and_then(|(offset, len)| Some((parse_hex(offset.as_bytes())?, parse_hex(len.as_bytes())?)));
+            match range {
+                Some((offset, len)) => {
+                    let xml = self.target_xml.as_bytes();
+                    let start = (offset as usize).min(xml.len());
+                    let end = start.saturating_add(len as usize).min(xml.len());
+                    let prefix = if end < xml.len() { 'm' } else { 'l' };
+                    format!("{}{}", prefix, String::from_utf8_lossy(&xml[start..end]))
+                },
+                None => String::from("E01"),
+            }
+        } else if packet.starts_with("qXfer:features:read:") {
+            String::from("E00")
+        } else if packet == "qAttached" {
+            String::from("1")
+        } else if packet == "qC" {
+            String::from("QC1")
+        } else if packet == "qfThreadInfo" {
+            String::from("m1")
+        } else if packet == "qsThreadInfo" {
+            String::from("l")
+        } else if packet.starts_with("qSymbol") {
+            String::from("OK")
+        } else {
+            String::new()
+        }
+    }
+
+    /// Handles `Z` (`insert` is true) and `z` packets.
+    fn breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split([',', ';']).map(|field| parse_hex(field.as_bytes()));
+        let (kind, addr, len) = match (fields.next().flatten(), fields.next().flatten(), fields.next().flatten()) {
+            (Some(kind), Some(addr), Some(len)) => (kind, addr, len),
+            _ => return String::from("E01"),
+        };
+
+        let (breakpoints, others) = match kind {
+            0 => (&mut self.software_breakpoints, &self.hardware_breakpoints),
+            1 => (&mut self.hardware_breakpoints, &self.software_breakpoints),
+            _ => {
+                let kind = match watch_kind(kind) {
+                    Some(kind) => kind,
+                    None => return String::new(),
+                };
+                if insert {
+                    self.hart.add_watchpoint(U::from_u64(addr), len, kind);
+                    self.watchpoints.push((addr, len, kind));
+                } else if let Some(index) = self.watchpoints.iter().position(|&w| w == (addr, len, kind)) {
+                    self.hart.remove_watchpoint(U::from_u64(addr), len, kind);
+                    self.watchpoints.remove(index);
+                }
+                return String::from("OK");
+            },
+        };
+        // Software and hardware breakpoints at the same address share the breakpoint of the hart.
+        if insert {
+            breakpoints.insert(addr);
+            self.hart.add_breakpoint(U::from_u64(addr));
+        } else if breakpoints.remove(&addr) && !others.contains(&addr) {
+            self.hart.remove_breakpoint(U::from_u64(addr));
+        }
+        String::from("OK")
+    }
+
+    /// Executes one instruction if `step` is true, or until a breakpoint, a watchpoint, a trap or an interrupt request.
+    fn resume(&mut self, step: bool, receiver: &Receiver<u8>) -> Stop {
+        let max_instructions = if step { 1 } else { INTERRUPT_CHECK_INTERVAL };
+        loop {
+            match self.hart.run_until_stop(max_instructions) {
+                StopReason::Breakpoint { pc } if self.software_breakpoints.contains(&pc.as_u64()) => return Stop::SoftwareBreakpoint,
+                StopReason::Breakpoint { .. } => return Stop::HardwareBreakpoint,
+                StopReason::Condition { .. } => return Stop::Signal(SIGTRAP),
+                StopReason::Watchpoint { kind, addr, .. } => return Stop::Watchpoint(kind, addr.as_u64()),
+                StopReason::Trap { cause, .. } if !self.hart.config.privileged => return Stop::Signal(Self::signal(cause)),
+                _ if step => return Stop::Signal(SIGTRAP),
+                _ => (),
+            }
+            match receiver.try_recv() {
+                Ok(0x03) => return Stop::Signal(SIGINT),
+                Err(TryRecvError::Disconnected) => return Stop::Disconnected,
+                _ => (),
+            }
+        }
+    }
+
+    fn signal(trap: Traps) -> u8 {
+        match trap {
+            Traps::IllegalInstruction => SIGILL,
+            Traps::InstructionAddressMisaligned | Traps::LoadAddressMisaligned | Traps::StoreAMOAddressMisaligned => SIGBUS,
+            Traps::InstructionAccessFault | Traps::LoadAccessFault | Traps::StoreAMOAccessFault |
+            Traps::InstructionPageFault | Traps::LoadPageFault | Traps::StoreAMOPageFault => SIGSEGV,
+            _ => SIGTRAP,
+        }
+    }
+
+    fn stop_reply(&self, stop: Stop) -> String {
+        match stop {
+            Stop::Signal(signal) => format!("T{:02x}", signal),
+            Stop::SoftwareBreakpoint => format!("T{:02x}swbreak:;", SIGTRAP),
+            Stop::HardwareBreakpoint => format!("T{:02x}hwbreak:;", SIGTRAP),
+            Stop::Watchpoint(kind, addr) => format!("T{:02x}{}:{:x};", SIGTRAP, watch_reason(kind), addr),
+            Stop::Disconnected => String::new(),
+        }
+    }
+
+    const XLEN: usize = RVI::<U, S, EEI, N>::XLEN;
+
+    /// Width of the floating-point registers in bits, 0 if there are none.
+    fn flen(&self) -> usize {
+        if self.hart.has_extension('D') {
+            64
+        } else if self.hart.has_extension('F') {
+            32
+        } else {
+            0
+        }
+    }
+
+    /// Returns the value of the given register and its size in bytes.
+    fn read_register(&self, reg: usize) -> Option<(u64, usize)> {
+        let flen = self.flen();
+        match reg {
+            0..=31 => self.hart.x.get(reg).map(|x| (x.as_u64(), Self::XLEN / 8)),
+            32 => Some((self.hart.pc.as_u64(), Self::XLEN / 8)),
+            33..=64 if flen != 0 => Some((self.hart.f[reg - 33], flen / 8)),
+            _ if (CSR_FIRST_REGNUM..CSR_FIRST_REGNUM + 4096).contains(&reg) => {
+                let csr = (reg - CSR_FIRST_REGNUM) as u16;
+                // The floating-point CSRs are described as 32-bit registers of the fpu feature.
+                let size = if (1..=3).contains(&csr) { 4 } else { Self::XLEN / 8 };
+                self.hart.read_csr(csr).map(|value| (value.as_u64(), size))
+            },
+            _ => None,
+        }
+    }
+
+    /// Writes the given register, returns false if it does not exist or is read-only.
+    fn write_register(&mut self, reg: usize, value: u64) -> bool {
+        let flen = self.flen();
+        match reg {
+            0 => true, // x0 is hardwired to 0.
+            1..=31 if reg < N => {
+                self.hart.x[reg] = U::from_u64(value).as_s();
+                true
+            },
+            32 => {
+                self.hart.pc = U::from_u64(value);
+                true
+            },
+            33..=64 if flen == 64 => {
+                self.hart.f[reg - 33] = value;
+                true
+            },
+            33..=64 if flen == 32 => {
+                self.hart.f[reg - 33] = value | 0xFFFF_FFFF_0000_0000;
+                true
+            },
+            _ if (CSR_FIRST_REGNUM..CSR_FIRST_REGNUM + 4096).contains(&reg) => {
+                self.hart.write_csr((reg - CSR_FIRST_REGNUM) as u16, U::from_u64(value))
+            },
+            _ => false,
+        }
+    }
+
+    fn encode_register(&self, reg: usize) -> Option<String> {
+        let (value, size) = self.read_register(reg)?;
+        Some(value.to_le_bytes()[..size].iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    fn read_memory(&mut self, addr: u64, len: u64) -> Option<String> {
+        let mut data = String::with_capacity(2 * len as usize);
+        for i in 0..len {
+            let byte = self.hart.eei_mut().get_8(U::from_u64(addr.wrapping_add(i))).ok()?;
+            write!(data, "{:02x}", byte).unwrap();
+        }
+        Some(data)
+    }
+
+    fn write_memory(&mut self, addr: u64, data: &[u8]) -> bool {
+        data.iter().enumerate().all(|(i, &byte)| self.hart.eei_mut().set_8(U::from_u64(addr.wrapping_add(i as u64)), byte).is_ok())
+    }
+
+    fn build_target_xml(&self) -> String {
+        let xlen = Self::XLEN;
+        let mut xml = format!("<?xml version=\"1.0\"?>\n<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n<target version=\"1.0\">\n\
+            <architecture>riscv:rv{}</architecture>\n<feature name=\"org.gnu.gdb.riscv.cpu\">\n", xlen);
+        for reg in 0..N {
+            let kind = match reg {
+                1 => "code_ptr",
+                2..=4 => "data_ptr",
+                _ => "int",
+            };
+            writeln!(xml, "<reg name=\"{}\" bitsize=\"{}\" type=\"{}\" regnum=\"{}\"/>", get_x_register_name(reg as u8, true), xlen, kind, reg).unwrap();
+        }
+        writeln!(xml, "<reg name=\"pc\" bitsize=\"{}\" type=\"code_ptr\" regnum=\"32\"/>\n</feature>", xlen).unwrap();
+
+        let flen = self.flen();
+        if flen != 0 {
+            xml.push_str("<feature name=\"org.gnu.gdb.riscv.fpu\">\n");
+            let kind = if flen == 64 { "ieee_double" } else { "ieee_single" };
+            for reg in 0..32 {
+                writeln!(xml, "<reg name=\"{}\" bitsize=\"{}\" type=\"{}\" regnum=\"{}\"/>", get_f_register_name(reg as u8, true), flen, kind, 33 + reg).unwrap();
+            }
+            for csr in 1..=3 {
+                writeln!(xml, "<reg name=\"{}\" bitsize=\"32\" type=\"int\" regnum=\"{}\"/>", get_csr_name(csr), CSR_FIRST_REGNUM + csr as usize).unwrap();
+            }
+            xml.push_str("</feature>\n");
+        }
+
+        xml.push_str("<feature name=\"org.gnu.gdb.riscv.csr\">\n");
+        for csr in (4..4096u16).filter(|&csr| self.hart.read_csr(csr).is_some()) {
+            let name = get_csr_name(csr);
+            let name = if name.starts_with("0X") { format!("csr{}", csr) } else { name };
+            writeln!(xml, "<reg name=\"{}\" bitsize=\"{}\" type=\"int\" regnum=\"{}\" group=\"csr\"/>", name, xlen, CSR_FIRST_REGNUM + csr as usize).unwrap();
+        }
+        xml.push_str("</feature>\n</target>\n");
+        xml
+    }
+
+    /// Reads the next packet, acknowledging it if needed. Returns None when the client disconnects.
+    fn read_packet<W: Write>(&mut self, receiver: &Receiver<u8>, writer: &mut W) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            // Acknowledgements and interrupt requests outside of packets are ignored.
+            let mut byte = match receiver.recv() {
+                Ok(byte) => byte,
+                Err(_) => return Ok(None),
+            };
+            if byte != b'$' {
+                continue;
+            }
+
+            let mut packet = Vec::new();
+            loop {
+                byte = match receiver.recv() {
+                    Ok(byte) => byte,
+                    Err(_) => return Ok(None),
+                };
+                if byte == b'#' {
+                    break;
+                }
+                packet.push(byte);
+            }
+            let checksum: Vec<u8> = receiver.iter().take(2).collect();
+            let valid = parse_hex(&checksum) == Some(packet.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) as u64);
+
+            if !self.no_ack {
+                writer.write_all(if valid { b"+" } else { b"-" })?;
+                writer.flush()?;
+            }
+            if valid || self.no_ack {
+                return Ok(Some(unescape(&packet)));
+            }
+        }
+    }
+
+    fn write_packet<W: Write>(&self, writer: &mut W, data: &[u8]) -> io::Result<()> {
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        for &byte in data {
+            if matches!(byte, b'$' | b'#' | b'}' | b'*') {
+                packet.extend_from_slice(&[b'}', byte ^ 0x20]);
+            } else {
+                packet.push(byte);
+            }
+        }
+        let checksum = packet[1..].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        write!(packet, "#{:02x}", checksum)?;
+        writer.write_all(&packet)?;
+        writer.flush()
+    }
+}
+
+/// Removes the breakpoints and watchpoints set by the client from the hart.
+impl<'a, U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> Drop for GdbStub<'a, U, S, EEI, N> {
+    fn drop(&mut self) {
+        for &addr in self.software_breakpoints.union(&self.hardware_breakpoints) {
+            self.hart.remove_breakpoint(U::from_u64(addr));
+        }
+        for &(addr, len, kind) in &self.watchpoints {
+            self.hart.remove_watchpoint(U::from_u64(addr), len, kind);
+        }
+    }
+}
+
+/// Removes the `}` escapes of binary data.
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'}' => out.extend(bytes.next().map(|b| b ^ 0x20)),
+            _ => out.push(byte),
+        }
+    }
+    out
+}
+
+fn parse_hex(hex: &[u8]) -> Option<u64> {
+    u64::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Decodes a little-endian register value.
+fn decode_le(hex: &str) -> Option<u64> {
+    let bytes = decode_hex(hex)?;
+    if bytes.len() > 8 {
+        return None;
+    }
+    Some(bytes.iter().rev().fold(0, |value, &byte| value << 8 | byte as u64))
+}
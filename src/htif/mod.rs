@@ -0,0 +1,254 @@
+//! Host-target interface (HTIF) used by riscv-tests and the proxy kernel to signal the end of the test and print to the console.
+//!
+//! [`Htif`] wraps the memory of the hart and watches the stores to the `tohost` symbol. A command is handled when the last byte
+//! of `tohost` is written, so RV32 programs writing the low word then the high word are supported. `tohost` is then cleared
+//! and the response, if any, is written to `fromhost`.
+//!
+//! The commands are 64-bits values with the device in bits 63:56, the command in bits 55:48 and the payload in bits 47:0:
+//! - device 0, command 0, payload with bit 0 set: exit with the code `payload >> 1`, 0 meaning success.
+//!   riscv-tests write the number of the failing test as the exit code.
+//! - device 0, command 0, payload with bit 0 clear: proxied system call. The payload is the address of an array of
+//!   8 double-words containing the system call number and its arguments. `write` (64) to stdout or stderr and `exit` (93)
+//!   are supported, the others return `-ENOSYS`. `write` copies at most 64 KiB per call and returns `-EFAULT` if the buffer
+//!   is not readable. The return value is written to the first double-word.
+//! - device 1, command 1: write the low byte of the payload to the console.
+
+use std::convert::TryFrom;
+
+use crate::common::types::{Signed, Unsigned};
+use crate::elf::ElfFile;
+use crate::public::{AMO, ExecutionEnvironmentInterface, MemoryAccess, MemoryFault, Traps};
+use crate::rvi::RVI;
+
+const SYS_WRITE: u64 = 64;
+const SYS_EXIT: u64 = 93;
+const EFAULT: u64 = 14;
+const ENOSYS: u64 = 38;
+/// Maximum number of bytes copied by a `write` system call.
+const MAX_WRITE_LEN: u64 = 0x1_0000;
+
+/// HTIF wrapper around the memory of the hart. See the [module documentation](self).
+pub struct Htif<M> {
+    /// The wrapped memory and execution environment.
+    pub memory: M,
+    /// Address of `tohost`.
+    pub tohost: u64,
+    /// Address of `fromhost`, responses are not written if None.
+    pub fromhost: Option<u64>,
+    /// The bytes written to the console and to stdout and stderr by the program.
+    pub console: Vec<u8>,
+    exit_code: Option<u64>,
+}
+
+impl<M> Htif<M> {
+    /// Wraps the given memory with `tohost` and `fromhost` at the given addresses.
+    pub fn new(memory: M, tohost: u64, fromhost: Option<u64>) -> Self {
+        Self {
+            memory,
+            tohost,
+            fromhost,
+            console: Vec::new(),
+            exit_code: None,
+        }
+    }
+
+    /// Wraps the given memory with the addresses of the `tohost` and `fromhost` symbols of the given file.
+    /// Returns None if the file has no `tohost` symbol.
+    pub fn from_elf(memory: M, elf: &ElfFile) -> Option<Self> {
+        let tohost = elf.symbol("tohost")?.value;
+        Some(Self::new(memory, tohost, elf.symbol("fromhost").map(|symbol| symbol.value)))
+    }
+
+    /// Returns the exit code written by the program, or None if it has not exited.
+    pub fn exit_code(&self) -> Option<u64> {
+        self.exit_code
+    }
+
+    /// Returns true if the program has exited with code 0.
+    pub fn passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Handles the command in `tohost` if the store of `size` bytes at `addr` wrote its last byte.
+    fn stored<ADDR: Copy + Into<u64> + TryFrom<u64>>(&mut self, addr: ADDR, size: u64) where M: MemoryAccess<ADDR> {
+        let addr = addr.into();
+        let last = match self.tohost.checked_add(7) {
+            Some(last) => last,
+            None => return,
+        };
+        if addr <= last && last - addr < size {
+            let _ = self.command();
+        }
+    }
+
+    fn command<ADDR: Copy + Into<u64> + TryFrom<u64>>(&mut self) -> Result<(), MemoryFault> where M: MemoryAccess<ADDR> {
+        let tohost = address(self.tohost)?;
+        let value = self.memory.get_64(tohost)?;
+        if value == 0 {
+            return Ok(());
+        }
+        self.memory.set_64(tohost, 0)?;
+
+        let payload = value & 0xFFFF_FFFF_FFFF;
+        match (value >> 56, value >> 48 & 0xFF) {
+            (0, 0) if payload & 1 != 0 => self.exit_code = Some(payload >> 1),
+            (0, 0) => {
+                self.syscall(payload)?;
+                self.respond(1)?;
+            },
+            (1, 1) => {
+                self.console.push(payload as u8);
+                self.respond(value & !0xFFFF_FFFF_FFFF)?;
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn syscall<ADDR: Copy + Into<u64> + TryFrom<u64>>(&mut self, magic_mem: u64) -> Result<(), MemoryFault> where M: MemoryAccess<ADDR> {
+        let mut args = [0u64; 4];
+        for (i, arg) in args.iter_mut().enumerate() {
+            let addr = magic_mem.checked_add(8 * i as u64).ok_or(MemoryFault::AccessFault)?;
+            *arg = self.memory.get_64(address(addr)?)?;
+        }
+
+        let ret = match args {
+            [SYS_WRITE, 1 | 2, buf, len] => {
+                let memory = &mut self.memory;
+                let bytes = (0..len.min(MAX_WRITE_LEN))
+                    .map(|i| memory.get_8(address(buf.checked_add(i).ok_or(MemoryFault::AccessFault)?)?))
+                    .collect::<Result<Vec<_>, _>>();
+                match bytes {
+                    Ok(bytes) => {
+                        self.console.extend_from_slice(&bytes);
+                        bytes.len() as u64
+                    },
+                    Err(_) => EFAULT.wrapping_neg(),
+                }
+            },
+            [SYS_EXIT, code, ..] => {
+                self.exit_code = Some(code);
+                0
+            },
+            _ => ENOSYS.wrapping_neg(),
+        };
+        self.memory.set_64(address(magic_mem)?, ret)
+    }
+
+    fn respond<ADDR: Copy + Into<u64> + TryFrom<u64>>(&mut self, value: u64) -> Result<(), MemoryFault> where M: MemoryAccess<ADDR> {
+        match self.fromhost {
+            Some(fromhost) => self.memory.set_64(address(fromhost)?, value),
+            None => Ok(()),
+        }
+    }
+}
+
+fn address<ADDR: TryFrom<u64>>(addr: u64) -> Result<ADDR, MemoryFault> {
+    ADDR::try_from(addr).map_err(|_| MemoryFault::AccessFault)
+}
+
+impl<ADDR: Copy + Into<u64> + TryFrom<u64>, M: MemoryAccess<ADDR>> MemoryAccess<ADDR> for Htif<M> {
+    fn get_8(&mut self, addr: ADDR) -> Result<u8, MemoryFault> {
+        self.memory.get_8(addr)
+    }
+
+    fn get_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault> {
+        self.memory.get_16(addr)
+    }
+
+    fn get_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.memory.get_32(addr)
+    }
+
+    fn get_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault> {
+        self.memory.get_64(addr)
+    }
+
+    fn set_8(&mut self, addr: ADDR, data: u8) -> Result<(), MemoryFault> {
+        self.memory.set_8(addr, data)?;
+        self.stored(addr, 1);
+        Ok(())
+    }
+
+    fn set_16(&mut self, addr: ADDR, data: u16) -> Result<(), MemoryFault> {
+        self.memory.set_16(addr, data)?;
+        self.stored(addr, 2);
+        Ok(())
+    }
+
+    fn set_32(&mut self, addr: ADDR, data: u32) -> Result<(), MemoryFault> {
+        self.memory.set_32(addr, data)?;
+        self.stored(addr, 4);
+        Ok(())
+    }
+
+    fn set_64(&mut self, addr: ADDR, data: u64) -> Result<(), MemoryFault> {
+        self.memory.set_64(addr, data)?;
+        self.stored(addr, 8);
+        Ok(())
+    }
+
+    fn get_opcode_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.memory.get_opcode_32(addr)
+    }
+
+    fn get_opcode_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault> {
+        self.memory.get_opcode_16(addr)
+    }
+
+    fn amo_32(&mut self, addr: ADDR, op: AMO, value: u32) -> Result<u32, MemoryFault> {
+        let old = self.memory.amo_32(addr, op, value)?;
+        self.stored(addr, 4);
+        Ok(old)
+    }
+
+    fn amo_64(&mut self, addr: ADDR, op: AMO, value: u64) -> Result<u64, MemoryFault> {
+        let old = self.memory.amo_64(addr, op, value)?;
+        self.stored(addr, 8);
+        Ok(old)
+    }
+
+    fn load_reserved_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.memory.load_reserved_32(addr)
+    }
+
+    fn load_reserved_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault> {
+        self.memory.load_reserved_64(addr)
+    }
+
+    fn store_conditional_32(&mut self, addr: ADDR, data: u32) -> Result<bool, MemoryFault> {
+        let stored = self.memory.store_conditional_32(addr, data)?;
+        if stored {
+            self.stored(addr, 4);
+        }
+        Ok(stored)
+    }
+
+    fn store_conditional_64(&mut self, addr: ADDR, data: u64) -> Result<bool, MemoryFault> {
+        let stored = self.memory.store_conditional_64(addr, data)?;
+        if stored {
+            self.stored(addr, 8);
+        }
+        Ok(stored)
+    }
+}
+
+impl<ADDR: Copy + Into<u64> + TryFrom<u64>, M: ExecutionEnvironmentInterface<ADDR>> ExecutionEnvironmentInterface<ADDR> for Htif<M> {
+    fn trap(&mut self, trap: Traps, tval: ADDR, pc: ADDR) {
+        self.memory.trap(trap, tval, pc);
+    }
+}
+
+/// Executes at most `max_instructions` steps on the hart, stopping when the program exits through HTIF.
+///
+/// Returns the exit code, or None if the program has not exited.
+pub fn run<U, S, M, const N: usize>(hart: &mut RVI<U, S, Htif<M>, N>, max_instructions: usize) -> Option<u64>
+    where U: Unsigned<S> + Into<u64> + TryFrom<u64>, S: Signed<U>, M: ExecutionEnvironmentInterface<U> {
+    for _ in 0..max_instructions {
+        hart.single_step();
+        if let Some(code) = hart.eei().exit_code() {
+            return Some(code);
+        }
+    }
+    hart.eei().exit_code()
+}
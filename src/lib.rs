@@ -3,6 +3,12 @@
 #![allow(overflowing_literals)]
 #![allow(type_alias_bounds)]
 
+pub mod bus;
 pub mod common;
+pub mod compliance;
+pub mod elf;
+pub mod gdb;
+pub mod htif;
+pub mod lockstep;
 pub mod public;
 pub mod rvi;
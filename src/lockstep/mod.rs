@@ -0,0 +1,448 @@
+//! Differential checking of a hart against a reference commit log.
+//!
+//! [`Lockstep`] executes a hart one instruction at a time, reads the matching instruction retired by the reference
+//! from a Spike commit log ([`SpikeLog`], `spike --log-commits`) or RVFI-DII execution packets ([`RvfiLog`]),
+//! and stops at the first instruction whose PC, destination register value or memory writes differ.
+//! The resulting [`Divergence`] reports both instructions, the registers of the hart and its last instructions.
+//!
+//! The hart records its memory accesses with [`RVI::record_accesses`], so any execution environment can be used.
+//! Floating-point destination registers are only checked when the reference reports them, which RVFI does not.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::io::{self, BufRead, Read};
+
+use crate::common::{get_f_register_name, get_x_register_name, types::*};
+use crate::public::ExecutionEnvironmentInterface;
+use crate::rvi::{StepResult, RVI, rvfi::{RvfiRecord, RVFI_PACKET_SIZE}};
+
+/// Maximum number of traps taken by the hart before an instruction of the reference.
+/// The hart is stuck in a trap loop if it reaches it, which is reported as a [`Mismatch::Trap`].
+pub const MAX_TRAPS: usize = 64;
+
+/// Register written by an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    X(u8),
+    F(u8),
+}
+
+/// Store performed by an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Store {
+    pub addr: u64,
+    /// The size in bytes.
+    pub size: u64,
+    pub data: u64,
+}
+
+/// Instruction retired by the reference or by the hart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Commit {
+    pub pc: u64,
+    /// The instruction bits, if known.
+    pub insn: Option<u32>,
+    /// The written register and its new value. Writes to `x0` are ignored.
+    pub rd: Option<(Register, u64)>,
+    pub stores: Vec<Store>,
+    /// True if the instruction trapped instead of retiring. Only reported by RVFI.
+    pub trap: bool,
+}
+
+impl fmt::Display for Commit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pc {:#x}", self.pc)?;
+        if let Some(insn) = self.insn {
+            write!(f, " ({:#010x})", insn)?;
+        }
+        match self.rd {
+            Some((Register::X(reg), value)) => write!(f, " {} {:#x}", get_x_register_name(reg, false), value)?,
+            Some((Register::F(reg), value)) => write!(f, " {} {:#x}", get_f_register_name(reg, false), value)?,
+            None => (),
+        }
+        for store in &self.stores {
+            write!(f, " mem {:#x} {:#x} ({} bytes)", store.addr, store.data, store.size)?;
+        }
+        if self.trap {
+            write!(f, " trap")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the retired instructions of a Spike commit log. Lines other than commits (disassembly, traps) are skipped.
+pub struct SpikeLog<R: BufRead> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> SpikeLog<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: String::new() }
+    }
+
+    /// Parses a commit line, returns None if the line is not a commit.
+    pub fn parse_line(line: &str) -> Option<io::Result<Commit>> {
+        let (core, rest) = line.split_once(':')?;
+        if !core.starts_with("core") {
+            return None;
+        }
+        let mut tokens = rest.split_whitespace();
+        // The privilege mode, absent from the disassembly and trap lines.
+        if tokens.next()?.parse::<u8>().is_err() {
+            return None;
+        }
+        Some(Self::parse_commit(tokens.collect()).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid commit line: {}", line.trim_end()))
+        }))
+    }
+
+    fn parse_commit(tokens: Vec<&str>) -> Option<Commit> {
+        let insn = tokens.get(1)?.strip_prefix('(')?.strip_suffix(')')?;
+        let mut commit = Commit { pc: parse_hex(tokens[0])?, insn: Some(parse_hex(insn)? as u32), ..Commit::default() };
+
+        let mut i = 2;
+        while i < tokens.len() {
+            let token = tokens[i];
+            if token == "mem" {
+                let addr = parse_hex(tokens.get(i + 1)?)?;
+                // Loads only have an address, stores are followed by their value.
+                match tokens.get(i + 2).filter(|value| value.starts_with("0x")) {
+                    Some(value) => {
+                        let size = (value.len() as u64 - 2) / 2;
+                        commit.stores.push(Store { addr, size, data: parse_hex(value)? });
+                        i += 3;
+                    },
+                    None => i += 2,
+                }
+                continue;
+            }
+
+            // Registers are written `x5` or `x 5` depending on the version of Spike, and CSRs `c768_mstatus`.
+            let (name, next) = match tokens.get(i + 1) {
+                Some(&number) if token.len() == 1 && number.parse::<u8>().is_ok() => (format!("{}{}", token, number), i + 2),
+                _ => (String::from(token), i + 1),
+            };
+            let value = parse_hex(tokens.get(next)?)?;
+            let reg = name.get(1..).and_then(|number| number.parse::<u8>().ok());
+            match (name.as_bytes()[0], reg) {
+                (b'x', Some(reg)) if reg < 32 => commit.rd = (reg != 0).then_some((Register::X(reg), value)),
+                (b'f', Some(reg)) if reg < 32 => commit.rd = Some((Register::F(reg), value)),
+                (b'c', _) | (b'v', _) => (),
+                _ => return None,
+            }
+            i = next + 1;
+        }
+        Some(commit)
+    }
+}
+
+impl<R: BufRead> Iterator for SpikeLog<R> {
+    type Item = io::Result<Commit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    if let Some(commit) = Self::parse_line(&self.line) {
+                        return Some(commit);
+                    }
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Reads the retired instructions of a stream of RVFI-DII execution packets, until the packet with `rvfi_halt` set.
+pub struct RvfiLog<R: Read> {
+    reader: R,
+    halted: bool,
+}
+
+impl<R: Read> RvfiLog<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, halted: false }
+    }
+
+    /// Converts an execution packet. Returns None for the halt packet.
+    pub fn parse_packet(packet: &[u8; RVFI_PACKET_SIZE]) -> Option<Commit> {
+        let record = RvfiRecord::from_packet(packet);
+        if record.halt {
+            return None;
+        }
+
+        let mut commit = Commit {
+            pc: record.pc_rdata,
+            insn: Some(record.insn as u32),
+            rd: (record.rd_addr != 0).then_some((Register::X(record.rd_addr), record.rd_wdata)),
+            stores: Vec::new(),
+            trap: record.trap,
+        };
+        if record.mem_wmask != 0 {
+            let offset = record.mem_wmask.trailing_zeros() as u64;
+            let size = record.mem_wmask.count_ones() as u64;
+            commit.stores.push(Store { addr: record.mem_addr + offset, size, data: record.mem_wdata >> (offset * 8) });
+        }
+        Some(commit)
+    }
+}
+
+impl<R: Read> Iterator for RvfiLog<R> {
+    type Item = io::Result<Commit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.halted {
+            return None;
+        }
+        let mut packet = [0; RVFI_PACKET_SIZE];
+        match self.reader.read_exact(&mut packet) {
+            Ok(()) => match Self::parse_packet(&packet) {
+                Some(commit) => Some(Ok(commit)),
+                None => {
+                    self.halted = true;
+                    None
+                },
+            },
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// What differs between the reference and the hart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    Pc,
+    Register,
+    MemoryWrite,
+    /// One of them trapped and the other did not, or the hart took [`MAX_TRAPS`] traps in a row.
+    Trap,
+}
+
+/// First instruction on which the hart diverged from the reference.
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    /// Number of instructions checked before this one.
+    pub index: usize,
+    pub mismatch: Mismatch,
+    pub expected: Commit,
+    pub actual: Commit,
+    /// The registers of the hart, its last instructions and the diff of the instruction.
+    pub report: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.report)
+    }
+}
+
+/// Errors returned by [`Lockstep::run`].
+#[derive(Debug)]
+pub enum LockstepError {
+    /// The reference could not be read.
+    Reference(io::Error),
+    Divergence(Box<Divergence>),
+}
+
+impl fmt::Display for LockstepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Reference(err) => write!(f, "failed to read the reference: {}", err),
+            Self::Divergence(divergence) => write!(f, "{}", divergence),
+        }
+    }
+}
+
+impl std::error::Error for LockstepError {}
+
+/// Checks a hart against a reference commit log. See the [module documentation](self).
+pub struct Lockstep<I: Iterator<Item = io::Result<Commit>>> {
+    reference: std::iter::Peekable<I>,
+    /// Disassembly of the last instructions of the hart.
+    history: VecDeque<String>,
+    history_len: usize,
+    checked: usize,
+    synchronized: bool,
+}
+
+impl<I: Iterator<Item = io::Result<Commit>>> Lockstep<I> {
+    /// Checks against the given reference, keeping the last `history_len` instructions for the report.
+    ///
+    /// The instructions of the reference before the first one at the PC of the hart are skipped,
+    /// like the boot ROM of Spike.
+    pub fn new(reference: I, history_len: usize) -> Self {
+        Self { reference: reference.peekable(), history: VecDeque::with_capacity(history_len), history_len, checked: 0, synchronized: false }
+    }
+
+    /// Returns the number of instructions checked.
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// Executes and checks at most `max_instructions` steps, or until the end of the reference.
+    /// Returns the number of instructions checked.
+    pub fn run<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize>(
+        &mut self, hart: &mut RVI<U, S, EEI, N>, max_instructions: usize,
+    ) -> Result<usize, LockstepError> {
+        hart.record_accesses(true);
+        if !self.synchronized {
+            self.synchronize(hart.pc.as_u64())?;
+        }
+
+        for _ in 0..max_instructions {
+            let expected = match self.reference.next() {
+                Some(commit) => commit.map_err(LockstepError::Reference)?,
+                None => break,
+            };
+            self.step(hart, expected)?;
+        }
+        Ok(self.checked)
+    }
+
+    fn synchronize(&mut self, pc: u64) -> Result<(), LockstepError> {
+        while let Some(commit) = self.reference.peek() {
+            match commit {
+                Ok(commit) if commit.pc != pc => { self.reference.next(); },
+                Ok(_) => break,
+                Err(_) => return Err(LockstepError::Reference(self.reference.next().unwrap().unwrap_err())),
+            }
+        }
+        self.synchronized = true;
+        Ok(())
+    }
+
+    /// Executes the instruction matching `expected`, and the interrupts taken before it.
+    fn step<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize>(
+        &mut self, hart: &mut RVI<U, S, EEI, N>, expected: Commit,
+    ) -> Result<(), LockstepError> {
+        let mask = if RVI::<U, S, EEI, N>::XLEN == 32 { 0xFFFF_FFFF } else { u64::MAX };
+        let mut traps = 0;
+        let actual = loop {
+            match hart.single_step() {
+                StepResult::Trap { cause, pc, .. } => {
+                    traps += 1;
+                    // Interrupts are not instructions.
+                    let interrupt = cause.is_interrupt();
+                    let commit = Commit { pc: pc.as_u64(), insn: (!interrupt).then_some(hart.inst.opcode), trap: true, ..Commit::default() };
+                    // Exceptions are only reported by RVFI, Spike does not log the instructions that trapped.
+                    if (expected.trap && !interrupt) || traps == MAX_TRAPS {
+                        break commit;
+                    }
+                    if !interrupt {
+                        self.record(hart, &commit);
+                    }
+                },
+                StepResult::Retired { pc, inst } => {
+                    let rd = if inst.writes_x_register() && inst.rd != 0 {
+                        Some((Register::X(inst.rd), hart.x[inst.rd as usize].as_u64() & mask))
+                    } else if inst.writes_f_register() {
+                        Some((Register::F(inst.rd), hart.f[inst.rd as usize]))
+                    } else {
+                        None
+                    };
+                    let stores = hart.accesses().iter().filter(|access| access.store)
+                        .map(|access| Store { addr: access.addr & mask, size: access.size, data: access.data })
+                        .collect();
+                    break Commit { pc: pc.as_u64(), insn: Some(inst.opcode), rd, stores, trap: false };
+                },
+            }
+        };
+        self.record(hart, &actual);
+
+        let mismatch = if expected.pc & mask != actual.pc {
+            Some(Mismatch::Pc)
+        } else if expected.trap != actual.trap {
+            Some(Mismatch::Trap)
+        } else if !Self::same_rd(&expected, &actual, mask) {
+            Some(Mismatch::Register)
+        } else if !Self::same_stores(&expected, &actual, mask) {
+            Some(Mismatch::MemoryWrite)
+        } else {
+            None
+        };
+
+        match mismatch {
+            Some(mismatch) => {
+                let report = self.report(hart, mismatch, &expected, &actual);
+                Err(LockstepError::Divergence(Box::new(Divergence { index: self.checked, mismatch, expected, actual, report })))
+            },
+            None => {
+                self.checked += 1;
+                Ok(())
+            },
+        }
+    }
+
+    fn same_rd(expected: &Commit, actual: &Commit, mask: u64) -> bool {
+        match (expected.rd, actual.rd) {
+            (Some((Register::X(a), a_value)), Some((Register::X(b), b_value))) => a == b && a_value & mask == b_value,
+            (Some((Register::F(a), a_value)), Some((Register::F(b), b_value))) => {
+                // Spike logs single-precision registers on 32 bits when D is not supported.
+                let flen_mask = if a_value >> 32 == 0 { 0xFFFF_FFFF } else { u64::MAX };
+                a == b && a_value == b_value & flen_mask
+            },
+            (None, Some((Register::F(_), _))) => true,
+            (expected, actual) => expected == actual,
+        }
+    }
+
+    fn same_stores(expected: &Commit, actual: &Commit, mask: u64) -> bool {
+        let size_mask = |size: u64| if size >= 8 { u64::MAX } else { (1 << (size * 8)) - 1 };
+        expected.stores.len() == actual.stores.len() && expected.stores.iter().zip(&actual.stores).all(|(a, b)| {
+            a.addr & mask == b.addr && a.size == b.size && a.data & size_mask(a.size) == b.data & size_mask(b.size)
+        })
+    }
+
+    /// Adds the given instruction of the hart to the history.
+    fn record<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize>(
+        &mut self, hart: &RVI<U, S, EEI, N>, commit: &Commit,
+    ) {
+        if self.history_len == 0 {
+            return;
+        }
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+        self.history.push_back(format!("{:#010x}: {:<32} {}", commit.pc, hart.disassemble_instruction(hart.inst), commit));
+    }
+
+    fn report<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize>(
+        &self, hart: &RVI<U, S, EEI, N>, mismatch: Mismatch, expected: &Commit, actual: &Commit,
+    ) -> String {
+        let mut report = String::new();
+        writeln!(report, "{:?} mismatch at instruction {}", mismatch, self.checked).unwrap();
+        writeln!(report, "- reference: {}", expected).unwrap();
+        writeln!(report, "+ hart:      {}", actual).unwrap();
+
+        writeln!(report, "\nRegisters of the hart (* differs from the reference):").unwrap();
+        for (i, x) in hart.x.iter().enumerate() {
+            let differs = matches!(expected.rd, Some((Register::X(reg), _)) if reg as usize == i) && mismatch == Mismatch::Register;
+            let mark = if differs { '*' } else { ' ' };
+            write!(report, "{}{:>4} {:#018x}", mark, get_x_register_name(i as u8, true), x.as_u64()).unwrap();
+            report.push_str(if i % 4 == 3 { "\n" } else { "  " });
+        }
+        if hart.has_extension('F') {
+            for (i, f) in hart.f.iter().enumerate() {
+                let differs = matches!(expected.rd, Some((Register::F(reg), _)) if reg as usize == i) && mismatch == Mismatch::Register;
+                let mark = if differs { '*' } else { ' ' };
+                write!(report, "{}{:>4} {:#018x}", mark, get_f_register_name(i as u8, true), f).unwrap();
+                report.push_str(if i % 4 == 3 { "\n" } else { "  " });
+            }
+        }
+        writeln!(report, "    pc {:#018x}  mode {:?}", hart.pc.as_u64(), hart.mode).unwrap();
+
+        writeln!(report, "\nLast {} instructions of the hart:", self.history.len()).unwrap();
+        for line in &self.history {
+            writeln!(report, "  {}", line).unwrap();
+        }
+        report
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok()
+}
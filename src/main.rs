@@ -1,71 +1,181 @@
-use dyriscvic::public::*;
+use dyriscvic::bus::Bus;
+use dyriscvic::compliance;
+use dyriscvic::elf::ElfFile;
+use dyriscvic::gdb::GdbStub;
+use dyriscvic::htif;
+use dyriscvic::lockstep::{Commit, Lockstep, RvfiLog, SpikeLog};
 use dyriscvic::{rvi::*, common::*};
 
-struct ExecutionEnvironment {
-    pub memory: [u8; 4096],
-}
-
-impl MemoryAccess<u32> for ExecutionEnvironment {
-    fn get_8(&mut self, addr: u32) -> u8 {
-        return self.memory[addr as usize];
-    }
-
-    fn get_16(&mut self, addr: u32) -> u16 {
-        return (self.get_8(addr + 1) as u16) << 8 | self.get_8(addr) as u16;
-    }
-
-    fn get_32(&mut self, addr: u32) -> u32 {
-        return (self.get_16(addr + 2) as u32) << 16 | self.get_16(addr) as u32;
-    }
+/// Runs the given riscv-tests executable until it writes to `tohost`, and returns the exit code of the process.
+/// The commit log is written to the standard error if `log` is Some, with the disassembly if it is `Some(true)`,
+/// and the RVFI-DII execution packets to the file `rvfi` if it is Some.
+fn run_elf(path: &str, ext: Option<&str>, log: Option<bool>, rvfi: Option<&str>) -> i32 {
+    let data = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+    let elf = ElfFile::parse(&data).unwrap_or_else(|e| panic!("Invalid ELF file {}: {}", path, e));
+    let conf = compliance::config(&elf, ext);
+    elf.check_config(elf.xlen, &conf).unwrap_or_else(|e| panic!("Incompatible ELF file {}: {}", path, e));
+    let eei = compliance::load(&elf).unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+    let rvfi = rvfi.map(|rvfi| {
+        let file = std::fs::File::create(rvfi).unwrap_or_else(|e| panic!("Failed to create {}: {}", rvfi, e));
+        std::io::BufWriter::new(file)
+    });
+
+    let code = if elf.xlen == 32 {
+        let mut rv32 = RV32I::new([0; 32], elf.entry as u32, conf, eei);
+        if let Some(disassembly) = log {
+            rv32.set_commit_log(std::io::stderr(), disassembly);
+        }
+        if let Some(rvfi) = rvfi {
+            rv32.set_rvfi_dump(rvfi);
+        }
+        let code = htif::run(&mut rv32, 100_000_000);
+        print!("{}", String::from_utf8_lossy(&rv32.eei().console));
+        code
+    } else {
+        let mut rv64 = RV64I::new([0; 32], elf.entry, conf, eei);
+        if let Some(disassembly) = log {
+            rv64.set_commit_log(std::io::stderr(), disassembly);
+        }
+        if let Some(rvfi) = rvfi {
+            rv64.set_rvfi_dump(rvfi);
+        }
+        let code = htif::run(&mut rv64, 100_000_000);
+        print!("{}", String::from_utf8_lossy(&rv64.eei().console));
+        code
+    };
 
-    fn get_64(&mut self, addr: u32) -> u64 {
-        return (self.get_32(addr + 4) as u64) << 32 | self.get_32(addr) as u64;
+    match code {
+        Some(0) => println!("PASS"),
+        Some(code) => println!("FAIL (test {})", code),
+        None => println!("TIMEOUT"),
     }
+    code.map_or(2, |code| (code != 0) as i32)
+}
 
-    fn set_8(&mut self, addr: u32, data: u8) {
-        self.memory[addr as usize] = data;
+/// Runs the given riscv-arch-test executable and writes its signature to `signature`.
+fn run_arch_test(path: &str, ext: Option<&str>, signature: &str) -> i32 {
+    let data = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+    match compliance::run_test(&data, ext, 100_000_000) {
+        Ok(words) => {
+            std::fs::write(signature, compliance::format_signature(&words))
+                .unwrap_or_else(|e| panic!("Failed to write {}: {}", signature, e));
+            0
+        },
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            1
+        },
     }
+}
 
-    fn set_16(&mut self, addr: u32, data: u16) {
-        self.set_8(addr, data as u8);
-        self.set_8(addr + 1, (data >> 8) as u8);
+/// Runs the given executable in lockstep with the reference commit log, and returns the exit code of the process.
+fn run_lockstep<I: Iterator<Item = std::io::Result<Commit>>>(path: &str, ext: Option<&str>, reference: I) -> i32 {
+    let data = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+    let elf = ElfFile::parse(&data).unwrap_or_else(|e| panic!("Invalid ELF file {}: {}", path, e));
+    let conf = compliance::config(&elf, ext);
+    elf.check_config(elf.xlen, &conf).unwrap_or_else(|e| panic!("Incompatible ELF file {}: {}", path, e));
+    let eei = compliance::load(&elf).unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+
+    let mut lockstep = Lockstep::new(reference, 32);
+    let result = if elf.xlen == 32 {
+        let mut rv32 = RV32I::new([0; 32], elf.entry as u32, conf, eei);
+        lockstep.run(&mut rv32, 100_000_000)
+    } else {
+        let mut rv64 = RV64I::new([0; 32], elf.entry, conf, eei);
+        lockstep.run(&mut rv64, 100_000_000)
+    };
+    match result {
+        Ok(checked) => {
+            println!("{} instructions match the reference", checked);
+            0
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        },
     }
+}
 
-    fn set_32(&mut self, addr: u32, data: u32) {
-        self.set_16(addr, data as u16);
-        self.set_16(addr + 2, (data >> 16) as u16);
+/// Loads the given executable and waits for a GDB client on `addr`.
+fn debug_elf(path: &str, ext: Option<&str>, addr: &str) -> i32 {
+    let data = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+    let elf = ElfFile::parse(&data).unwrap_or_else(|e| panic!("Invalid ELF file {}: {}", path, e));
+    let conf = compliance::config(&elf, ext);
+    elf.check_config(elf.xlen, &conf).unwrap_or_else(|e| panic!("Incompatible ELF file {}: {}", path, e));
+    let eei = compliance::load(&elf).unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e));
+
+    println!("Waiting for GDB on {}", addr);
+    let result = if elf.xlen == 32 {
+        let mut rv32 = RV32I::new([0; 32], elf.entry as u32, conf, eei);
+        let mut stub = GdbStub::new(&mut rv32);
+        stub.listen(addr)
+    } else {
+        let mut rv64 = RV64I::new([0; 32], elf.entry, conf, eei);
+        let mut stub = GdbStub::new(&mut rv64);
+        stub.listen(addr)
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("GDB connection failed: {}", e);
+            1
+        },
     }
+}
 
-    fn set_64(&mut self, addr: u32, data: u64) {
-        self.set_32(addr, data as u32);
-        self.set_32(addr + 4, (data >> 32) as u32);
+fn main() {
+    // dyriscvic [--isa <ISA string>] [--signature <output file> | --gdb <address> | [--log-commits [-l]] [--rvfi <output file>] |
+    //     --lockstep <Spike commit log> | --lockstep-rvfi <RVFI-DII packets>] <ELF file>
+    let mut args = std::env::args().skip(1);
+    let (mut path, mut ext, mut signature, mut gdb, mut log) = (None, None, None, None, None);
+    let (mut reference, mut rvfi) = (None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--isa" => ext = args.next(),
+            "--signature" => signature = args.next(),
+            "--gdb" => gdb = args.next(),
+            "--log-commits" => log = Some(log.unwrap_or(false)),
+            "-l" => log = Some(true),
+            "--rvfi" => rvfi = args.next(),
+            "--lockstep" => reference = args.next().map(|path| (path, false)),
+            "--lockstep-rvfi" => reference = args.next().map(|path| (path, true)),
+            _ => path = Some(arg),
+        }
     }
-
-    fn get_opcode_32(&mut self, addr: u32) -> u32 {
-        return self.get_32(addr);
+    if let (Some(path), Some((reference, rvfi))) = (&path, reference) {
+        let file = std::fs::File::open(&reference).unwrap_or_else(|e| panic!("Failed to read {}: {}", reference, e));
+        let reader = std::io::BufReader::new(file);
+        std::process::exit(if rvfi {
+            run_lockstep(path, ext.as_deref(), RvfiLog::new(reader))
+        } else {
+            run_lockstep(path, ext.as_deref(), SpikeLog::new(reader))
+        });
     }
-}
-
-impl ExecutionEnvironmentInterface<u32> for ExecutionEnvironment {
-    fn trap(&mut self, trap: Traps) {
-        println!("Trap: {:?}", trap);
+    if let Some(path) = path {
+        std::process::exit(match (signature, gdb) {
+            (Some(signature), _) => run_arch_test(&path, ext.as_deref(), &signature),
+            (None, Some(addr)) => debug_elf(&path, ext.as_deref(), &addr),
+            (None, None) => run_elf(&path, ext.as_deref(), log, rvfi.as_deref()),
+        });
     }
-}
 
-fn main() {
-    let mut eei = ExecutionEnvironment {
-        memory: [0; 4096],
-    };
+    let mut bus = Bus::new();
+    bus.map_ram(0, 4096).unwrap();
+    bus.set_trap_handler(|trap, tval, pc| println!("Trap: {:?} at {:#x}, tval {:#x}", trap, pc, tval));
 
-    eei.memory[0..4].copy_from_slice(&assembler::ANDI(1, 0, 0).as_slice_le()); // ANDI x1, x0, 0
-    eei.memory[4..8].copy_from_slice(&assembler::JAL(0, -4i32 as u32).as_slice_le()); // JAL x0, -4
+    let memory = bus.memory_mut(0, 8).unwrap();
+    memory[0..4].copy_from_slice(&assembler::ANDI(1, 0, 0).as_slice_le()); // ANDI x1, x0, 0
+    memory[4..8].copy_from_slice(&assembler::JAL(0, -4i32 as u32).as_slice_le()); // JAL x0, -4
 
     let conf: RVConfig = RVConfig {
         ext: String::from(""),
         abi_name: true,
+        privileged: false,
+        pmp_regions: 0,
+        misaligned_access: MisalignedAccess::Emulate,
     };
 
-    let mut rv32i = RV32I::new([0; 32], 0, conf, eei);
+    let mut rv32i = RV32I::new([0; 32], 0, conf, bus);
 
     for _ in 0..130_000_000 {
         rv32i.single_step();
@@ -1,50 +1,264 @@
 //! Traits and enums that has to be defined by the user and provided to dyriscvic.
 
 /// Memory access interface. Has to be defined by the host and provided to dyriscvic.
+///
+/// Every access can fail with a [`MemoryFault`], which is raised as an access-fault or address-misaligned exception.
 pub trait MemoryAccess<ADDR> {
     /// Returns the byte at the given address.
-    fn get_8(&mut self, addr: ADDR) -> u8;
+    fn get_8(&mut self, addr: ADDR) -> Result<u8, MemoryFault>;
 
     /// Returns the half-word (16 bits) at the given address.
-    fn get_16(&mut self, addr: ADDR) -> u16;
+    fn get_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault>;
 
     /// Returns the word (32 bits) at the given address.
-    fn get_32(&mut self, addr: ADDR) -> u32;
+    fn get_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault>;
 
     /// Returns the double-word (64 bits) at the given address.
-    fn get_64(&mut self, addr: ADDR) -> u64;
+    fn get_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault>;
 
     /// Sets the given byte at the given address.
-    fn set_8(&mut self, addr: ADDR, data: u8);
+    fn set_8(&mut self, addr: ADDR, data: u8) -> Result<(), MemoryFault>;
 
     /// Sets the given half-word (16 bits) at the given address.
-    fn set_16(&mut self, addr: ADDR, data: u16);
+    fn set_16(&mut self, addr: ADDR, data: u16) -> Result<(), MemoryFault>;
 
     /// Sets the given word (32 bits) at the given address.
-    fn set_32(&mut self, addr: ADDR, data: u32);
+    fn set_32(&mut self, addr: ADDR, data: u32) -> Result<(), MemoryFault>;
 
     /// Sets the given double-word (64 bits) at the given address.
-    fn set_64(&mut self, addr: ADDR, data: u64);
+    fn set_64(&mut self, addr: ADDR, data: u64) -> Result<(), MemoryFault>;
 
     /// Returns the 32-bits opcode at the given address.
-    fn get_opcode_32(&mut self, addr: ADDR) -> u32;
+    fn get_opcode_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault>;
+
+    /// Returns the 16-bits opcode parcel at the given address. Used to fetch instructions when the C extension is enabled.
+    fn get_opcode_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault> {
+        self.get_16(addr)
+    }
+
+    /// Atomically applies the given operation to the word (32 bits) at the given address and returns its previous value.
+    ///
+    /// The default implementation is not atomic and has to be overridden if the memory is shared between several harts.
+    fn amo_32(&mut self, addr: ADDR, op: AMO, value: u32) -> Result<u32, MemoryFault> where ADDR: Copy {
+        let old = self.get_32(addr)?;
+        self.set_32(addr, op.apply_32(old, value))?;
+        Ok(old)
+    }
+
+    /// Atomically applies the given operation to the double-word (64 bits) at the given address and returns its previous value.
+    ///
+    /// The default implementation is not atomic and has to be overridden if the memory is shared between several harts.
+    fn amo_64(&mut self, addr: ADDR, op: AMO, value: u64) -> Result<u64, MemoryFault> where ADDR: Copy {
+        let old = self.get_64(addr)?;
+        self.set_64(addr, op.apply_64(old, value))?;
+        Ok(old)
+    }
+
+    /// Returns the word (32 bits) at the given address, loaded by a LR.W instruction.
+    ///
+    /// The hart keeps its own reservation, this allows a shared memory to track the reservations of several harts.
+    fn load_reserved_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.get_32(addr)
+    }
+
+    /// Returns the double-word (64 bits) at the given address, loaded by a LR.D instruction.
+    fn load_reserved_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault> {
+        self.get_64(addr)
+    }
+
+    /// Stores the given word (32 bits) at the given address for a SC.W instruction whose reservation is still valid on the hart.
+    /// Returns false if the store has not been performed, for example because another hart has written to the reserved address.
+    fn store_conditional_32(&mut self, addr: ADDR, data: u32) -> Result<bool, MemoryFault> {
+        self.set_32(addr, data)?;
+        Ok(true)
+    }
+
+    /// Stores the given double-word (64 bits) at the given address for a SC.D instruction whose reservation is still valid on the hart.
+    /// Returns false if the store has not been performed.
+    fn store_conditional_64(&mut self, addr: ADDR, data: u64) -> Result<bool, MemoryFault> {
+        self.set_64(addr, data)?;
+        Ok(true)
+    }
+}
+
+/// Reasons for a memory access to fail, returned by [`MemoryAccess`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryFault {
+    /// Nothing is mapped at the address or the device rejected the access (bus error).
+    /// Raised as an instruction, load or store/AMO access-fault.
+    AccessFault,
+    /// The device does not support accesses that are not naturally aligned.
+    /// Raised as an instruction, load or store/AMO address-misaligned exception.
+    Misaligned,
+}
+
+/// Operations performed by the AMO instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AMO {
+    Swap,
+    Add,
+    Xor,
+    And,
+    Or,
+    Min,
+    Max,
+    Minu,
+    Maxu,
 }
 
-/// Traps that can occur during execution.
-#[derive(Clone, Copy, Debug)]
+impl AMO {
+    /// Returns the value to store given the value in memory and the value of the source register.
+    pub fn apply_32(self, old: u32, value: u32) -> u32 {
+        match self {
+            AMO::Swap => value,
+            AMO::Add => old.wrapping_add(value),
+            AMO::Xor => old ^ value,
+            AMO::And => old & value,
+            AMO::Or => old | value,
+            AMO::Min => (old as i32).min(value as i32) as u32,
+            AMO::Max => (old as i32).max(value as i32) as u32,
+            AMO::Minu => old.min(value),
+            AMO::Maxu => old.max(value),
+        }
+    }
+
+    /// Returns the value to store given the value in memory and the value of the source register.
+    pub fn apply_64(self, old: u64, value: u64) -> u64 {
+        match self {
+            AMO::Swap => value,
+            AMO::Add => old.wrapping_add(value),
+            AMO::Xor => old ^ value,
+            AMO::And => old & value,
+            AMO::Or => old | value,
+            AMO::Min => (old as i64).min(value as i64) as u64,
+            AMO::Max => (old as i64).max(value as i64) as u64,
+            AMO::Minu => old.min(value),
+            AMO::Maxu => old.max(value),
+        }
+    }
+}
+
+/// Traps that can occur during execution, with their exception code in `mcause` in brackets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Traps {
-    /// Occurs when an EBREAK instruction is executed.
-    Breakpoint,
-    /// Occurs when branching to a misaligned address.
+    /// (0) Occurs when branching to a misaligned address. `tval` is the target address.
     InstructionAddressMisaligned,
-    /// Occurs when an illegal instruction or unknown opcode is executed.
+    /// (1) Occurs when an instruction fetch fails or is denied by the physical memory protection. `tval` is the address.
+    InstructionAccessFault,
+    /// (2) Occurs when an illegal instruction or unknown opcode is executed. `tval` is the instruction bits.
     IllegalInstruction,
-    /// Occurs when an ECALL instruction is executed.
-    SystemCall,
+    /// (3) Occurs when an EBREAK instruction is executed. `tval` is its address.
+    Breakpoint,
+    /// (4) Occurs when a load or LR instruction accesses a misaligned address. `tval` is the address.
+    LoadAddressMisaligned,
+    /// (5) Occurs when a load fails or is denied by the physical memory protection. `tval` is the address.
+    LoadAccessFault,
+    /// (6) Occurs when a store, SC or AMO instruction accesses a misaligned address. `tval` is the address.
+    StoreAMOAddressMisaligned,
+    /// (7) Occurs when a store or AMO fails or is denied by the physical memory protection. `tval` is the address.
+    StoreAMOAccessFault,
+    /// (8) Occurs when an ECALL instruction is executed in U-mode. `tval` is 0.
+    EnvironmentCallFromUMode,
+    /// (9) Occurs when an ECALL instruction is executed in S-mode. `tval` is 0.
+    EnvironmentCallFromSMode,
+    /// (11) Occurs when an ECALL instruction is executed in M-mode. `tval` is 0.
+    EnvironmentCallFromMMode,
+    /// (12) Occurs when the translation of the address of an instruction fetch fails. `tval` is the address.
+    InstructionPageFault,
+    /// (13) Occurs when the translation of the address of a load fails. `tval` is the address.
+    LoadPageFault,
+    /// (15) Occurs when the translation of the address of a store or AMO fails. `tval` is the address.
+    StoreAMOPageFault,
+    /// (1) Supervisor-level software interrupt.
+    SupervisorSoftwareInterrupt,
+    /// (3) Machine-level software interrupt.
+    MachineSoftwareInterrupt,
+    /// (5) Supervisor-level timer interrupt.
+    SupervisorTimerInterrupt,
+    /// (7) Machine-level timer interrupt.
+    MachineTimerInterrupt,
+    /// (9) Supervisor-level external interrupt.
+    SupervisorExternalInterrupt,
+    /// (11) Machine-level external interrupt.
+    MachineExternalInterrupt,
+}
+
+impl Traps {
+    /// Every trap, exceptions first, in increasing exception code order.
+    pub const ALL: [Traps; 20] = [
+        Traps::InstructionAddressMisaligned,
+        Traps::InstructionAccessFault,
+        Traps::IllegalInstruction,
+        Traps::Breakpoint,
+        Traps::LoadAddressMisaligned,
+        Traps::LoadAccessFault,
+        Traps::StoreAMOAddressMisaligned,
+        Traps::StoreAMOAccessFault,
+        Traps::EnvironmentCallFromUMode,
+        Traps::EnvironmentCallFromSMode,
+        Traps::EnvironmentCallFromMMode,
+        Traps::InstructionPageFault,
+        Traps::LoadPageFault,
+        Traps::StoreAMOPageFault,
+        Traps::SupervisorSoftwareInterrupt,
+        Traps::MachineSoftwareInterrupt,
+        Traps::SupervisorTimerInterrupt,
+        Traps::MachineTimerInterrupt,
+        Traps::SupervisorExternalInterrupt,
+        Traps::MachineExternalInterrupt,
+    ];
+
+    /// Returns true if the trap is an interrupt, false if it is an exception.
+    pub fn is_interrupt(self) -> bool {
+        matches!(self,
+            Traps::SupervisorSoftwareInterrupt | Traps::MachineSoftwareInterrupt |
+            Traps::SupervisorTimerInterrupt | Traps::MachineTimerInterrupt |
+            Traps::SupervisorExternalInterrupt | Traps::MachineExternalInterrupt
+        )
+    }
+
+    /// Returns the exception code written in the `xcause` register, without the interrupt bit.
+    pub fn exception_code(self) -> u8 {
+        match self {
+            Traps::InstructionAddressMisaligned => 0,
+            Traps::InstructionAccessFault => 1,
+            Traps::IllegalInstruction => 2,
+            Traps::Breakpoint => 3,
+            Traps::LoadAddressMisaligned => 4,
+            Traps::LoadAccessFault => 5,
+            Traps::StoreAMOAddressMisaligned => 6,
+            Traps::StoreAMOAccessFault => 7,
+            Traps::EnvironmentCallFromUMode => 8,
+            Traps::EnvironmentCallFromSMode => 9,
+            Traps::EnvironmentCallFromMMode => 11,
+            Traps::InstructionPageFault => 12,
+            Traps::LoadPageFault => 13,
+            Traps::StoreAMOPageFault => 15,
+            Traps::SupervisorSoftwareInterrupt => 1,
+            Traps::MachineSoftwareInterrupt => 3,
+            Traps::SupervisorTimerInterrupt => 5,
+            Traps::MachineTimerInterrupt => 7,
+            Traps::SupervisorExternalInterrupt => 9,
+            Traps::MachineExternalInterrupt => 11,
+        }
+    }
+
+    /// Returns the value of the `xcause` register for a hart of the given XLEN, with the interrupt bit.
+    pub fn cause(self, xlen: usize) -> u64 {
+        let interrupt = if self.is_interrupt() { 1 << (xlen - 1) } else { 0 };
+        interrupt | self.exception_code() as u64
+    }
+
+    /// Returns the trap associated with the given value of the `xcause` register, or None if the cause is reserved.
+    pub fn from_cause(cause: u64, xlen: usize) -> Option<Self> {
+        Self::ALL.iter().copied().find(|trap| trap.cause(xlen) == cause)
+    }
 }
 
 /// Trait representing the execution environment.
 pub trait ExecutionEnvironmentInterface<ADDR> : MemoryAccess<ADDR> {
-    /// Called by the library when a trap occurs. See [`Traps`] for a list of the possible traps.
-    fn trap(&mut self, trap: Traps);
+    /// Called by the library when a trap occurs and [`RVConfig::privileged`](crate::rvi::RVConfig::privileged) is false.
+    /// See [`Traps`] for a list of the possible traps and their `tval` (bad address or instruction bits).
+    /// `pc` is the address of the instruction that caused the exception, or of the next instruction for interrupts.
+    fn trap(&mut self, trap: Traps, tval: ADDR, pc: ADDR);
 }
@@ -212,3 +212,615 @@ pub fn SRLW(rd: u8, rs1: u8, rs2: u8) -> u32 {
 pub fn SUBW(rd: u8, rs1: u8, rs2: u8) -> u32 {
     encode_type_r(0b0111011, rd, 0, rs1, rs2, 0b0100000)
 }
+
+// M32
+pub fn DIV(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 4, rs1, rs2, 1)
+}
+
+pub fn DIVU(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 5, rs1, rs2, 1)
+}
+
+pub fn MUL(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 0, rs1, rs2, 1)
+}
+
+pub fn MULH(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 1, rs1, rs2, 1)
+}
+
+pub fn MULHSU(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 2, rs1, rs2, 1)
+}
+
+pub fn MULHU(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 3, rs1, rs2, 1)
+}
+
+pub fn REM(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 6, rs1, rs2, 1)
+}
+
+pub fn REMU(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0110011, rd, 7, rs1, rs2, 1)
+}
+
+// M64
+pub fn DIVUW(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0111011, rd, 5, rs1, rs2, 1)
+}
+
+pub fn DIVW(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0111011, rd, 4, rs1, rs2, 1)
+}
+
+pub fn MULW(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0111011, rd, 0, rs1, rs2, 1)
+}
+
+pub fn REMUW(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0111011, rd, 7, rs1, rs2, 1)
+}
+
+pub fn REMW(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b0111011, rd, 6, rs1, rs2, 1)
+}
+
+// A32
+pub fn AMOADD_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOAND_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b01100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMAX_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b10100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMAXU_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b11100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMIN_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b10000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMINU_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b11000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOOR_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b01000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOSWAP_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b00001 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOXOR_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b00100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn LR_W(rd: u8, rs1: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, 0, 0b00010 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn SC_W(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 2, rs1, rs2, 0b00011 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+// A64
+pub fn AMOADD_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOAND_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b01100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMAX_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b10100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMAXU_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b11100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMIN_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b10000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOMINU_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b11000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOOR_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b01000 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOSWAP_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b00001 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn AMOXOR_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b00100 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn LR_D(rd: u8, rs1: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, 0, 0b00010 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+pub fn SC_D(rd: u8, rs1: u8, rs2: u8, aq: bool, rl: bool) -> u32 {
+    encode_type_r(0b0101111, rd, 3, rs1, rs2, 0b00011 << 2 | (aq as u8) << 1 | rl as u8)
+}
+
+// F32
+pub fn FADD_S(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0000000)
+}
+
+pub fn FCLASS_S(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, 0, 0b1110000)
+}
+
+pub fn FCVT_S_W(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b1101000)
+}
+
+pub fn FCVT_S_WU(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 1, 0b1101000)
+}
+
+pub fn FCVT_W_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b1100000)
+}
+
+pub fn FCVT_WU_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 1, 0b1100000)
+}
+
+pub fn FDIV_S(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0001100)
+}
+
+pub fn FEQ_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 2, rs1, rs2, 0b1010000)
+}
+
+pub fn FLE_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b1010000)
+}
+
+pub fn FLT_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b1010000)
+}
+
+pub fn FLW(rd: u8, rs1: u8, imm: u32) -> u32 {
+    encode_type_i(0b0000111, rd, 2, rs1, imm)
+}
+
+pub fn FMADD_S(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1000011, rd, rm, rs1, rs2, rs3, 0)
+}
+
+pub fn FMAX_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b0010100)
+}
+
+pub fn FMIN_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b0010100)
+}
+
+pub fn FMSUB_S(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1000111, rd, rm, rs1, rs2, rs3, 0)
+}
+
+pub fn FMUL_S(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0001000)
+}
+
+pub fn FMV_W_X(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, 0, 0b1111000)
+}
+
+pub fn FMV_X_W(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, 0, 0b1110000)
+}
+
+pub fn FNMADD_S(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1001111, rd, rm, rs1, rs2, rs3, 0)
+}
+
+pub fn FNMSUB_S(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1001011, rd, rm, rs1, rs2, rs3, 0)
+}
+
+pub fn FSGNJ_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b0010000)
+}
+
+pub fn FSGNJN_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b0010000)
+}
+
+pub fn FSGNJX_S(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 2, rs1, rs2, 0b0010000)
+}
+
+pub fn FSQRT_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b0101100)
+}
+
+pub fn FSUB_S(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0000100)
+}
+
+pub fn FSW(rs2: u8, rs1: u8, imm: u32) -> u32 {
+    encode_type_s(0b0100111, 2, rs1, rs2, imm)
+}
+
+// F64
+pub fn FCVT_L_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 2, 0b1100000)
+}
+
+pub fn FCVT_LU_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 3, 0b1100000)
+}
+
+pub fn FCVT_S_L(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 2, 0b1101000)
+}
+
+pub fn FCVT_S_LU(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 3, 0b1101000)
+}
+
+// D32
+pub fn FADD_D(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0000001)
+}
+
+pub fn FCLASS_D(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, 0, 0b1110001)
+}
+
+pub fn FCVT_D_S(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b0100001)
+}
+
+pub fn FCVT_D_W(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b1101001)
+}
+
+pub fn FCVT_D_WU(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 1, 0b1101001)
+}
+
+pub fn FCVT_S_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 1, 0b0100000)
+}
+
+pub fn FCVT_W_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b1100001)
+}
+
+pub fn FCVT_WU_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 1, 0b1100001)
+}
+
+pub fn FDIV_D(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0001101)
+}
+
+pub fn FEQ_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 2, rs1, rs2, 0b1010001)
+}
+
+pub fn FLD(rd: u8, rs1: u8, imm: u32) -> u32 {
+    encode_type_i(0b0000111, rd, 3, rs1, imm)
+}
+
+pub fn FLE_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b1010001)
+}
+
+pub fn FLT_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b1010001)
+}
+
+pub fn FMADD_D(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1000011, rd, rm, rs1, rs2, rs3, 1)
+}
+
+pub fn FMAX_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b0010101)
+}
+
+pub fn FMIN_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b0010101)
+}
+
+pub fn FMSUB_D(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1000111, rd, rm, rs1, rs2, rs3, 1)
+}
+
+pub fn FMUL_D(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0001001)
+}
+
+pub fn FNMADD_D(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1001111, rd, rm, rs1, rs2, rs3, 1)
+}
+
+pub fn FNMSUB_D(rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8) -> u32 {
+    encode_type_r4(0b1001011, rd, rm, rs1, rs2, rs3, 1)
+}
+
+pub fn FSD(rs2: u8, rs1: u8, imm: u32) -> u32 {
+    encode_type_s(0b0100111, 3, rs1, rs2, imm)
+}
+
+pub fn FSGNJ_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, rs2, 0b0010001)
+}
+
+pub fn FSGNJN_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 1, rs1, rs2, 0b0010001)
+}
+
+pub fn FSGNJX_D(rd: u8, rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 2, rs1, rs2, 0b0010001)
+}
+
+pub fn FSQRT_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 0, 0b0101101)
+}
+
+pub fn FSUB_D(rd: u8, rs1: u8, rs2: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, rs2, 0b0000101)
+}
+
+// D64
+pub fn FCVT_D_L(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 2, 0b1101001)
+}
+
+pub fn FCVT_D_LU(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 3, 0b1101001)
+}
+
+pub fn FCVT_L_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 2, 0b1100001)
+}
+
+pub fn FCVT_LU_D(rd: u8, rs1: u8, rm: u8) -> u32 {
+    encode_type_r(0b1010011, rd, rm, rs1, 3, 0b1100001)
+}
+
+pub fn FMV_D_X(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, 0, 0b1111001)
+}
+
+pub fn FMV_X_D(rd: u8, rs1: u8) -> u32 {
+    encode_type_r(0b1010011, rd, 0, rs1, 0, 0b1110001)
+}
+
+// C
+pub fn C_ADD(rd: u8, rs2: u8) -> u16 {
+    encode_type_cr(0b10, 0b1001, rd, rs2)
+}
+
+pub fn C_ADDI(rd: u8, imm: u32) -> u16 {
+    encode_type_ci(0b01, 0b000, rd, imm)
+}
+
+pub fn C_ADDI16SP(imm: u32) -> u16 {
+    (0b011 << 13 | imm << 3 & 0x1000 | 2 << 7 | imm << 2 & 0x40 | imm >> 1 & 0x20 | imm >> 4 & 0x18 | imm >> 3 & 0x4 | 0b01) as u16
+}
+
+pub fn C_ADDI4SPN(rd: u8, imm: u32) -> u16 {
+    (imm << 7 & 0x1800 | imm << 1 & 0x780 | imm << 4 & 0x40 | imm << 2 & 0x20 | (rd as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_ADDIW(rd: u8, imm: u32) -> u16 {
+    encode_type_ci(0b01, 0b001, rd, imm)
+}
+
+pub fn C_ADDW(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100111, rd, 0b01, rs2)
+}
+
+pub fn C_AND(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100011, rd, 0b11, rs2)
+}
+
+pub fn C_ANDI(rd: u8, imm: u32) -> u16 {
+    encode_type_ci(0b01, 0b100, 0b10_000 | rd & 0b111, imm)
+}
+
+pub fn C_BEQZ(rs1: u8, imm: u32) -> u16 {
+    encode_type_cb(0b01, 0b110, rs1, imm)
+}
+
+pub fn C_BNEZ(rs1: u8, imm: u32) -> u16 {
+    encode_type_cb(0b01, 0b111, rs1, imm)
+}
+
+pub fn C_EBREAK() -> u16 {
+    encode_type_cr(0b10, 0b1001, 0, 0)
+}
+
+pub fn C_FLD(rd: u8, rs1: u8, imm: u32) -> u16 {
+    (0b001 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm >> 1 & 0x60 | (rd as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_FLDSP(rd: u8, imm: u32) -> u16 {
+    (0b001 << 13 | imm << 7 & 0x1000 | (rd as u32) << 7 & 0xF80 | imm << 2 & 0x60 | imm >> 4 & 0x1C | 0b10) as u16
+}
+
+pub fn C_FLW(rd: u8, rs1: u8, imm: u32) -> u16 {
+    (0b011 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm << 4 & 0x40 | imm >> 1 & 0x20 | (rd as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_FLWSP(rd: u8, imm: u32) -> u16 {
+    (0b011 << 13 | imm << 7 & 0x1000 | (rd as u32) << 7 & 0xF80 | imm << 2 & 0x70 | imm >> 4 & 0xC | 0b10) as u16
+}
+
+pub fn C_FSD(rs2: u8, rs1: u8, imm: u32) -> u16 {
+    (0b101 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm >> 1 & 0x60 | (rs2 as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_FSDSP(rs2: u8, imm: u32) -> u16 {
+    (0b101 << 13 | imm << 7 & 0x1C00 | imm << 1 & 0x380 | (rs2 as u32) << 2 & 0x7C | 0b10) as u16
+}
+
+pub fn C_FSW(rs2: u8, rs1: u8, imm: u32) -> u16 {
+    (0b111 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm << 4 & 0x40 | imm >> 1 & 0x20 | (rs2 as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_FSWSP(rs2: u8, imm: u32) -> u16 {
+    (0b111 << 13 | imm << 7 & 0x1E00 | imm << 1 & 0x180 | (rs2 as u32) << 2 & 0x7C | 0b10) as u16
+}
+
+pub fn C_J(imm: u32) -> u16 {
+    encode_type_cj(0b01, 0b101, imm)
+}
+
+pub fn C_JAL(imm: u32) -> u16 {
+    encode_type_cj(0b01, 0b001, imm)
+}
+
+pub fn C_JALR(rs1: u8) -> u16 {
+    encode_type_cr(0b10, 0b1001, rs1, 0)
+}
+
+pub fn C_JR(rs1: u8) -> u16 {
+    encode_type_cr(0b10, 0b1000, rs1, 0)
+}
+
+pub fn C_LD(rd: u8, rs1: u8, imm: u32) -> u16 {
+    (0b011 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm >> 1 & 0x60 | (rd as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_LDSP(rd: u8, imm: u32) -> u16 {
+    (0b011 << 13 | imm << 7 & 0x1000 | (rd as u32) << 7 & 0xF80 | imm << 2 & 0x60 | imm >> 4 & 0x1C | 0b10) as u16
+}
+
+pub fn C_LI(rd: u8, imm: u32) -> u16 {
+    encode_type_ci(0b01, 0b010, rd, imm)
+}
+
+pub fn C_LUI(rd: u8, imm: u32) -> u16 {
+    encode_type_ci(0b01, 0b011, rd, imm >> 12)
+}
+
+pub fn C_LW(rd: u8, rs1: u8, imm: u32) -> u16 {
+    (0b010 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm << 4 & 0x40 | imm >> 1 & 0x20 | (rd as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_LWSP(rd: u8, imm: u32) -> u16 {
+    (0b010 << 13 | imm << 7 & 0x1000 | (rd as u32) << 7 & 0xF80 | imm << 2 & 0x70 | imm >> 4 & 0xC | 0b10) as u16
+}
+
+pub fn C_MV(rd: u8, rs2: u8) -> u16 {
+    encode_type_cr(0b10, 0b1000, rd, rs2)
+}
+
+pub fn C_NOP() -> u16 {
+    encode_type_ci(0b01, 0b000, 0, 0)
+}
+
+pub fn C_OR(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100011, rd, 0b10, rs2)
+}
+
+pub fn C_SD(rs2: u8, rs1: u8, imm: u32) -> u16 {
+    (0b111 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm >> 1 & 0x60 | (rs2 as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_SDSP(rs2: u8, imm: u32) -> u16 {
+    (0b111 << 13 | imm << 7 & 0x1C00 | imm << 1 & 0x380 | (rs2 as u32) << 2 & 0x7C | 0b10) as u16
+}
+
+pub fn C_SLLI(rd: u8, shamt: u32) -> u16 {
+    encode_type_ci(0b10, 0b000, rd, shamt)
+}
+
+pub fn C_SRAI(rd: u8, shamt: u32) -> u16 {
+    encode_type_ci(0b01, 0b100, 0b01_000 | rd & 0b111, shamt)
+}
+
+pub fn C_SRLI(rd: u8, shamt: u32) -> u16 {
+    encode_type_ci(0b01, 0b100, rd & 0b111, shamt)
+}
+
+pub fn C_SUB(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100011, rd, 0b00, rs2)
+}
+
+pub fn C_SUBW(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100111, rd, 0b00, rs2)
+}
+
+pub fn C_SW(rs2: u8, rs1: u8, imm: u32) -> u16 {
+    (0b110 << 13 | imm << 7 & 0x1C00 | (rs1 as u32 & 0b111) << 7 | imm << 4 & 0x40 | imm >> 1 & 0x20 | (rs2 as u32 & 0b111) << 2) as u16
+}
+
+pub fn C_SWSP(rs2: u8, imm: u32) -> u16 {
+    (0b110 << 13 | imm << 7 & 0x1E00 | imm << 1 & 0x180 | (rs2 as u32) << 2 & 0x7C | 0b10) as u16
+}
+
+pub fn C_XOR(rd: u8, rs2: u8) -> u16 {
+    encode_type_ca(0b01, 0b100011, rd, 0b01, rs2)
+}
+
+// Zicsr
+pub fn CSRRC(rd: u8, csr: u16, rs1: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 3, rs1, csr as u32)
+}
+
+pub fn CSRRCI(rd: u8, csr: u16, uimm: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 7, uimm, csr as u32)
+}
+
+pub fn CSRRS(rd: u8, csr: u16, rs1: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 2, rs1, csr as u32)
+}
+
+pub fn CSRRSI(rd: u8, csr: u16, uimm: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 6, uimm, csr as u32)
+}
+
+pub fn CSRRW(rd: u8, csr: u16, rs1: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 1, rs1, csr as u32)
+}
+
+pub fn CSRRWI(rd: u8, csr: u16, uimm: u8) -> u32 {
+    encode_type_i(0b1110011, rd, 5, uimm, csr as u32)
+}
+
+// Zifencei
+pub fn FENCE_I() -> u32 {
+    encode_type_i(0b0001111, 0, 1, 0, 0)
+}
+
+// Privileged
+pub fn MRET() -> u32 {
+    encode_type_r(0b1110011, 0, 0, 0, 0b00010, 0b0011000)
+}
+
+pub fn SFENCE_VMA(rs1: u8, rs2: u8) -> u32 {
+    encode_type_r(0b1110011, 0, 0, rs1, rs2, 0b0001001)
+}
+
+pub fn SRET() -> u32 {
+    encode_type_r(0b1110011, 0, 0, 0, 0b00010, 0b0001000)
+}
+
+pub fn WFI() -> u32 {
+    encode_type_r(0b1110011, 0, 0, 0, 0b00101, 0b0001000)
+}
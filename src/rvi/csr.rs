@@ -0,0 +1,363 @@
+//! Control and status registers of the hart.
+
+use crate::common::{csr::*, types::*};
+use crate::public::ExecutionEnvironmentInterface;
+use crate::rvi::*;
+
+pub const MSTATUS_SIE: u64 = 1 << 1;
+pub const MSTATUS_MIE: u64 = 1 << 3;
+pub const MSTATUS_SPIE: u64 = 1 << 5;
+pub const MSTATUS_MPIE: u64 = 1 << 7;
+pub const MSTATUS_SPP: u64 = 1 << 8;
+pub const MSTATUS_MPP: u64 = 0b11 << 11;
+pub const MSTATUS_FS: u64 = 0b11 << 13;
+pub const MSTATUS_MPRV: u64 = 1 << 17;
+pub const MSTATUS_SUM: u64 = 1 << 18;
+pub const MSTATUS_MXR: u64 = 1 << 19;
+pub const MSTATUS_TVM: u64 = 1 << 20;
+pub const MSTATUS_TW: u64 = 1 << 21;
+pub const MSTATUS_TSR: u64 = 1 << 22;
+pub const MSTATUS_UXL: u64 = 0b11 << 32;
+pub const MSTATUS_SXL: u64 = 0b11 << 34;
+
+/// Fields of `mstatus` visible in `sstatus`.
+pub const SSTATUS_MASK: u64 = MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_FS | MSTATUS_SUM | MSTATUS_MXR | MSTATUS_UXL;
+
+pub const MIP_SSIP: u64 = 1 << 1;
+pub const MIP_MSIP: u64 = 1 << 3;
+pub const MIP_STIP: u64 = 1 << 5;
+pub const MIP_MTIP: u64 = 1 << 7;
+pub const MIP_SEIP: u64 = 1 << 9;
+pub const MIP_MEIP: u64 = 1 << 11;
+
+/// Exceptions that can be delegated to S-mode: all of them except environment call from M-mode.
+const MEDELEG_MASK: u64 = 0xB3FF;
+/// Interrupts that can be delegated to S-mode.
+const MIDELEG_MASK: u64 = MIP_SSIP | MIP_STIP | MIP_SEIP;
+
+/// Storage of the implemented control and status registers.
+///
+/// Registers are stored on 64 bits regardless of XLEN and are truncated when read by a RV32 hart.
+#[derive(Clone, Debug)]
+pub struct CSRFile {
+    pub cycle: u64,
+    pub instret: u64,
+    pub fcsr: u64,
+    pub misa: u64,
+    pub mscratch: u64,
+    pub mstatus: u64,
+    pub mtvec: u64,
+    pub mepc: u64,
+    pub mcause: u64,
+    pub mtval: u64,
+    pub mie: u64,
+    pub mip: u64,
+    pub medeleg: u64,
+    pub mideleg: u64,
+    pub mcounteren: u64,
+    pub scounteren: u64,
+    pub sscratch: u64,
+    pub stvec: u64,
+    pub sepc: u64,
+    pub scause: u64,
+    pub stval: u64,
+    pub satp: u64,
+    pub pmpcfg: [u8; 64],
+    pub pmpaddr: [u64; 64],
+}
+
+impl CSRFile {
+    /// Creates the CSRs of a hart with the given XLEN and ISA.
+    pub fn new(xlen: usize, isa: &ISAString) -> Self {
+        let misa = (xlen as u64 / 32) << (xlen - 2) | isa.misa_extensions();
+
+        let mut mstatus = MSTATUS_MPP;
+        if misa & 1 << ('F' as u64 - 'A' as u64) != 0 {
+            mstatus |= 1 << 13; // FS starts in the Initial state.
+        }
+        if xlen == 64 && misa & 1 << ('U' as u64 - 'A' as u64) != 0 {
+            mstatus |= 2 << 32;
+        }
+        if xlen == 64 && misa & 1 << ('S' as u64 - 'A' as u64) != 0 {
+            mstatus |= 2 << 34;
+        }
+
+        Self {
+            cycle: 0,
+            instret: 0,
+            fcsr: 0,
+            misa,
+            mscratch: 0,
+            mstatus,
+            mtvec: 0,
+            mepc: 0,
+            mcause: 0,
+            mtval: 0,
+            mie: 0,
+            mip: 0,
+            medeleg: 0,
+            mideleg: 0,
+            mcounteren: 0,
+            scounteren: 0,
+            sscratch: 0,
+            stvec: 0,
+            sepc: 0,
+            scause: 0,
+            stval: 0,
+            satp: 0,
+            pmpcfg: [0; 64],
+            pmpaddr: [0; 64],
+        }
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Returns the value of the given CSR, or `None` if the CSR is not implemented.
+    pub fn read_csr(&self, csr: u16) -> Option<U> {
+        let privileged = self.config.privileged;
+        let supervisor = privileged && self.has_extension('S');
+        let user = privileged && self.has_extension('U');
+        let float = self.has_extension('F');
+        Some(U::from_u64(match csr {
+            FFLAGS if float => self.csr.fcsr & 0x1F,
+            FRM if float => self.csr.fcsr >> 5 & 0b111,
+            FCSR if float => self.csr.fcsr,
+            CYCLE | TIME | MCYCLE => self.csr.cycle,
+            INSTRET | MINSTRET => self.csr.instret,
+            CYCLEH | TIMEH | MCYCLEH if Self::XLEN == 32 => self.csr.cycle >> 32,
+            INSTRETH | MINSTRETH if Self::XLEN == 32 => self.csr.instret >> 32,
+            MVENDORID | MARCHID | MIMPID | MHARTID => 0,
+            MISA => self.csr.misa,
+            MSCRATCH => self.csr.mscratch,
+            MSTATUS if privileged => self.mstatus_with_sd(),
+            MSTATUSH if privileged && Self::XLEN == 32 => 0,
+            MTVEC if privileged => self.csr.mtvec,
+            MEPC if privileged => self.csr.mepc & self.epc_mask(),
+            MCAUSE if privileged => self.csr.mcause,
+            MTVAL if privileged => self.csr.mtval,
+            MIE if privileged => self.csr.mie,
+            MIP if privileged => self.csr.mip,
+            MEDELEG if supervisor => self.csr.medeleg,
+            MIDELEG if supervisor => self.csr.mideleg,
+            MCOUNTEREN if user => self.csr.mcounteren,
+            SSTATUS if supervisor => self.mstatus_with_sd() & (SSTATUS_MASK | self.sd_bit()),
+            SIE if supervisor => self.csr.mie & self.csr.mideleg,
+            STVEC if supervisor => self.csr.stvec,
+            SCOUNTEREN if supervisor => self.csr.scounteren,
+            SSCRATCH if supervisor => self.csr.sscratch,
+            SEPC if supervisor => self.csr.sepc & self.epc_mask(),
+            SCAUSE if supervisor => self.csr.scause,
+            STVAL if supervisor => self.csr.stval,
+            SIP if supervisor => self.csr.mip & self.csr.mideleg,
+            SATP if supervisor => self.csr.satp,
+            _ if privileged && (PMPCFG0..PMPCFG0 + 16).contains(&csr) => self.read_pmpcfg((csr - PMPCFG0) as usize)?,
+            _ if privileged && (PMPADDR0..PMPADDR0 + 64).contains(&csr) => self.csr.pmpaddr[(csr - PMPADDR0) as usize],
+            _ => return None,
+        }))
+    }
+
+    /// Writes the given value to the given CSR.
+    ///
+    /// WARL fields keep their previous value if an illegal value is written to them.
+    /// Returns false if the CSR is not implemented or is read-only.
+    pub fn write_csr(&mut self, csr: u16, value: U) -> bool {
+        if is_read_only(csr) {
+            return false;
+        }
+
+        let privileged = self.config.privileged;
+        let supervisor = privileged && self.has_extension('S');
+        let user = privileged && self.has_extension('U');
+        let float = self.has_extension('F');
+        let value = value.as_u64();
+        match csr {
+            FFLAGS if float => {
+                self.csr.fcsr = self.csr.fcsr & !0x1F | value & 0x1F;
+                self.set_fp_dirty();
+            },
+            FRM if float => {
+                self.csr.fcsr = self.csr.fcsr & 0x1F | (value & 0b111) << 5;
+                self.set_fp_dirty();
+            },
+            FCSR if float => {
+                self.csr.fcsr = value & 0xFF;
+                self.set_fp_dirty();
+            },
+            MCYCLE if Self::XLEN == 32 => self.csr.cycle = self.csr.cycle & 0xFFFF_FFFF_0000_0000 | value,
+            MCYCLE => self.csr.cycle = value,
+            MINSTRET if Self::XLEN == 32 => self.csr.instret = self.csr.instret & 0xFFFF_FFFF_0000_0000 | value,
+            MINSTRET => self.csr.instret = value,
+            MCYCLEH if Self::XLEN == 32 => self.csr.cycle = self.csr.cycle & 0xFFFF_FFFF | value << 32,
+            MINSTRETH if Self::XLEN == 32 => self.csr.instret = self.csr.instret & 0xFFFF_FFFF | value << 32,
+            MISA => (), // Extensions cannot be changed at runtime.
+            MSCRATCH => self.csr.mscratch = value,
+            MSTATUS if privileged => {
+                let mut mask = MSTATUS_MIE | MSTATUS_MPIE;
+                if supervisor {
+                    mask |= MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_SUM | MSTATUS_MXR | MSTATUS_TVM | MSTATUS_TSR;
+                }
+                if user {
+                    mask |= MSTATUS_MPRV | MSTATUS_TW;
+                }
+                if float {
+                    mask |= MSTATUS_FS;
+                }
+                let mpp = value & MSTATUS_MPP;
+                if self.is_mode_supported(mpp >> 11) {
+                    mask |= MSTATUS_MPP;
+                }
+                self.csr.mstatus = self.csr.mstatus & !mask | value & mask;
+            },
+            MSTATUSH if privileged && Self::XLEN == 32 => (),
+            MTVEC if privileged => self.csr.mtvec = Self::write_tvec(self.csr.mtvec, value),
+            MEPC if privileged => self.csr.mepc = value & !1,
+            MCAUSE if privileged => self.csr.mcause = value,
+            MTVAL if privileged => self.csr.mtval = value,
+            MIE if privileged => {
+                let mut mask = MIP_MSIP | MIP_MTIP | MIP_MEIP;
+                if supervisor {
+                    mask |= MIDELEG_MASK;
+                }
+                self.csr.mie = self.csr.mie & !mask | value & mask;
+            },
+            // Machine-level interrupts are set by the execution environment.
+            MIP if supervisor => self.csr.mip = self.csr.mip & !MIDELEG_MASK | value & MIDELEG_MASK,
+            MIP if privileged => (),
+            MEDELEG if supervisor => self.csr.medeleg = value & MEDELEG_MASK,
+            MIDELEG if supervisor => self.csr.mideleg = value & MIDELEG_MASK,
+            MCOUNTEREN if user => self.csr.mcounteren = value & 0xFFFF_FFFF,
+            SSTATUS if supervisor => {
+                let mut mask = MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_SUM | MSTATUS_MXR;
+                if float {
+                    mask |= MSTATUS_FS;
+                }
+                self.csr.mstatus = self.csr.mstatus & !mask | value & mask;
+            },
+            SIE if supervisor => self.csr.mie = self.csr.mie & !self.csr.mideleg | value & self.csr.mideleg,
+            STVEC if supervisor => self.csr.stvec = Self::write_tvec(self.csr.stvec, value),
+            SCOUNTEREN if supervisor => self.csr.scounteren = value & 0xFFFF_FFFF,
+            SSCRATCH if supervisor => self.csr.sscratch = value,
+            SEPC if supervisor => self.csr.sepc = value & !1,
+            SCAUSE if supervisor => self.csr.scause = value,
+            STVAL if supervisor => self.csr.stval = value,
+            SIP if supervisor => {
+                let mask = self.csr.mideleg & MIP_SSIP;
+                self.csr.mip = self.csr.mip & !mask | value & mask;
+            },
+            SATP if supervisor => {
+                // Writes selecting an unsupported translation mode have no effect.
+                if Self::is_satp_valid(value) {
+                    self.csr.satp = value;
+                    self.tlb.flush();
+                }
+            },
+            _ if privileged && (PMPCFG0..PMPCFG0 + 16).contains(&csr) => return self.write_pmpcfg((csr - PMPCFG0) as usize, value),
+            _ if privileged && (PMPADDR0..PMPADDR0 + 64).contains(&csr) => self.write_pmpaddr((csr - PMPADDR0) as usize, value),
+            _ => return false,
+        };
+        true
+    }
+
+    /// Returns the value of the CSR with the given name. See [`get_csr_number`] for the accepted names.
+    pub fn read_csr_by_name(&self, name: &str) -> Option<U> {
+        self.read_csr(get_csr_number(name)?)
+    }
+
+    /// Writes the given value to the CSR with the given name. See [`write_csr`](Self::write_csr).
+    pub fn write_csr_by_name(&mut self, name: &str, value: U) -> bool {
+        match get_csr_number(name) {
+            Some(csr) => self.write_csr(csr, value),
+            None => false,
+        }
+    }
+
+    /// Returns true if the given single-letter extension is enabled in `misa`.
+    pub fn has_extension(&self, ext: char) -> bool {
+        self.csr.misa & 1 << (ext as u64 - 'A' as u64) != 0
+    }
+
+    /// Returns true if the given privilege mode is implemented on the hart.
+    pub(super) fn is_mode_supported(&self, mode: u64) -> bool {
+        mode == PrivilegeMode::Machine as u64 ||
+        mode == PrivilegeMode::Supervisor as u64 && self.has_extension('S') ||
+        mode == PrivilegeMode::User as u64 && self.has_extension('U')
+    }
+
+    /// Bit of `mstatus` holding the read-only SD field.
+    fn sd_bit(&self) -> u64 {
+        1 << (Self::XLEN - 1)
+    }
+
+    /// Returns `mstatus` with the SD bit set if the floating-point state is dirty.
+    fn mstatus_with_sd(&self) -> u64 {
+        if self.csr.mstatus & MSTATUS_FS == MSTATUS_FS {
+            self.csr.mstatus | self.sd_bit()
+        } else {
+            self.csr.mstatus
+        }
+    }
+
+    /// WARL behaviour of `mtvec` and `stvec`: reserved modes are ignored.
+    fn write_tvec(old: u64, value: u64) -> u64 {
+        let mode = if value & 0b11 < 2 { value & 0b11 } else { old & 0b11 };
+        value & !0b11 | mode
+    }
+
+    /// Returns true if the given CSR can be accessed in the current privilege mode.
+    fn is_csr_accessible(&self, csr: u16) -> bool {
+        if get_privilege_level(csr) > self.mode as u8 {
+            return false;
+        }
+
+        if (FFLAGS..=FCSR).contains(&csr) && !self.is_fp_enabled() {
+            return false;
+        }
+
+        if csr == SATP && self.mode == PrivilegeMode::Supervisor && self.csr.mstatus & MSTATUS_TVM != 0 {
+            return false;
+        }
+
+        if (CYCLE..=CYCLE + 0x1F).contains(&csr) || (CYCLEH..=CYCLEH + 0x1F).contains(&csr) {
+            let bit = 1 << (csr & 0x1F);
+            if self.mode < PrivilegeMode::Machine && self.csr.mcounteren & bit == 0 {
+                return false;
+            }
+            if self.mode == PrivilegeMode::User && self.has_extension('S') && self.csr.scounteren & bit == 0 {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Mask applied when reading `xepc`: bit 1 is masked when instructions are 32-bits aligned.
+    pub(super) fn epc_mask(&self) -> u64 {
+        if self.has_extension('C') { !1 } else { !0b11 }
+    }
+
+    /// Executes a CSR instruction: reads the old value in `rd` if `read` is true and writes `value(old)` if `write` is true.
+    /// When the CSR is not read, `value` receives 0.
+    pub(super) fn execute_csr(&mut self, read: bool, write: bool, value: impl Fn(U) -> U) {
+        let csr = self.inst.imm.as_u16() & 0xFFF;
+        if !self.is_csr_accessible(csr) {
+            return self.illegal_instruction();
+        }
+
+        let old = if read {
+            match self.read_csr(csr) {
+                Some(old) => old,
+                None => return self.illegal_instruction(),
+            }
+        } else {
+            0u32.into()
+        };
+
+        if write && !self.write_csr(csr, value(old)) {
+            return self.illegal_instruction();
+        }
+
+        if read && self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = old.as_s();
+        }
+    }
+}
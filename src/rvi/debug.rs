@@ -0,0 +1,181 @@
+//! Breakpoints and watchpoints of the hart.
+//!
+//! PC breakpoints and conditional breakpoints are checked before each instruction executed by [`RVI::run_until_stop`],
+//! which then stops without executing it. Watchpoints are checked on the virtual address of every load, store and AMO,
+//! and stop the hart after the instruction that triggered them. When no watchpoint is set, the only cost of an access
+//! is a check that the list of watchpoints is empty.
+
+use std::collections::HashSet;
+
+use crate::common::types::*;
+use crate::public::{ExecutionEnvironmentInterface, Traps};
+use crate::rvi::*;
+
+/// Kinds of memory accesses, and of the accesses watched by a watchpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchKind {
+    Write,
+    Read,
+    /// Reads and writes. AMOs are accesses of this kind.
+    Access,
+}
+
+impl WatchKind {
+    /// Returns true if a watchpoint of this kind triggers on an access of the given kind.
+    pub fn matches(self, access: WatchKind) -> bool {
+        self == WatchKind::Access || access == WatchKind::Access || self == access
+    }
+}
+
+/// Reason for [`RVI::run_until_stop`] to return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason<U> {
+    /// The hart reached a PC breakpoint. The instruction at `pc` has not been executed.
+    Breakpoint { pc: U },
+    /// The conditional breakpoint `id` returned true. The instruction at `pc` has not been executed.
+    Condition { id: usize, pc: U },
+    /// The instruction at `pc` accessed `addr`, watched by a watchpoint of the given kind. It has been executed.
+    Watchpoint { kind: WatchKind, addr: U, pc: U },
+    /// A trap occured, see [`StepResult::Trap`].
+    Trap { cause: Traps, tval: U, pc: U },
+    /// The maximum number of instructions has been executed.
+    InstructionLimit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Watchpoint {
+    kind: WatchKind,
+    addr: u64,
+    len: u64,
+}
+
+/// Condition evaluated on the state of the hart before each instruction.
+type Condition<U, S, EEI, const N: usize> = Box<dyn Fn(&RVI<U, S, EEI, N>) -> bool + Send>;
+
+/// Breakpoints and watchpoints set on a hart.
+pub(super) struct Debugger<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
+    breakpoints: HashSet<u64>,
+    conditions: Vec<(usize, Condition<U, S, EEI, N>)>,
+    next_condition: usize,
+    watchpoints: Vec<Watchpoint>,
+    /// The first watchpoint triggered during the current step, and the accessed address.
+    triggered: Option<(WatchKind, U)>,
+    /// Address of the last breakpoint the hart stopped on, not checked again when resuming.
+    resume_pc: Option<U>,
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> Debugger<U, S, EEI, N> {
+    pub(super) fn new() -> Self {
+        Self {
+            breakpoints: HashSet::new(),
+            conditions: Vec::new(),
+            next_condition: 0,
+            watchpoints: Vec::new(),
+            triggered: None,
+            resume_pc: None,
+        }
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Sets a breakpoint at the given address. Returns false if there was already one.
+    pub fn add_breakpoint(&mut self, pc: U) -> bool {
+        self.debug.breakpoints.insert(pc.as_u64())
+    }
+
+    /// Removes the breakpoint at the given address. Returns false if there was none.
+    pub fn remove_breakpoint(&mut self, pc: U) -> bool {
+        self.debug.breakpoints.remove(&pc.as_u64())
+    }
+
+    /// Adds a breakpoint stopping the hart before an instruction when `condition` returns true, and returns its id.
+    pub fn add_condition<F: Fn(&Self) -> bool + Send + 'static>(&mut self, condition: F) -> usize {
+        let id = self.debug.next_condition;
+        self.debug.next_condition += 1;
+        self.debug.conditions.push((id, Box::new(condition)));
+        id
+    }
+
+    /// Removes the conditional breakpoint with the given id. Returns false if there was none.
+    pub fn remove_condition(&mut self, id: usize) -> bool {
+        let len = self.debug.conditions.len();
+        self.debug.conditions.retain(|(condition, _)| *condition != id);
+        self.debug.conditions.len() != len
+    }
+
+    /// Watches the accesses of the given kind to the `len` bytes at the virtual address `addr`.
+    pub fn add_watchpoint(&mut self, addr: U, len: u64, kind: WatchKind) {
+        self.debug.watchpoints.push(Watchpoint { kind, addr: addr.as_u64(), len });
+    }
+
+    /// Removes a watchpoint added with the same arguments. Returns false if there was none.
+    pub fn remove_watchpoint(&mut self, addr: U, len: u64, kind: WatchKind) -> bool {
+        let watchpoint = Watchpoint { kind, addr: addr.as_u64(), len };
+        match self.debug.watchpoints.iter().position(|w| *w == watchpoint) {
+            Some(index) => {
+                self.debug.watchpoints.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Removes all the breakpoints, conditional breakpoints and watchpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.debug = Debugger::new();
+    }
+
+    /// Executes at most `max_instructions` steps, stopping on breakpoints, watchpoints and traps.
+    ///
+    /// When resuming after a stop on a breakpoint, the breakpoints are not checked again for the first instruction.
+    pub fn run_until_stop(&mut self, max_instructions: usize) -> StopReason<U> {
+        for _ in 0..max_instructions {
+            let pc = self.pc;
+            if self.debug.resume_pc.take() != Some(pc) {
+                if let Some(stop) = self.check_breakpoints(pc) {
+                    self.debug.resume_pc = Some(pc);
+                    return stop;
+                }
+            }
+
+            self.debug.triggered = None;
+            match self.single_step() {
+                StepResult::Trap { cause, tval, pc } => return StopReason::Trap { cause, tval, pc },
+                StepResult::Retired { pc, .. } => {
+                    if let Some((kind, addr)) = self.debug.triggered.take() {
+                        return StopReason::Watchpoint { kind, addr, pc };
+                    }
+                },
+            }
+        }
+        StopReason::InstructionLimit
+    }
+
+    fn check_breakpoints(&self, pc: U) -> Option<StopReason<U>> {
+        if self.debug.breakpoints.contains(&pc.as_u64()) {
+            return Some(StopReason::Breakpoint { pc });
+        }
+        self.debug.conditions.iter()
+            .find(|(_, condition)| condition(self))
+            .map(|&(id, _)| StopReason::Condition { id, pc })
+    }
+
+    /// Records the access of `size` bytes at `vaddr` if it triggers a watchpoint.
+    #[inline(always)]
+    pub(super) fn watch(&mut self, vaddr: U, size: u64, access: WatchKind) {
+        if !self.debug.watchpoints.is_empty() {
+            self.check_watchpoints(vaddr, size, access);
+        }
+    }
+
+    #[cold]
+    fn check_watchpoints(&mut self, vaddr: U, size: u64, access: WatchKind) {
+        if self.debug.triggered.is_some() {
+            return;
+        }
+        let addr = vaddr.as_u64();
+        self.debug.triggered = self.debug.watchpoints.iter()
+            .find(|w| w.kind.matches(access) && addr < w.addr.wrapping_add(w.len) && w.addr < addr.wrapping_add(size))
+            .map(|w| (w.kind, vaddr));
+    }
+}
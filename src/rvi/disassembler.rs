@@ -1,4 +1,4 @@
-use crate::common::{isa::*, types::*};
+use crate::common::{csr::get_csr_name, isa::*, types::*};
 use crate::rvi::*;
 
 impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleI32<U, S, EEI, N> for RVI<U, S, EEI, N> {
@@ -171,7 +171,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     }
 }
 
-impl<EEI: ExecutionEnvironmentInterface<u64>> DisassembleI64<u64, i64> for RV64I<EEI> {
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> DisassembleI64<u64, i64> for RV64<EEI, N> {
     fn load_disassemble_i64(&mut self) {
         self.disassemble[ISA::ADDIW as usize..=ISA::SUBW as usize].copy_from_slice(&Self::DISASSEMBLE_I64);
     }
@@ -224,3 +224,579 @@ impl<EEI: ExecutionEnvironmentInterface<u64>> DisassembleI64<u64, i64> for RV64I
         format!("subw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
     }
 }
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleM32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_m32(&mut self) {
+        self.disassemble[ISA::DIV as usize..=ISA::REMU as usize].copy_from_slice(&Self::DISASSEMBLE_M32);
+    }
+
+    fn disassemble_DIV(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("div {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_DIVU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("divu {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_MUL(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("mul {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_MULH(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("mulh {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_MULHSU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("mulhsu {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_MULHU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("mulhu {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_REM(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("rem {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_REMU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("remu {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> DisassembleM64<u64, i64> for RV64<EEI, N> {
+    fn load_disassemble_m64(&mut self) {
+        self.disassemble[ISA::DIVUW as usize..=ISA::REMW as usize].copy_from_slice(&Self::DISASSEMBLE_M64);
+    }
+
+    fn disassemble_DIVUW(inst: Instruction64, abi_name: bool) -> String {
+        format!("divuw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_DIVW(inst: Instruction64, abi_name: bool) -> String {
+        format!("divw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_MULW(inst: Instruction64, abi_name: bool) -> String {
+        format!("mulw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_REMUW(inst: Instruction64, abi_name: bool) -> String {
+        format!("remuw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_REMW(inst: Instruction64, abi_name: bool) -> String {
+        format!("remw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+}
+
+/// Returns the suffix of an atomic instruction given its aq and rl bits.
+fn get_aqrl(opcode: u32) -> &'static str {
+    match opcode >> 25 & 0b11 {
+        0b01 => ".rl",
+        0b10 => ".aq",
+        0b11 => ".aqrl",
+        _ => "",
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleA32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_a32(&mut self) {
+        self.disassemble[ISA::AMOADD_W as usize..=ISA::SC_W as usize].copy_from_slice(&Self::DISASSEMBLE_A32);
+    }
+
+    fn disassemble_AMOADD_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amoadd.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOAND_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amoand.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMAX_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amomax.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMAXU_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amomaxu.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMIN_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amomin.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMINU_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amominu.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOOR_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amoor.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOSWAP_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amoswap.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOXOR_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("amoxor.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_LR_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("lr.w{} {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_SC_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("sc.w{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> DisassembleA64<u64, i64> for RV64<EEI, N> {
+    fn load_disassemble_a64(&mut self) {
+        self.disassemble[ISA::AMOADD_D as usize..=ISA::SC_D as usize].copy_from_slice(&Self::DISASSEMBLE_A64);
+    }
+
+    fn disassemble_AMOADD_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amoadd.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOAND_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amoand.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMAX_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amomax.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMAXU_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amomaxu.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMIN_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amomin.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOMINU_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amominu.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOOR_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amoor.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOSWAP_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amoswap.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_AMOXOR_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("amoxor.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_LR_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("lr.d{} {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_SC_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("sc.d{} {}, {}, ({})", get_aqrl(inst.opcode), get_x_register_name(inst.rd, abi_name), get_x_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+}
+
+/// Returns the rounding mode operand of a floating-point instruction, omitted when it is dynamic.
+fn get_rm(opcode: u32) -> &'static str {
+    match opcode >> 12 & 0b111 {
+        0b000 => ", rne",
+        0b001 => ", rtz",
+        0b010 => ", rdn",
+        0b011 => ", rup",
+        0b100 => ", rmm",
+        0b111 => "",
+        _ => ", invalid",
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleF32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_f32(&mut self) {
+        self.disassemble[ISA::FADD_S as usize..=ISA::FSW as usize].copy_from_slice(&Self::DISASSEMBLE_F32);
+    }
+
+    fn disassemble_FADD_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fadd.s {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCLASS_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fclass.s {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_FCVT_S_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.s.w {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_S_WU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.s.wu {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_W_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.w.s {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_WU_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.wu.s {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FDIV_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fdiv.s {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FEQ_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("feq.s {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FLE_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fle.s {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FLT_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("flt.s {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FLW(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("flw {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), inst.imm)
+    }
+
+    fn disassemble_FMADD_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmadd.s {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMAX_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmax.s {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FMIN_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmin.s {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FMSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmsub.s {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMUL_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmul.s {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMV_W_X(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmv.w.x {}, {}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_FMV_X_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmv.x.w {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_FNMADD_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fnmadd.s {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FNMSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fnmsub.s {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FSGNJ_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnj.s {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSGNJN_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnjn.s {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSGNJX_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnjx.s {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSQRT_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsqrt.s {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FSUB_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsub.s {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FSW(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsw {}, {}, {}", get_f_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name), inst.imm)
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> DisassembleF64<u64, i64> for RV64<EEI, N> {
+    fn load_disassemble_f64(&mut self) {
+        self.disassemble[ISA::FCVT_L_S as usize..=ISA::FCVT_S_LU as usize].copy_from_slice(&Self::DISASSEMBLE_F64);
+    }
+
+    fn disassemble_FCVT_L_S(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.l.s {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_LU_S(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.lu.s {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_S_L(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.s.l {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_S_LU(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.s.lu {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleD32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_d32(&mut self) {
+        self.disassemble[ISA::FADD_D as usize..=ISA::FSUB_D as usize].copy_from_slice(&Self::DISASSEMBLE_D32);
+    }
+
+    fn disassemble_FADD_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fadd.d {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCLASS_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fclass.d {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_FCVT_D_S(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.d.s {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_D_W(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.d.w {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_D_WU(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.d.wu {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_S_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.s.d {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_W_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.w.d {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_WU_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fcvt.wu.d {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FDIV_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fdiv.d {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FEQ_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("feq.d {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FLD(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fld {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), inst.imm)
+    }
+
+    fn disassemble_FLE_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fle.d {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FLT_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("flt.d {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FMADD_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmadd.d {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMAX_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmax.d {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FMIN_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmin.d {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FMSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmsub.d {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMUL_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fmul.d {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FNMADD_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fnmadd.d {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FNMSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fnmsub.d {}, {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_f_register_name(inst.rs3, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FSD(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsd {}, {}, {}", get_f_register_name(inst.rs2, abi_name), get_x_register_name(inst.rs1, abi_name), inst.imm)
+    }
+
+    fn disassemble_FSGNJ_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnj.d {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSGNJN_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnjn.d {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSGNJX_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsgnjx.d {}, {}, {}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_FSQRT_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsqrt.d {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FSUB_D(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("fsub.d {}, {}, {}{}", get_f_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_f_register_name(inst.rs2, abi_name), get_rm(inst.opcode))
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> DisassembleD64<u64, i64> for RV64<EEI, N> {
+    fn load_disassemble_d64(&mut self) {
+        self.disassemble[ISA::FCVT_D_L as usize..=ISA::FMV_X_D as usize].copy_from_slice(&Self::DISASSEMBLE_D64);
+    }
+
+    fn disassemble_FCVT_D_L(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.d.l {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_D_LU(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.d.lu {}, {}{}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_L_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.l.d {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FCVT_LU_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("fcvt.lu.d {}, {}{}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name), get_rm(inst.opcode))
+    }
+
+    fn disassemble_FMV_D_X(inst: Instruction64, abi_name: bool) -> String {
+        format!("fmv.d.x {}, {}", get_f_register_name(inst.rd, abi_name), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_FMV_X_D(inst: Instruction64, abi_name: bool) -> String {
+        format!("fmv.x.d {}, {}", get_x_register_name(inst.rd, abi_name), get_f_register_name(inst.rs1, abi_name))
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleZicsr<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_zicsr(&mut self) {
+        self.disassemble[ISA::CSRRC as usize..=ISA::CSRRWI as usize].copy_from_slice(&Self::DISASSEMBLE_ZICSR);
+    }
+
+    fn disassemble_CSRRC(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrc {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_CSRRCI(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrci {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), inst.rs1)
+    }
+
+    fn disassemble_CSRRS(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrs {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_CSRRSI(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrsi {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), inst.rs1)
+    }
+
+    fn disassemble_CSRRW(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrw {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), get_x_register_name(inst.rs1, abi_name))
+    }
+
+    fn disassemble_CSRRWI(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("csrrwi {}, {}, {}", get_x_register_name(inst.rd, abi_name), get_csr_name(inst.imm.as_u16()), inst.rs1)
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassembleZifencei<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_zifencei(&mut self) {
+        self.disassemble[ISA::FENCE_I as usize] = Self::DISASSEMBLE_ZIFENCEI[0];
+    }
+
+    fn disassemble_FENCE_I(_: Instruction<U, S>, _: bool) -> String {
+        String::from("fence.i")
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> DisassemblePrivileged<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_disassemble_privileged(&mut self) {
+        self.disassemble[ISA::MRET as usize..=ISA::WFI as usize].copy_from_slice(&Self::DISASSEMBLE_PRIVILEGED);
+    }
+
+    fn disassemble_MRET(_: Instruction<U, S>, _: bool) -> String {
+        String::from("mret")
+    }
+
+    fn disassemble_SFENCE_VMA(inst: Instruction<U, S>, abi_name: bool) -> String {
+        format!("sfence.vma {}, {}", get_x_register_name(inst.rs1, abi_name), get_x_register_name(inst.rs2, abi_name))
+    }
+
+    fn disassemble_SRET(_: Instruction<U, S>, _: bool) -> String {
+        String::from("sret")
+    }
+
+    fn disassemble_WFI(_: Instruction<U, S>, _: bool) -> String {
+        String::from("wfi")
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Disassembles a compressed instruction in its compressed form, from the fields of its 32-bit expansion.
+    pub fn disassemble_compressed(inst: Instruction<U, S>, abi_name: bool) -> String {
+        let x = |reg| get_x_register_name(reg, abi_name);
+        let f = |reg| get_f_register_name(reg, abi_name);
+        let stack = inst.opcode & 0b11 == 0b10;
+
+        match inst.inst {
+            ISA::ADDI if inst.opcode & 0b11 == 0b00 => format!("c.addi4spn {}, {}", x(inst.rd), inst.imm),
+            ISA::ADDI if inst.opcode >> 13 == 0b011 => format!("c.addi16sp {}", inst.imm),
+            ISA::ADDI if inst.opcode >> 13 == 0b010 => format!("c.li {}, {}", x(inst.rd), inst.imm),
+            ISA::ADDI if inst.rd == 0 => String::from("c.nop"),
+            ISA::ADDI => format!("c.addi {}, {}", x(inst.rd), inst.imm),
+            ISA::ADDIW => format!("c.addiw {}, {}", x(inst.rd), inst.imm),
+            ISA::LUI => format!("c.lui {}, {}", x(inst.rd), inst.imm),
+            ISA::SLLI => format!("c.slli {}, {}", x(inst.rd), inst.imm & 0x3F.into()),
+            ISA::SRLI => format!("c.srli {}, {}", x(inst.rd), inst.imm & 0x3F.into()),
+            ISA::SRAI => format!("c.srai {}, {}", x(inst.rd), inst.imm & 0x3F.into()),
+            ISA::ANDI => format!("c.andi {}, {}", x(inst.rd), inst.imm),
+            ISA::ADD if inst.rs1 == 0 => format!("c.mv {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::ADD => format!("c.add {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::SUB => format!("c.sub {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::XOR => format!("c.xor {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::OR => format!("c.or {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::AND => format!("c.and {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::SUBW => format!("c.subw {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::ADDW => format!("c.addw {}, {}", x(inst.rd), x(inst.rs2)),
+            ISA::JAL if inst.rd == 0 => format!("c.j {}", inst.imm),
+            ISA::JAL => format!("c.jal {}", inst.imm),
+            ISA::JALR if inst.rd == 0 => format!("c.jr {}", x(inst.rs1)),
+            ISA::JALR => format!("c.jalr {}", x(inst.rs1)),
+            ISA::BEQ => format!("c.beqz {}, {}", x(inst.rs1), inst.imm),
+            ISA::BNE => format!("c.bnez {}, {}", x(inst.rs1), inst.imm),
+            ISA::EBREAK => String::from("c.ebreak"),
+            ISA::LW if stack => format!("c.lwsp {}, {}", x(inst.rd), inst.imm),
+            ISA::LW => format!("c.lw {}, {}, {}", x(inst.rd), x(inst.rs1), inst.imm),
+            ISA::LD if stack => format!("c.ldsp {}, {}", x(inst.rd), inst.imm),
+            ISA::LD => format!("c.ld {}, {}, {}", x(inst.rd), x(inst.rs1), inst.imm),
+            ISA::SW if stack => format!("c.swsp {}, {}", x(inst.rs2), inst.imm),
+            ISA::SW => format!("c.sw {}, {}, {}", x(inst.rs2), x(inst.rs1), inst.imm),
+            ISA::SD if stack => format!("c.sdsp {}, {}", x(inst.rs2), inst.imm),
+            ISA::SD => format!("c.sd {}, {}, {}", x(inst.rs2), x(inst.rs1), inst.imm),
+            ISA::FLW if stack => format!("c.flwsp {}, {}", f(inst.rd), inst.imm),
+            ISA::FLW => format!("c.flw {}, {}, {}", f(inst.rd), x(inst.rs1), inst.imm),
+            ISA::FLD if stack => format!("c.fldsp {}, {}", f(inst.rd), inst.imm),
+            ISA::FLD => format!("c.fld {}, {}, {}", f(inst.rd), x(inst.rs1), inst.imm),
+            ISA::FSW if stack => format!("c.fswsp {}, {}", f(inst.rs2), inst.imm),
+            ISA::FSW => format!("c.fsw {}, {}, {}", f(inst.rs2), x(inst.rs1), inst.imm),
+            ISA::FSD if stack => format!("c.fsdsp {}, {}", f(inst.rs2), inst.imm),
+            ISA::FSD => format!("c.fsd {}, {}, {}", f(inst.rs2), x(inst.rs1), inst.imm),
+            _ => Self::disassemble_UNKNOWN(inst, abi_name),
+        }
+    }
+}
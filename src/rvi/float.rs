@@ -0,0 +1,204 @@
+//! Floating-point register file accesses and common execution steps of the F and D extensions.
+//!
+//! Single-precision values are NaN-boxed in the 64-bit `f` registers: the upper 32 bits are all ones.
+//! Reading an improperly boxed value as single-precision returns the canonical NaN.
+
+use crate::common::softfloat::*;
+use crate::common::types::*;
+use crate::public::ExecutionEnvironmentInterface;
+use crate::rvi::*;
+use csr::MSTATUS_FS;
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Returns the value of the given floating-point register in the given format.
+    pub(super) fn read_f(&self, f: Format, reg: u8) -> u64 {
+        let value = self.f[reg as usize];
+        if f == Format::DOUBLE {
+            value
+        } else if value >> 32 == 0xFFFF_FFFF {
+            value & 0xFFFF_FFFF
+        } else {
+            f.canonical_nan()
+        }
+    }
+
+    /// Writes the given value in the given format to a floating-point register, NaN-boxing single-precision values.
+    pub(super) fn write_f(&mut self, f: Format, reg: u8, value: u64) {
+        self.f[reg as usize] = if f == Format::DOUBLE { value } else { 0xFFFF_FFFF_0000_0000 | value };
+        self.set_fp_dirty();
+    }
+
+    /// Returns true if floating-point instructions can be executed (`mstatus.FS` is not Off).
+    pub(super) fn is_fp_enabled(&self) -> bool {
+        !self.config.privileged || self.csr.mstatus & MSTATUS_FS != 0
+    }
+
+    /// Sets `mstatus.FS` to Dirty.
+    pub(super) fn set_fp_dirty(&mut self) {
+        self.csr.mstatus |= MSTATUS_FS;
+    }
+
+    /// Returns the floating-point environment of the current instruction, with the static or dynamic rounding mode.
+    ///
+    /// Raises an illegal-instruction exception and returns `None` if the floating-point unit is disabled or the rounding mode is invalid.
+    fn begin_fp(&mut self) -> Option<Softfloat> {
+        if !self.is_fp_enabled() {
+            self.illegal_instruction();
+            return None;
+        }
+
+        let rm = match self.inst.opcode >> 12 & 0b111 {
+            0b111 => self.csr.fcsr >> 5 & 0b111,
+            rm => rm as u64,
+        };
+        match RoundingMode::from_u8(rm as u8) {
+            Some(rounding_mode) => Some(Softfloat::new(rounding_mode)),
+            None => {
+                self.illegal_instruction();
+                None
+            },
+        }
+    }
+
+    /// Accrues the exception flags raised by the instruction in `fflags`.
+    fn end_fp(&mut self, fp: Softfloat) {
+        if fp.flags != 0 {
+            self.csr.fcsr |= fp.flags as u64;
+            self.set_fp_dirty();
+        }
+    }
+
+    /// Writes the given value to `rd`, sign-extending it from `bits`.
+    fn write_x_sign_extended(&mut self, value: u64, bits: u32) {
+        if self.inst.rd != 0 {
+            let value = if bits == 32 { value as u32 as i32 as u64 } else { value };
+            self.x[self.inst.rd as usize] = U::from_u64(value).as_s();
+        }
+    }
+
+    /// Executes `rd = op(rs1)`.
+    pub(super) fn fp_unary(&mut self, f: Format, op: fn(&mut Softfloat, Format, u64) -> u64) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = op(&mut fp, f, self.read_f(f, self.inst.rs1));
+            self.write_f(f, self.inst.rd, result);
+            self.end_fp(fp);
+        }
+    }
+
+    /// Executes `rd = op(rs1, rs2)`.
+    pub(super) fn fp_binary(&mut self, f: Format, op: fn(&mut Softfloat, Format, u64, u64) -> u64) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = op(&mut fp, f, self.read_f(f, self.inst.rs1), self.read_f(f, self.inst.rs2));
+            self.write_f(f, self.inst.rd, result);
+            self.end_fp(fp);
+        }
+    }
+
+    /// Executes `rd = ±(rs1 * rs2) ± rs3`, negating the product and/or the addend.
+    pub(super) fn fp_fused(&mut self, f: Format, negate_product: bool, negate_addend: bool) {
+        if let Some(mut fp) = self.begin_fp() {
+            let a = self.read_f(f, self.inst.rs1);
+            let c = self.read_f(f, self.inst.rs3);
+            let a = f.with_sign(a, f.sign(a) != negate_product);
+            let c = f.with_sign(c, f.sign(c) != negate_addend);
+            let result = fp.fused_mul_add(f, a, self.read_f(f, self.inst.rs2), c);
+            self.write_f(f, self.inst.rd, result);
+            self.end_fp(fp);
+        }
+    }
+
+    /// Executes a sign injection: the result is `rs1` with the sign `op(sign(rs1), sign(rs2))`.
+    pub(super) fn fp_sign_injection(&mut self, f: Format, op: fn(bool, bool) -> bool) {
+        if self.begin_fp().is_some() {
+            let a = self.read_f(f, self.inst.rs1);
+            let b = self.read_f(f, self.inst.rs2);
+            self.write_f(f, self.inst.rd, f.with_sign(a, op(f.sign(a), f.sign(b))));
+        }
+    }
+
+    /// Executes a comparison, writing 1 to the integer register `rd` if it is true.
+    pub(super) fn fp_compare(&mut self, f: Format, op: fn(&mut Softfloat, Format, u64, u64) -> bool) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = op(&mut fp, f, self.read_f(f, self.inst.rs1), self.read_f(f, self.inst.rs2));
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = result.into();
+            }
+            self.end_fp(fp);
+        }
+    }
+
+    pub(super) fn fp_classify(&mut self, f: Format) {
+        if self.begin_fp().is_some() && self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = f.classify(self.read_f(f, self.inst.rs1)).into();
+        }
+    }
+
+    /// Converts `rs1` to a `bits`-bit integer. 32-bit results are sign-extended on RV64.
+    pub(super) fn fp_to_int(&mut self, f: Format, signed: bool, bits: u32) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = fp.to_int(f, self.read_f(f, self.inst.rs1), signed, bits);
+            self.write_x_sign_extended(result, bits);
+            self.end_fp(fp);
+        }
+    }
+
+    /// Converts the lower `bits` bits of the integer register `rs1` to a floating-point value.
+    pub(super) fn fp_from_int(&mut self, f: Format, signed: bool, bits: u32) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = fp.from_int(f, self.x[self.inst.rs1 as usize].as_u64(), signed, bits);
+            self.write_f(f, self.inst.rd, result);
+            self.end_fp(fp);
+        }
+    }
+
+    pub(super) fn fp_convert(&mut self, from: Format, to: Format) {
+        if let Some(mut fp) = self.begin_fp() {
+            let result = fp.convert(from, to, self.read_f(from, self.inst.rs1));
+            self.write_f(to, self.inst.rd, result);
+            self.end_fp(fp);
+        }
+    }
+
+    /// Moves the raw bits of `rs1` to the integer register `rd`. NaN-boxing is not checked.
+    pub(super) fn fp_move_to_int(&mut self, f: Format) {
+        if self.begin_fp().is_some() {
+            let value = self.f[self.inst.rs1 as usize];
+            self.write_x_sign_extended(value, if f == Format::DOUBLE { 64 } else { 32 });
+        }
+    }
+
+    /// Moves the lower bits of the integer register `rs1` to `rd`.
+    pub(super) fn fp_move_from_int(&mut self, f: Format) {
+        if self.begin_fp().is_some() {
+            let value = self.x[self.inst.rs1 as usize].as_u64();
+            self.write_f(f, self.inst.rd, if f == Format::DOUBLE { value } else { value & 0xFFFF_FFFF });
+        }
+    }
+
+    pub(super) fn fp_load(&mut self, f: Format) {
+        if !self.is_fp_enabled() {
+            return self.illegal_instruction();
+        }
+
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        let data = if f == Format::DOUBLE { self.load_64(addr) } else { self.load_32(addr).map(u64::from) };
+        if let Some(data) = data {
+            self.write_f(f, self.inst.rd, data);
+        }
+    }
+
+    /// Stores the raw bits of `rs2`. NaN-boxing is not checked.
+    pub(super) fn fp_store(&mut self, f: Format) {
+        if !self.is_fp_enabled() {
+            return self.illegal_instruction();
+        }
+
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        let data = self.f[self.inst.rs2 as usize];
+        if f == Format::DOUBLE {
+            self.store_64(addr, data);
+        } else {
+            self.store_32(addr, data as u32);
+        }
+    }
+}
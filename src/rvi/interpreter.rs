@@ -1,4 +1,5 @@
 use crate::common::isa::*;
+use crate::common::softfloat::{Format, Softfloat};
 use crate::public::*;
 use crate::rvi::*;
 
@@ -8,7 +9,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     }
 
     fn UNKNOWN(&mut self) {
-        self.eei.trap(Traps::IllegalInstruction);
+        self.illegal_instruction();
     }
 
     fn ADD(&mut self) {
@@ -45,7 +46,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if self.x[self.inst.rs1 as usize] == self.x[self.inst.rs2 as usize] {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -56,7 +57,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if self.x[self.inst.rs1 as usize] >= self.x[self.inst.rs2 as usize] {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -67,7 +68,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if (self.x[self.inst.rs1 as usize].as_u()) >= (self.x[self.inst.rs2 as usize].as_u()) {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -78,7 +79,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if self.x[self.inst.rs1 as usize] < self.x[self.inst.rs2 as usize] {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -89,7 +90,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if (self.x[self.inst.rs1 as usize].as_u()) < (self.x[self.inst.rs2 as usize].as_u()) {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -100,7 +101,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
         if self.x[self.inst.rs1 as usize] != self.x[self.inst.rs2 as usize] {
             let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
             if self.is_misaligned(pc) {
-                self.eei.trap(Traps::InstructionAddressMisaligned);
+                self.trap(Traps::InstructionAddressMisaligned, pc);
             } else {
                 self.pc = pc;
             }
@@ -108,11 +109,16 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     }
 
     fn EBREAK(&mut self) {
-        self.eei.trap(Traps::Breakpoint);
+        self.trap(Traps::Breakpoint, self.inst.pc);
     }
 
     fn ECALL(&mut self) {
-        self.eei.trap(Traps::SystemCall);
+        let trap = match self.mode {
+            PrivilegeMode::User => Traps::EnvironmentCallFromUMode,
+            PrivilegeMode::Supervisor => Traps::EnvironmentCallFromSMode,
+            PrivilegeMode::Machine => Traps::EnvironmentCallFromMMode,
+        };
+        self.trap(trap, 0u32.into());
     }
 
     fn FENCE(&mut self) {
@@ -121,60 +127,60 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     fn JAL(&mut self) {
         let pc = (self.inst.pc.as_s() + self.inst.imm).as_u();
         if self.is_misaligned(pc) {
-            self.eei.trap(Traps::InstructionAddressMisaligned);
+            self.trap(Traps::InstructionAddressMisaligned, pc);
         } else {
-            self.pc = pc;
             if self.inst.rd != 0 {
-                self.x[self.inst.rd as usize] = self.inst.pc.as_s() + 4.into();
+                self.x[self.inst.rd as usize] = self.pc.as_s();
             }
+            self.pc = pc;
         }
     }
 
     fn JALR(&mut self) {
         let pc = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u() & 0xFFFF_FFFEu32.into();
         if self.is_misaligned(pc) {
-            self.eei.trap(Traps::InstructionAddressMisaligned);
+            self.trap(Traps::InstructionAddressMisaligned, pc);
         } else {
-            self.pc = pc;
             if self.inst.rd != 0 {
-                self.x[self.inst.rd as usize] = self.inst.pc.as_s() + 4.into();
+                self.x[self.inst.rd as usize] = self.pc.as_s();
             }
+            self.pc = pc;
         }
     }
 
     fn LB(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = (self.eei.get_8(addr) as i8).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_8(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i8).into();
+            }
         }
     }
 
     fn LBU(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = self.eei.get_8(addr).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_8(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data.into();
+            }
         }
     }
 
     fn LH(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = (self.eei.get_16(addr) as i16).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_16(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i16).into();
+            }
         }
     }
 
     fn LHU(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = self.eei.get_16(addr).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_16(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data.into();
+            }
         }
     }
 
@@ -185,11 +191,11 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     }
 
     fn LW(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = (self.eei.get_32(addr) as i32).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_32(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
         }
     }
 
@@ -207,12 +213,12 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
 
     fn SB(&mut self) {
         let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-        self.eei.set_8(addr, self.x[self.inst.rs2 as usize].as_u8());
+        self.store_8(addr, self.x[self.inst.rs2 as usize].as_u8());
     }
 
     fn SH(&mut self) {
         let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-        self.eei.set_16(addr, self.x[self.inst.rs2 as usize].as_u16());
+        self.store_16(addr, self.x[self.inst.rs2 as usize].as_u16());
     }
 
     fn SLL(&mut self) {
@@ -283,7 +289,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
 
     fn SW(&mut self) {
         let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-        self.eei.set_32(addr, self.x[self.inst.rs2 as usize].as_u32());
+        self.store_32(addr, self.x[self.inst.rs2 as usize].as_u32());
     }
 
     fn XOR(&mut self) {
@@ -299,7 +305,7 @@ impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const
     }
 }
 
-impl<EEI: ExecutionEnvironmentInterface<u64>> I64 for RV64I<EEI> {
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> I64 for RV64<EEI, N> {
     fn load_execute_i64(&mut self) {
         self.execute[ISA::ADDIW as usize..=ISA::SUBW as usize].copy_from_slice(&Self::EXECUTE_I64);
     }
@@ -317,26 +323,26 @@ impl<EEI: ExecutionEnvironmentInterface<u64>> I64 for RV64I<EEI> {
     }
 
     fn LD(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = (self.eei.get_64(addr) as i64).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_64(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
         }
     }
 
     fn LWU(&mut self) {
-        if self.inst.rd == 0 {
-            self.eei.trap(Traps::IllegalInstruction);
-        } else {
-            let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-            self.x[self.inst.rd as usize] = self.eei.get_32(addr).into();
+        let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
+        if let Some(data) = self.load_32(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data.into();
+            }
         }
     }
 
     fn SD(&mut self) {
         let addr = (self.x[self.inst.rs1 as usize] + self.inst.imm).as_u();
-        self.eei.set_64(addr, self.x[self.inst.rs2 as usize].as_u64());
+        self.store_64(addr, self.x[self.inst.rs2 as usize].as_u64());
     }
 
     fn SLLIW(&mut self) {
@@ -381,3 +387,691 @@ impl<EEI: ExecutionEnvironmentInterface<u64>> I64 for RV64I<EEI> {
         }
     }
 }
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> M32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_m32(&mut self) {
+        self.execute[ISA::DIV as usize..=ISA::REMU as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_M32);
+    }
+
+    fn DIV(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].div(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn DIVU(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].divu(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn MUL(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].mul(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn MULH(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].mulh(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn MULHSU(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].mulhsu(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn MULHU(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].mulhu(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn REM(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].rem(self.x[self.inst.rs2 as usize]);
+        }
+    }
+
+    fn REMU(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = self.x[self.inst.rs1 as usize].remu(self.x[self.inst.rs2 as usize]);
+        }
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> M64 for RV64<EEI, N> {
+    fn load_execute_m64(&mut self) {
+        self.execute[ISA::DIVUW as usize..=ISA::REMW as usize].copy_from_slice(&Self::EXECUTE_M64);
+    }
+
+    fn DIVUW(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = (self.x[self.inst.rs1 as usize] as i32).divu(self.x[self.inst.rs2 as usize] as i32).into();
+        }
+    }
+
+    fn DIVW(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = (self.x[self.inst.rs1 as usize] as i32).div(self.x[self.inst.rs2 as usize] as i32).into();
+        }
+    }
+
+    fn MULW(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = (self.x[self.inst.rs1 as usize] as i32).mul(self.x[self.inst.rs2 as usize] as i32).into();
+        }
+    }
+
+    fn REMUW(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = (self.x[self.inst.rs1 as usize] as i32).remu(self.x[self.inst.rs2 as usize] as i32).into();
+        }
+    }
+
+    fn REMW(&mut self) {
+        if self.inst.rd != 0 {
+            self.x[self.inst.rd as usize] = (self.x[self.inst.rs1 as usize] as i32).rem(self.x[self.inst.rs2 as usize] as i32).into();
+        }
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> A32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_a32(&mut self) {
+        self.execute[ISA::AMOADD_W as usize..=ISA::SC_W as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_A32);
+    }
+
+    fn AMOADD_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Add, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOAND_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::And, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOMAX_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Max, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOMAXU_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Maxu, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOMIN_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Min, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOMINU_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Minu, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOOR_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Or, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOSWAP_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Swap, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn AMOXOR_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.amo_32(addr, AMO::Xor, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn LR_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(data) = self.load_reserved_32(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (data as i32).into();
+            }
+        }
+    }
+
+    fn SC_W(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize].as_u();
+        if let Some(success) = self.store_conditional_32(addr, self.x[self.inst.rs2 as usize].as_u32()) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (!success).into();
+            }
+        }
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> A64 for RV64<EEI, N> {
+    fn load_execute_a64(&mut self) {
+        self.execute[ISA::AMOADD_D as usize..=ISA::SC_D as usize].copy_from_slice(&Self::EXECUTE_A64);
+    }
+
+    fn AMOADD_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Add, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOAND_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::And, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOMAX_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Max, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOMAXU_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Maxu, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOMIN_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Min, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOMINU_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Minu, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOOR_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Or, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOSWAP_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Swap, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn AMOXOR_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.amo_64(addr, AMO::Xor, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn LR_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(data) = self.load_reserved_64(addr) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = data as i64;
+            }
+        }
+    }
+
+    fn SC_D(&mut self) {
+        let addr = self.x[self.inst.rs1 as usize] as u64;
+        if let Some(success) = self.store_conditional_64(addr, self.x[self.inst.rs2 as usize] as u64) {
+            if self.inst.rd != 0 {
+                self.x[self.inst.rd as usize] = (!success).into();
+            }
+        }
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> F32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_f32(&mut self) {
+        self.execute[ISA::FADD_S as usize..=ISA::FSW as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_F32);
+    }
+
+    fn FADD_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::add);
+    }
+
+    fn FCLASS_S(&mut self) {
+        self.fp_classify(Format::SINGLE);
+    }
+
+    fn FCVT_S_W(&mut self) {
+        self.fp_from_int(Format::SINGLE, true, 32);
+    }
+
+    fn FCVT_S_WU(&mut self) {
+        self.fp_from_int(Format::SINGLE, false, 32);
+    }
+
+    fn FCVT_W_S(&mut self) {
+        self.fp_to_int(Format::SINGLE, true, 32);
+    }
+
+    fn FCVT_WU_S(&mut self) {
+        self.fp_to_int(Format::SINGLE, false, 32);
+    }
+
+    fn FDIV_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::div);
+    }
+
+    fn FEQ_S(&mut self) {
+        self.fp_compare(Format::SINGLE, Softfloat::eq);
+    }
+
+    fn FLE_S(&mut self) {
+        self.fp_compare(Format::SINGLE, Softfloat::le);
+    }
+
+    fn FLT_S(&mut self) {
+        self.fp_compare(Format::SINGLE, Softfloat::lt);
+    }
+
+    fn FLW(&mut self) {
+        self.fp_load(Format::SINGLE);
+    }
+
+    fn FMADD_S(&mut self) {
+        self.fp_fused(Format::SINGLE, false, false);
+    }
+
+    fn FMAX_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::max);
+    }
+
+    fn FMIN_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::min);
+    }
+
+    fn FMSUB_S(&mut self) {
+        self.fp_fused(Format::SINGLE, false, true);
+    }
+
+    fn FMUL_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::mul);
+    }
+
+    fn FMV_W_X(&mut self) {
+        self.fp_move_from_int(Format::SINGLE);
+    }
+
+    fn FMV_X_W(&mut self) {
+        self.fp_move_to_int(Format::SINGLE);
+    }
+
+    fn FNMADD_S(&mut self) {
+        self.fp_fused(Format::SINGLE, true, true);
+    }
+
+    fn FNMSUB_S(&mut self) {
+        self.fp_fused(Format::SINGLE, true, false);
+    }
+
+    fn FSGNJ_S(&mut self) {
+        self.fp_sign_injection(Format::SINGLE, |_, b| b);
+    }
+
+    fn FSGNJN_S(&mut self) {
+        self.fp_sign_injection(Format::SINGLE, |_, b| !b);
+    }
+
+    fn FSGNJX_S(&mut self) {
+        self.fp_sign_injection(Format::SINGLE, |a, b| a != b);
+    }
+
+    fn FSQRT_S(&mut self) {
+        self.fp_unary(Format::SINGLE, Softfloat::sqrt);
+    }
+
+    fn FSUB_S(&mut self) {
+        self.fp_binary(Format::SINGLE, Softfloat::sub);
+    }
+
+    fn FSW(&mut self) {
+        self.fp_store(Format::SINGLE);
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> F64 for RV64<EEI, N> {
+    fn load_execute_f64(&mut self) {
+        self.execute[ISA::FCVT_L_S as usize..=ISA::FCVT_S_LU as usize].copy_from_slice(&Self::EXECUTE_F64);
+    }
+
+    fn FCVT_L_S(&mut self) {
+        self.fp_to_int(Format::SINGLE, true, 64);
+    }
+
+    fn FCVT_LU_S(&mut self) {
+        self.fp_to_int(Format::SINGLE, false, 64);
+    }
+
+    fn FCVT_S_L(&mut self) {
+        self.fp_from_int(Format::SINGLE, true, 64);
+    }
+
+    fn FCVT_S_LU(&mut self) {
+        self.fp_from_int(Format::SINGLE, false, 64);
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> D32<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_d32(&mut self) {
+        self.execute[ISA::FADD_D as usize..=ISA::FSUB_D as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_D32);
+    }
+
+    fn FADD_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::add);
+    }
+
+    fn FCLASS_D(&mut self) {
+        self.fp_classify(Format::DOUBLE);
+    }
+
+    fn FCVT_D_S(&mut self) {
+        self.fp_convert(Format::SINGLE, Format::DOUBLE);
+    }
+
+    fn FCVT_D_W(&mut self) {
+        self.fp_from_int(Format::DOUBLE, true, 32);
+    }
+
+    fn FCVT_D_WU(&mut self) {
+        self.fp_from_int(Format::DOUBLE, false, 32);
+    }
+
+    fn FCVT_S_D(&mut self) {
+        self.fp_convert(Format::DOUBLE, Format::SINGLE);
+    }
+
+    fn FCVT_W_D(&mut self) {
+        self.fp_to_int(Format::DOUBLE, true, 32);
+    }
+
+    fn FCVT_WU_D(&mut self) {
+        self.fp_to_int(Format::DOUBLE, false, 32);
+    }
+
+    fn FDIV_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::div);
+    }
+
+    fn FEQ_D(&mut self) {
+        self.fp_compare(Format::DOUBLE, Softfloat::eq);
+    }
+
+    fn FLD(&mut self) {
+        self.fp_load(Format::DOUBLE);
+    }
+
+    fn FLE_D(&mut self) {
+        self.fp_compare(Format::DOUBLE, Softfloat::le);
+    }
+
+    fn FLT_D(&mut self) {
+        self.fp_compare(Format::DOUBLE, Softfloat::lt);
+    }
+
+    fn FMADD_D(&mut self) {
+        self.fp_fused(Format::DOUBLE, false, false);
+    }
+
+    fn FMAX_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::max);
+    }
+
+    fn FMIN_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::min);
+    }
+
+    fn FMSUB_D(&mut self) {
+        self.fp_fused(Format::DOUBLE, false, true);
+    }
+
+    fn FMUL_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::mul);
+    }
+
+    fn FNMADD_D(&mut self) {
+        self.fp_fused(Format::DOUBLE, true, true);
+    }
+
+    fn FNMSUB_D(&mut self) {
+        self.fp_fused(Format::DOUBLE, true, false);
+    }
+
+    fn FSD(&mut self) {
+        self.fp_store(Format::DOUBLE);
+    }
+
+    fn FSGNJ_D(&mut self) {
+        self.fp_sign_injection(Format::DOUBLE, |_, b| b);
+    }
+
+    fn FSGNJN_D(&mut self) {
+        self.fp_sign_injection(Format::DOUBLE, |_, b| !b);
+    }
+
+    fn FSGNJX_D(&mut self) {
+        self.fp_sign_injection(Format::DOUBLE, |a, b| a != b);
+    }
+
+    fn FSQRT_D(&mut self) {
+        self.fp_unary(Format::DOUBLE, Softfloat::sqrt);
+    }
+
+    fn FSUB_D(&mut self) {
+        self.fp_binary(Format::DOUBLE, Softfloat::sub);
+    }
+}
+
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> D64 for RV64<EEI, N> {
+    fn load_execute_d64(&mut self) {
+        self.execute[ISA::FCVT_D_L as usize..=ISA::FMV_X_D as usize].copy_from_slice(&Self::EXECUTE_D64);
+    }
+
+    fn FCVT_D_L(&mut self) {
+        self.fp_from_int(Format::DOUBLE, true, 64);
+    }
+
+    fn FCVT_D_LU(&mut self) {
+        self.fp_from_int(Format::DOUBLE, false, 64);
+    }
+
+    fn FCVT_L_D(&mut self) {
+        self.fp_to_int(Format::DOUBLE, true, 64);
+    }
+
+    fn FCVT_LU_D(&mut self) {
+        self.fp_to_int(Format::DOUBLE, false, 64);
+    }
+
+    fn FMV_D_X(&mut self) {
+        self.fp_move_from_int(Format::DOUBLE);
+    }
+
+    fn FMV_X_D(&mut self) {
+        self.fp_move_to_int(Format::DOUBLE);
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> Zicsr<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_zicsr(&mut self) {
+        self.execute[ISA::CSRRC as usize..=ISA::CSRRWI as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_ZICSR);
+    }
+
+    fn CSRRC(&mut self) {
+        let mask = self.x[self.inst.rs1 as usize].as_u();
+        self.execute_csr(true, self.inst.rs1 != 0, |old| old & !mask);
+    }
+
+    fn CSRRCI(&mut self) {
+        let mask = U::from(self.inst.rs1);
+        self.execute_csr(true, self.inst.rs1 != 0, |old| old & !mask);
+    }
+
+    fn CSRRS(&mut self) {
+        let mask = self.x[self.inst.rs1 as usize].as_u();
+        self.execute_csr(true, self.inst.rs1 != 0, |old| old | mask);
+    }
+
+    fn CSRRSI(&mut self) {
+        let mask = U::from(self.inst.rs1);
+        self.execute_csr(true, self.inst.rs1 != 0, |old| old | mask);
+    }
+
+    fn CSRRW(&mut self) {
+        let value = self.x[self.inst.rs1 as usize].as_u();
+        self.execute_csr(self.inst.rd != 0, true, |_| value);
+    }
+
+    fn CSRRWI(&mut self) {
+        let value = U::from(self.inst.rs1);
+        self.execute_csr(self.inst.rd != 0, true, |_| value);
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> Zifencei<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_zifencei(&mut self) {
+        self.execute[ISA::FENCE_I as usize] = RVI::<U, S, EEI, N>::EXECUTE_ZIFENCEI[0];
+    }
+
+    /// Instructions are fetched from memory at each step, so there is no instruction cache to synchronize.
+    fn FENCE_I(&mut self) {
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> Privileged<U, S, EEI, N> for RVI<U, S, EEI, N> {
+    fn load_execute_privileged(&mut self) {
+        self.execute[ISA::MRET as usize..=ISA::WFI as usize].copy_from_slice(&RVI::<U, S, EEI, N>::EXECUTE_PRIVILEGED);
+    }
+
+    fn MRET(&mut self) {
+        if self.mode < PrivilegeMode::Machine {
+            return self.illegal_instruction();
+        }
+
+        self.pc = U::from_u64(self.csr.mepc & self.epc_mask());
+
+        let mpie = self.csr.mstatus & csr::MSTATUS_MPIE != 0;
+        let mpp = self.csr.mstatus >> 11 & 0b11;
+        self.mode = match mpp {
+            0 => PrivilegeMode::User,
+            1 => PrivilegeMode::Supervisor,
+            _ => PrivilegeMode::Machine,
+        };
+
+        self.csr.mstatus &= !(csr::MSTATUS_MIE | csr::MSTATUS_MPP);
+        if mpie {
+            self.csr.mstatus |= csr::MSTATUS_MIE;
+        }
+        self.csr.mstatus |= csr::MSTATUS_MPIE | (self.least_privileged_mode() as u64) << 11;
+        if self.mode != PrivilegeMode::Machine {
+            self.csr.mstatus &= !csr::MSTATUS_MPRV;
+        }
+    }
+
+    fn SFENCE_VMA(&mut self) {
+        if self.mode < PrivilegeMode::Supervisor ||
+           self.mode == PrivilegeMode::Supervisor && self.csr.mstatus & csr::MSTATUS_TVM != 0 {
+            return self.illegal_instruction();
+        }
+
+        // Flushing more entries than required is allowed, so the ASID in rs2 is ignored.
+        if self.inst.rs1 == 0 {
+            self.tlb.flush();
+        } else {
+            self.tlb.flush_address(self.x[self.inst.rs1 as usize].as_u64());
+        }
+    }
+
+    fn SRET(&mut self) {
+        if self.mode < PrivilegeMode::Supervisor ||
+           self.mode == PrivilegeMode::Supervisor && self.csr.mstatus & csr::MSTATUS_TSR != 0 {
+            return self.illegal_instruction();
+        }
+
+        self.pc = U::from_u64(self.csr.sepc & self.epc_mask());
+
+        let spie = self.csr.mstatus & csr::MSTATUS_SPIE != 0;
+        self.mode = if self.csr.mstatus & csr::MSTATUS_SPP != 0 { PrivilegeMode::Supervisor } else { PrivilegeMode::User };
+
+        self.csr.mstatus &= !(csr::MSTATUS_SIE | csr::MSTATUS_SPP | csr::MSTATUS_MPRV);
+        if spie {
+            self.csr.mstatus |= csr::MSTATUS_SIE;
+        }
+        self.csr.mstatus |= csr::MSTATUS_SPIE;
+    }
+
+    fn WFI(&mut self) {
+        if self.mode < PrivilegeMode::Machine && self.csr.mstatus & csr::MSTATUS_TW != 0 ||
+           self.mode == PrivilegeMode::User && self.has_extension('S') {
+            self.illegal_instruction();
+        }
+    }
+}
@@ -0,0 +1,561 @@
+//! Virtual memory translation (Sv32, Sv39 and Sv48) with a software TLB.
+//!
+//! Every access is then checked by the physical memory protection.
+//! The accessed and dirty bits of the page table entries are updated by the hart when needed.
+//! Physical addresses wider than XLEN (Sv32 has 34-bits physical addresses) are truncated before being sent to [`MemoryAccess`](crate::public::MemoryAccess).
+
+use crate::common::types::*;
+use crate::public::{AMO, ExecutionEnvironmentInterface, MemoryFault, Traps};
+use crate::rvi::*;
+use csr::*;
+use debug::WatchKind;
+
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+const PTE_X: u64 = 1 << 3;
+const PTE_U: u64 = 1 << 4;
+const PTE_G: u64 = 1 << 5;
+const PTE_A: u64 = 1 << 6;
+const PTE_D: u64 = 1 << 7;
+
+const PAGE_SHIFT: u64 = 12;
+const TLB_SIZE: usize = 256;
+
+/// The kind of memory access being translated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessType {
+    Fetch,
+    Load,
+    Store,
+}
+
+impl AccessType {
+    fn page_fault(self) -> Traps {
+        match self {
+            AccessType::Fetch => Traps::InstructionPageFault,
+            AccessType::Load => Traps::LoadPageFault,
+            AccessType::Store => Traps::StoreAMOPageFault,
+        }
+    }
+
+    fn access_fault(self) -> Traps {
+        match self {
+            AccessType::Fetch => Traps::InstructionAccessFault,
+            AccessType::Load => Traps::LoadAccessFault,
+            AccessType::Store => Traps::StoreAMOAccessFault,
+        }
+    }
+
+    fn misaligned(self) -> Traps {
+        match self {
+            AccessType::Fetch => Traps::InstructionAddressMisaligned,
+            AccessType::Load => Traps::LoadAddressMisaligned,
+            AccessType::Store => Traps::StoreAMOAddressMisaligned,
+        }
+    }
+
+    fn fault(self, fault: MemoryFault) -> Traps {
+        match fault {
+            MemoryFault::AccessFault => self.access_fault(),
+            MemoryFault::Misaligned => self.misaligned(),
+        }
+    }
+}
+
+/// Translation modes of the `satp` register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranslationMode {
+    Bare,
+    Sv32,
+    Sv39,
+    Sv48,
+}
+
+impl TranslationMode {
+    /// Returns the number of levels, the size of a PTE in bytes and the number of bits of each VPN field.
+    fn parameters(self) -> (u64, u64, u64) {
+        match self {
+            TranslationMode::Bare => (0, 0, 0),
+            TranslationMode::Sv32 => (2, 4, 10),
+            TranslationMode::Sv39 => (3, 8, 9),
+            TranslationMode::Sv48 => (4, 8, 9),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TLBEntry {
+    valid: bool,
+    vpn: u64,
+    asid: u64,
+    ppn: u64,
+    pte: u64,
+}
+
+/// Software TLB caching the 4KiB-page translations, direct-mapped on the virtual page number.
+#[derive(Clone, Debug)]
+pub struct TLB {
+    entries: Vec<TLBEntry>,
+}
+
+impl Default for TLB {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TLB {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![TLBEntry { valid: false, vpn: 0, asid: 0, ppn: 0, pte: 0 }; TLB_SIZE],
+        }
+    }
+
+    /// Invalidates every entry.
+    pub fn flush(&mut self) {
+        self.entries.iter_mut().for_each(|entry| entry.valid = false);
+    }
+
+    /// Invalidates the entry of the given virtual address.
+    pub fn flush_address(&mut self, vaddr: u64) {
+        let vpn = vaddr >> PAGE_SHIFT;
+        let entry = &mut self.entries[vpn as usize % TLB_SIZE];
+        if entry.vpn == vpn {
+            entry.valid = false;
+        }
+    }
+
+    fn get(&self, vpn: u64, asid: u64) -> Option<&TLBEntry> {
+        let entry = &self.entries[vpn as usize % TLB_SIZE];
+        if entry.valid && entry.vpn == vpn && (entry.asid == asid || entry.pte & PTE_G != 0) {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, vpn: u64, asid: u64, ppn: u64, pte: u64) {
+        self.entries[vpn as usize % TLB_SIZE] = TLBEntry { valid: true, vpn, asid, ppn, pte };
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Returns the translation mode currently selected in `satp`.
+    pub fn translation_mode(&self) -> TranslationMode {
+        if Self::XLEN == 32 {
+            if self.csr.satp >> 31 & 1 == 1 { TranslationMode::Sv32 } else { TranslationMode::Bare }
+        } else {
+            match self.csr.satp >> 60 {
+                8 => TranslationMode::Sv39,
+                9 => TranslationMode::Sv48,
+                _ => TranslationMode::Bare,
+            }
+        }
+    }
+
+    /// Returns true if the given value of `satp` selects a supported translation mode.
+    pub(super) fn is_satp_valid(value: u64) -> bool {
+        Self::XLEN == 32 || matches!(value >> 60, 0 | 8 | 9)
+    }
+
+    fn satp_asid(&self) -> u64 {
+        if Self::XLEN == 32 {
+            self.csr.satp >> 22 & 0x1FF
+        } else {
+            self.csr.satp >> 44 & 0xFFFF
+        }
+    }
+
+    fn satp_ppn(&self) -> u64 {
+        if Self::XLEN == 32 {
+            self.csr.satp & 0x3F_FFFF
+        } else {
+            self.csr.satp & 0xFFF_FFFF_FFFF
+        }
+    }
+
+    /// Returns the privilege mode used for the given access, taking `mstatus.MPRV` into account.
+    fn effective_mode(&self, access: AccessType) -> PrivilegeMode {
+        if access != AccessType::Fetch && self.mode == PrivilegeMode::Machine && self.csr.mstatus & MSTATUS_MPRV != 0 {
+            match self.csr.mstatus >> 11 & 0b11 {
+                0 => PrivilegeMode::User,
+                1 => PrivilegeMode::Supervisor,
+                _ => PrivilegeMode::Machine,
+            }
+        } else {
+            self.mode
+        }
+    }
+
+    /// Returns true if a leaf PTE with the given flags allows the given access.
+    fn check_permissions(&self, pte: u64, access: AccessType, mode: PrivilegeMode) -> bool {
+        if mode == PrivilegeMode::User && pte & PTE_U == 0 {
+            return false;
+        }
+        if mode == PrivilegeMode::Supervisor && pte & PTE_U != 0 &&
+           (access == AccessType::Fetch || self.csr.mstatus & MSTATUS_SUM == 0) {
+            return false;
+        }
+
+        match access {
+            AccessType::Fetch => pte & PTE_X != 0,
+            AccessType::Load => pte & PTE_R != 0 || pte & PTE_X != 0 && self.csr.mstatus & MSTATUS_MXR != 0,
+            AccessType::Store => pte & PTE_W != 0,
+        }
+    }
+
+    /// Translates the given virtual address to a physical address.
+    /// Returns the page fault to raise if the translation fails.
+    pub(super) fn translate(&mut self, vaddr: U, access: AccessType) -> Result<U, Traps> {
+        let mode = self.effective_mode(access);
+        let translation = self.translation_mode();
+        if mode == PrivilegeMode::Machine || translation == TranslationMode::Bare {
+            return Ok(vaddr);
+        }
+
+        let vaddr = vaddr.as_u64();
+        let vpn = vaddr >> PAGE_SHIFT;
+        let asid = self.satp_asid();
+        if let Some(entry) = self.tlb.get(vpn, asid) {
+            if self.check_permissions(entry.pte, access, mode) && (access != AccessType::Store || entry.pte & PTE_D != 0) {
+                return Ok(U::from_u64(entry.ppn << PAGE_SHIFT | vaddr & 0xFFF));
+            }
+        }
+
+        let (ppn, pte) = self.walk(vaddr, access, mode, translation)?;
+        self.tlb.insert(vpn, asid, ppn, pte);
+        Ok(U::from_u64(ppn << PAGE_SHIFT | vaddr & 0xFFF))
+    }
+
+    /// Walks the page table. Returns the physical page number of the 4KiB page containing `vaddr` and the leaf PTE.
+    /// Accesses to the page table are checked by the physical memory protection as S-mode accesses.
+    fn walk(&mut self, vaddr: u64, access: AccessType, mode: PrivilegeMode, translation: TranslationMode) -> Result<(u64, u64), Traps> {
+        let (levels, pte_size, vpn_bits) = translation.parameters();
+        let va_bits = PAGE_SHIFT + levels * vpn_bits;
+        let vpn_mask = (1 << vpn_bits) - 1;
+
+        if Self::XLEN == 64 {
+            // Upper bits must be a sign-extension of the highest virtual address bit.
+            let upper = (vaddr as i64 >> (va_bits - 1)) as u64;
+            if upper != 0 && upper != u64::MAX {
+                return Err(access.page_fault());
+            }
+        }
+
+        let mut table = self.satp_ppn() << PAGE_SHIFT;
+        for level in (0..levels).rev() {
+            let vpn = vaddr >> (PAGE_SHIFT + level * vpn_bits) & vpn_mask;
+            let pte_addr = table + vpn * pte_size;
+            if !self.pmp_check(pte_addr, pte_size, AccessType::Load, PrivilegeMode::Supervisor) {
+                return Err(access.access_fault());
+            }
+            let mut pte = if pte_size == 4 {
+                self.eei.get_32(U::from_u64(pte_addr)).map(|pte| pte as u64)
+            } else {
+                self.eei.get_64(U::from_u64(pte_addr))
+            }.map_err(|_| access.access_fault())?;
+
+            if pte & PTE_V == 0 || pte & PTE_R == 0 && pte & PTE_W != 0 {
+                return Err(access.page_fault());
+            }
+            if pte_size == 8 && pte >> 54 != 0 {
+                return Err(access.page_fault()); // Reserved bits, Svnapot and Svpbmt are not supported.
+            }
+
+            let ppn = if pte_size == 4 { pte >> 10 & 0x3F_FFFF } else { pte >> 10 & 0xFFF_FFFF_FFFF };
+            if pte & (PTE_R | PTE_X) == 0 {
+                table = ppn << PAGE_SHIFT;
+                continue;
+            }
+
+            if !self.check_permissions(pte, access, mode) {
+                return Err(access.page_fault());
+            }
+
+            let superpage_mask = (1 << (level * vpn_bits)) - 1;
+            if ppn & superpage_mask != 0 {
+                return Err(access.page_fault()); // Misaligned superpage.
+            }
+
+            if pte & PTE_A == 0 || access == AccessType::Store && pte & PTE_D == 0 {
+                pte |= PTE_A;
+                if access == AccessType::Store {
+                    pte |= PTE_D;
+                }
+                if !self.pmp_check(pte_addr, pte_size, AccessType::Store, PrivilegeMode::Supervisor) {
+                    return Err(access.access_fault());
+                }
+                if pte_size == 4 {
+                    self.eei.set_32(U::from_u64(pte_addr), pte as u32)
+                } else {
+                    self.eei.set_64(U::from_u64(pte_addr), pte)
+                }.map_err(|_| access.access_fault())?;
+            }
+
+            return Ok((ppn | vaddr >> PAGE_SHIFT & superpage_mask, pte));
+        }
+
+        Err(access.page_fault())
+    }
+
+    /// Translates the given address and checks the physical memory protection for an access of `size` bytes.
+    /// Raises the associated page fault or access fault if the access is not allowed.
+    fn translate_or_trap(&mut self, vaddr: U, size: u64, access: AccessType) -> Option<U> {
+        let result = self.translate(vaddr, access).and_then(|paddr| {
+            if self.pmp_check(paddr.as_u64(), size, access, self.effective_mode(access)) {
+                Ok(paddr)
+            } else {
+                Err(access.access_fault())
+            }
+        });
+
+        match result {
+            Ok(paddr) => Some(paddr),
+            Err(trap) => {
+                self.trap(trap, vaddr);
+                None
+            },
+        }
+    }
+
+    /// Returns the result of an access to the host, raising the exception associated with its fault if it failed.
+    fn check_fault<T>(&mut self, result: Result<T, MemoryFault>, vaddr: U, access: AccessType) -> Option<T> {
+        match result {
+            Ok(data) => Some(data),
+            Err(fault) => {
+                self.trap(access.fault(fault), vaddr);
+                None
+            },
+        }
+    }
+
+    /// Fetches the 32-bits instruction at the given virtual address.
+    pub(super) fn fetch_32(&mut self, vaddr: U) -> Option<u32> {
+        let paddr = self.translate_or_trap(vaddr, 4, AccessType::Fetch)?;
+        let result = self.eei.get_opcode_32(paddr);
+        self.check_fault(result, vaddr, AccessType::Fetch)
+    }
+
+    /// Fetches the 16-bits instruction parcel at the given virtual address.
+    pub(super) fn fetch_16(&mut self, vaddr: U) -> Option<u16> {
+        let paddr = self.translate_or_trap(vaddr, 2, AccessType::Fetch)?;
+        let result = self.eei.get_opcode_16(paddr);
+        self.check_fault(result, vaddr, AccessType::Fetch)
+    }
+
+    /// Fetches the instruction at the given virtual address.
+    ///
+    /// With the C extension instructions are only 16-bits aligned, so they are fetched one parcel at a time
+    /// and the upper parcel of a 32-bits instruction may lie on another page.
+    pub(super) fn fetch(&mut self, vaddr: U) -> Option<u32> {
+        if !self.has_extension('C') {
+            return self.fetch_32(vaddr);
+        }
+
+        let low = self.fetch_16(vaddr)?;
+        if get_instruction_length(low) == 2 {
+            Some(low as u32)
+        } else {
+            let high = self.fetch_16(vaddr + 2u32.into())?;
+            Some((high as u32) << 16 | low as u32)
+        }
+    }
+
+    /// Translates each byte of a misaligned access of `size` bytes, or raises the misaligned exception
+    /// if misaligned accesses are not emulated.
+    fn translate_misaligned(&mut self, vaddr: U, size: u64, access: AccessType) -> Option<[U; 8]> {
+        if self.config.misaligned_access == MisalignedAccess::Trap {
+            self.trap(access.misaligned(), vaddr);
+            return None;
+        }
+
+        let mut paddrs = [vaddr; 8];
+        for (i, paddr) in paddrs.iter_mut().enumerate().take(size as usize) {
+            *paddr = self.translate_or_trap(vaddr + U::from_u64(i as u64), 1, access)?;
+        }
+        Some(paddrs)
+    }
+
+    /// Loads `size` bytes at the given virtual address, using `get` for naturally aligned accesses.
+    fn load(&mut self, vaddr: U, size: u64, get: impl FnOnce(&mut EEI, U) -> Result<u64, MemoryFault>) -> Option<u64> {
+        if vaddr.as_u64() & (size - 1) == 0 {
+            let paddr = self.translate_or_trap(vaddr, size, AccessType::Load)?;
+            let result = get(&mut self.eei, paddr);
+            let data = self.check_fault(result, vaddr, AccessType::Load)?;
+            self.watch(vaddr, size, WatchKind::Read);
+            self.log_access(vaddr, size, data, false);
+            return Some(data);
+        }
+
+        let paddrs = self.translate_misaligned(vaddr, size, AccessType::Load)?;
+        let mut data = 0;
+        for (i, &paddr) in paddrs.iter().enumerate().take(size as usize) {
+            let result = self.eei.get_8(paddr);
+            data |= (self.check_fault(result, vaddr, AccessType::Load)? as u64) << (i * 8);
+        }
+        self.watch(vaddr, size, WatchKind::Read);
+        self.log_access(vaddr, size, data, false);
+        Some(data)
+    }
+
+    /// Stores the `size` lower bytes of `data` at the given virtual address, using `set` for naturally aligned accesses.
+    fn store(&mut self, vaddr: U, size: u64, data: u64, set: impl FnOnce(&mut EEI, U) -> Result<(), MemoryFault>) {
+        if vaddr.as_u64() & (size - 1) == 0 {
+            if let Some(paddr) = self.translate_or_trap(vaddr, size, AccessType::Store) {
+                self.invalidate_reservation(paddr, size);
+                let result = set(&mut self.eei, paddr);
+                if self.check_fault(result, vaddr, AccessType::Store).is_some() {
+                    self.watch(vaddr, size, WatchKind::Write);
+                    self.log_access(vaddr, size, data, true);
+                }
+            }
+            return;
+        }
+
+        if let Some(paddrs) = self.translate_misaligned(vaddr, size, AccessType::Store) {
+            for (i, &paddr) in paddrs.iter().enumerate().take(size as usize) {
+                self.invalidate_reservation(paddr, 1);
+                let result = self.eei.set_8(paddr, (data >> (i * 8)) as u8);
+                if self.check_fault(result, vaddr, AccessType::Store).is_none() {
+                    return;
+                }
+            }
+            self.watch(vaddr, size, WatchKind::Write);
+            self.log_access(vaddr, size, data, true);
+        }
+    }
+
+    pub(super) fn load_8(&mut self, vaddr: U) -> Option<u8> {
+        self.load(vaddr, 1, |eei, paddr| eei.get_8(paddr).map(u64::from)).map(|data| data as u8)
+    }
+
+    pub(super) fn load_16(&mut self, vaddr: U) -> Option<u16> {
+        self.load(vaddr, 2, |eei, paddr| eei.get_16(paddr).map(u64::from)).map(|data| data as u16)
+    }
+
+    pub(super) fn load_32(&mut self, vaddr: U) -> Option<u32> {
+        self.load(vaddr, 4, |eei, paddr| eei.get_32(paddr).map(u64::from)).map(|data| data as u32)
+    }
+
+    pub(super) fn load_64(&mut self, vaddr: U) -> Option<u64> {
+        self.load(vaddr, 8, |eei, paddr| eei.get_64(paddr))
+    }
+
+    pub(super) fn store_8(&mut self, vaddr: U, data: u8) {
+        self.store(vaddr, 1, data as u64, |eei, paddr| eei.set_8(paddr, data));
+    }
+
+    pub(super) fn store_16(&mut self, vaddr: U, data: u16) {
+        self.store(vaddr, 2, data as u64, |eei, paddr| eei.set_16(paddr, data));
+    }
+
+    pub(super) fn store_32(&mut self, vaddr: U, data: u32) {
+        self.store(vaddr, 4, data as u64, |eei, paddr| eei.set_32(paddr, data));
+    }
+
+    pub(super) fn store_64(&mut self, vaddr: U, data: u64) {
+        self.store(vaddr, 8, data, |eei, paddr| eei.set_64(paddr, data));
+    }
+
+    /// Invalidates the reservation if it overlaps with a store of `size` bytes at `paddr`.
+    /// The reservation set is the naturally aligned double-word containing the reserved address.
+    fn invalidate_reservation(&mut self, paddr: U, size: u64) {
+        if let Some(reserved) = self.reservation {
+            let start = reserved & !0b111;
+            if paddr.as_u64() < start + 8 && paddr.as_u64() + size > start {
+                self.reservation = None;
+            }
+        }
+    }
+
+    /// Raises the given misaligned trap if `vaddr` is not aligned on `size` bytes. AMOs are never emulated when misaligned.
+    fn check_atomic_alignment(&mut self, vaddr: U, size: u64, trap: Traps) -> Option<()> {
+        if vaddr.as_u64() & (size - 1) != 0 {
+            self.trap(trap, vaddr);
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    pub(super) fn load_reserved_32(&mut self, vaddr: U) -> Option<u32> {
+        self.check_atomic_alignment(vaddr, 4, Traps::LoadAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 4, AccessType::Load)?;
+        self.reservation = Some(paddr.as_u64());
+        let result = self.eei.load_reserved_32(paddr);
+        let data = self.check_fault(result, vaddr, AccessType::Load)?;
+        self.watch(vaddr, 4, WatchKind::Read);
+        self.log_access(vaddr, 4, data as u64, false);
+        Some(data)
+    }
+
+    pub(super) fn load_reserved_64(&mut self, vaddr: U) -> Option<u64> {
+        self.check_atomic_alignment(vaddr, 8, Traps::LoadAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 8, AccessType::Load)?;
+        self.reservation = Some(paddr.as_u64());
+        let result = self.eei.load_reserved_64(paddr);
+        let data = self.check_fault(result, vaddr, AccessType::Load)?;
+        self.watch(vaddr, 8, WatchKind::Read);
+        self.log_access(vaddr, 8, data, false);
+        Some(data)
+    }
+
+    /// Returns true if the store has been performed.
+    pub(super) fn store_conditional_32(&mut self, vaddr: U, data: u32) -> Option<bool> {
+        self.check_atomic_alignment(vaddr, 4, Traps::StoreAMOAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 4, AccessType::Store)?;
+        let reserved = self.reservation.take() == Some(paddr.as_u64());
+        if !reserved {
+            return Some(false);
+        }
+        let result = self.eei.store_conditional_32(paddr, data);
+        let success = self.check_fault(result, vaddr, AccessType::Store)?;
+        if success {
+            self.watch(vaddr, 4, WatchKind::Write);
+            self.log_access(vaddr, 4, data as u64, true);
+        }
+        Some(success)
+    }
+
+    /// Returns true if the store has been performed.
+    pub(super) fn store_conditional_64(&mut self, vaddr: U, data: u64) -> Option<bool> {
+        self.check_atomic_alignment(vaddr, 8, Traps::StoreAMOAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 8, AccessType::Store)?;
+        let reserved = self.reservation.take() == Some(paddr.as_u64());
+        if !reserved {
+            return Some(false);
+        }
+        let result = self.eei.store_conditional_64(paddr, data);
+        let success = self.check_fault(result, vaddr, AccessType::Store)?;
+        if success {
+            self.watch(vaddr, 8, WatchKind::Write);
+            self.log_access(vaddr, 8, data, true);
+        }
+        Some(success)
+    }
+
+    pub(super) fn amo_32(&mut self, vaddr: U, op: AMO, value: u32) -> Option<u32> {
+        self.check_atomic_alignment(vaddr, 4, Traps::StoreAMOAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 4, AccessType::Store)?;
+        self.invalidate_reservation(paddr, 4);
+        let result = self.eei.amo_32(paddr, op, value);
+        let data = self.check_fault(result, vaddr, AccessType::Store)?;
+        self.watch(vaddr, 4, WatchKind::Access);
+        self.log_access(vaddr, 4, data as u64, false);
+        self.log_access(vaddr, 4, op.apply_32(data, value) as u64, true);
+        Some(data)
+    }
+
+    pub(super) fn amo_64(&mut self, vaddr: U, op: AMO, value: u64) -> Option<u64> {
+        self.check_atomic_alignment(vaddr, 8, Traps::StoreAMOAddressMisaligned)?;
+        let paddr = self.translate_or_trap(vaddr, 8, AccessType::Store)?;
+        self.invalidate_reservation(paddr, 8);
+        let result = self.eei.amo_64(paddr, op, value);
+        let data = self.check_fault(result, vaddr, AccessType::Store)?;
+        self.watch(vaddr, 8, WatchKind::Access);
+        self.log_access(vaddr, 8, data, false);
+        self.log_access(vaddr, 8, op.apply_64(data, value), true);
+        Some(data)
+    }
+}
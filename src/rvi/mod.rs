@@ -1,19 +1,69 @@
 //! The core module, containing the structs, assembler, disassembler and interpreter.
 
 pub mod assembler;
+pub mod csr;
+pub mod debug;
 pub mod disassembler;
+mod float;
 mod interpreter;
+pub mod mmu;
+mod pmp;
+mod privileged;
+pub mod rvfi;
+pub mod trace;
 
-use crate::common::{*, instruction::*, isa::*, types::*};
-use crate::public::ExecutionEnvironmentInterface;
+use crate::common::{*, instruction::*, isa::*, isa_string::{ISAString, ISAStringError}, types::*};
+use csr::CSRFile;
+use debug::Debugger;
+use rvfi::Rvfi;
+use trace::{Access, CommitLog};
+use mmu::TLB;
+use crate::public::{ExecutionEnvironmentInterface, Traps};
 
 /// Configuration of the RISC-V hart.
 #[derive(Clone, Debug)]
 pub struct RVConfig {
-    /// The list of ISA extensions.
+    /// The ISA string, parsed by [`ISAString`]. It can be a full ISA string like `rv64imafdc_zicsr_zifencei`
+    /// or only list the extensions like `MZicsr`, in which case the base is the one of the hart.
     pub ext: String,
     /// Used by the disassembler. See [`get_x_register_name`] and [`get_f_register_name`].
     pub abi_name: bool,
+    /// Enables the machine-level privileged architecture.
+    /// If true, traps are taken by the hart and handled by the guest at the address in `mtvec`.
+    /// Otherwise they are sent to [`ExecutionEnvironmentInterface::trap`] and execution continues at the next instruction.
+    pub privileged: bool,
+    /// The number of physical memory protection entries (0, 16 or 64). Only used if `privileged` is true.
+    pub pmp_regions: usize,
+    /// What the hart does on loads and stores that are not naturally aligned. LR, SC and AMOs always trap.
+    pub misaligned_access: MisalignedAccess,
+}
+
+/// Handling of misaligned loads and stores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MisalignedAccess {
+    /// Raises a load or store/AMO address-misaligned exception.
+    Trap,
+    /// Splits the access into byte accesses, each one translated and checked separately.
+    /// Nothing is written if the translation or the memory protection check of any of the bytes of a store fails.
+    Emulate,
+}
+
+/// Privilege modes of the hart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PrivilegeMode {
+    User = 0,
+    Supervisor = 1,
+    Machine = 3,
+}
+
+/// Outcome of the execution of a single step of the hart.
+#[derive(Clone, Copy, Debug)]
+pub enum StepResult<U: Unsigned<S>, S: Signed<U>> {
+    /// The instruction at `pc` has been executed and retired.
+    Retired { pc: U, inst: Instruction<U, S> },
+    /// A trap occured. For exceptions `pc` is the address of the instruction that caused it,
+    /// and for interrupts the address of the next instruction, which has not been executed.
+    Trap { cause: Traps, tval: U, pc: U },
 }
 
 /// Struct representing a RISC-V hart.
@@ -25,13 +75,31 @@ pub struct RVConfig {
 pub struct RVI<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> {
     /// The integer registers. `x[0]` must always be 0.
     pub x: [S; N],
+    /// The floating-point registers. Single-precision values are NaN-boxed.
+    pub f: [u64; 32],
     /// The program counter. It points to the next instruction before executing the current instruction.
     pub pc: U,
 
+    /// The current privilege mode. Harts start in machine mode.
+    pub mode: PrivilegeMode,
+
     /// The instruction currently being executed.
     pub inst: Instruction<U, S>,
     /// Configuration of the context.
     pub config: RVConfig,
+    /// The ISA of the hart, parsed from `config.ext`.
+    pub isa: ISAString,
+    csr: CSRFile,
+    tlb: TLB,
+    /// Physical address reserved by the last LR instruction.
+    reservation: Option<u64>,
+    /// The trap raised during the current step.
+    trap: Option<StepResult<U, S>>,
+    debug: Debugger<U, S, EEI, N>,
+    commit_log: Option<CommitLog>,
+    /// Memory accesses of the current step, if recorded.
+    accesses: Option<Vec<Access>>,
+    rvfi: Option<Rvfi>,
     eei: EEI,
     execute: [fn(&mut Self); ISA::_SIZE as usize],
     disassemble: [fn(Instruction<U, S>, abi_name: bool) -> String; ISA::_SIZE as usize],
@@ -43,36 +111,128 @@ pub type RV32<EEI: ExecutionEnvironmentInterface<u32>, const N: usize> = RVI<u32
 pub type RV32E<EEI: ExecutionEnvironmentInterface<u32>> = RV32<EEI, 16>;
 /// Convenient alias defining a RV32I hart.
 pub type RV32I<EEI: ExecutionEnvironmentInterface<u32>> = RV32<EEI, 32>;
+/// Convenient alias defining a RV64 hart.
+pub type RV64<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> = RVI<u64, i64, EEI, N>;
+/// Convenient alias defining a RV64E hart.
+pub type RV64E<EEI: ExecutionEnvironmentInterface<u64>> = RV64<EEI, 16>;
 /// Convenient alias defining a RV64I hart.
-pub type RV64I<EEI: ExecutionEnvironmentInterface<u64>> = RVI<u64, i64, EEI, 32>;
+pub type RV64I<EEI: ExecutionEnvironmentInterface<u64>> = RV64<EEI, 32>;
 
 impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// The width of the integer registers in bits.
+    pub const XLEN: usize = std::mem::size_of::<U>() * 8;
+
     fn is_misaligned(&self, val: U) -> bool {
-        if self.config.ext.contains('C') {
+        if self.has_extension('C') {
             return !is_even(val);
         } else {
             return val & 0b11u32.into() != 0u32.into();
         }
     }
 
-    /// Executes a single intruction on the hart.
-    pub fn single_step(&mut self) {
+    /// Parses the ISA string of the configuration for this hart.
+    fn parse_isa(ext: &str) -> Result<ISAString, ISAStringError> {
+        ISAString::parse(ext).and_then(|isa| isa.for_hart(Self::XLEN, N))
+    }
+
+    /// Returns the disassembly of the given instruction. Compressed instructions are printed in their compressed form.
+    pub fn disassemble_instruction(&self, inst: Instruction<U, S>) -> String {
+        if get_instruction_length(inst.opcode as u16) == 2 && inst.inst != ISA::UNKNOWN {
+            Self::disassemble_compressed(inst, self.config.abi_name)
+        } else {
+            self.disassemble[inst.inst as usize](inst, self.config.abi_name)
+        }
+    }
+
+    /// Executes a single intruction on the hart, or takes a pending interrupt.
+    ///
+    /// Traps are also sent to [`ExecutionEnvironmentInterface::trap`] if [`RVConfig::privileged`] is false.
+    pub fn single_step(&mut self) -> StepResult<U, S> {
+        let mode = self.mode;
+        let x = if self.rvfi.is_some() { Some(self.x) } else { None };
+        let result = self.step();
+        if self.commit_log.is_some() {
+            self.log_step(mode, &result);
+        }
+        if let Some(x) = x {
+            self.rvfi_step(mode, &x, &result);
+        }
+        result
+    }
+
+    fn step(&mut self) -> StepResult<U, S> {
+        self.trap = None;
+        if let Some(accesses) = &mut self.accesses {
+            accesses.clear();
+        }
+        if self.config.privileged && self.take_interrupt() {
+            return self.trap.take().unwrap();
+        }
+
         let pc = self.pc;
         self.pc += 4u32.into();
-        let opcode = self.eei.get_opcode_32(pc); // TODO: instruction-address-misaligned
-        let inst_size = get_instruction_length(opcode as u16);
-        match inst_size {
-//            2 => if self.ext.contains('C'),
-            4 => {
-                self.inst = Instruction::<U, S>::from_opcode_32(pc, opcode);
+        self.inst = Instruction::<U, S>::empty(ISA::UNKNOWN, pc, 0);
+        if let Some(opcode) = self.fetch(pc) { // TODO: instruction-address-misaligned
+            match get_instruction_length(opcode as u16) {
+                2 if self.has_extension('C') => {
+                    self.pc = pc + 2u32.into();
+                    self.inst = Instruction::<U, S>::from_opcode_16(pc, opcode as u16);
+                },
+                4 => self.inst = Instruction::<U, S>::from_opcode_32(pc, opcode),
+                _ => self.inst.opcode = opcode,
+            };
+            if self.inst.max_x_register() as usize >= N {
+                self.inst.inst = ISA::UNKNOWN;
+            }
 
-                #[cfg(debug_assertions)]
-                println!("Instruction: {}", self.disassemble[self.inst.inst as usize](self.inst, self.config.abi_name));
+            self.execute[self.inst.inst as usize](self);
+        }
 
-                self.execute[self.inst.inst as usize](self);
+        self.csr.cycle = self.csr.cycle.wrapping_add(1);
+        match self.trap.take() {
+            Some(trap) => trap,
+            None => {
+                self.csr.instret = self.csr.instret.wrapping_add(1);
+                StepResult::Retired { pc, inst: self.inst }
             },
-            _ => println!("Unknown opcode {:#X} at {:#X}", opcode, pc),
-        };
+        }
+    }
+
+    /// Executes at most `max_instructions` steps, stopping after the first trap.
+    ///
+    /// Returns the trap, or `None` if all the instructions have been retired.
+    pub fn run(&mut self, max_instructions: usize) -> Option<StepResult<U, S>> {
+        for _ in 0..max_instructions {
+            if let trap @ StepResult::Trap { .. } = self.single_step() {
+                return Some(trap);
+            }
+        }
+        None
+    }
+
+    /// Executes steps until `predicate` returns true for the result of a step, and returns this result.
+    pub fn run_until<F: FnMut(&StepResult<U, S>) -> bool>(&mut self, mut predicate: F) -> StepResult<U, S> {
+        loop {
+            let result = self.single_step();
+            if predicate(&result) {
+                return result;
+            }
+        }
+    }
+
+    /// Returns the execution environment interface of the hart.
+    pub fn eei(&self) -> &EEI {
+        &self.eei
+    }
+
+    /// Returns the execution environment interface of the hart.
+    pub fn eei_mut(&mut self) -> &mut EEI {
+        &mut self.eei
+    }
+
+    /// Consumes the hart and returns its execution environment interface.
+    pub fn into_eei(self) -> EEI {
+        self.eei
     }
 }
 
@@ -81,44 +241,94 @@ impl<EEI: ExecutionEnvironmentInterface<u32>, const N: usize> RV32<EEI, N> {
     ///
     /// `x` is the initial state of the registers. `x[0]` will be set to 0 anyway. and must stay at 0.
     /// `pc` is the initial program counter value.
-    /// `ext` is a string containing the ISA extensions to be used.
+    /// `config` is the configuration of the hart.
     /// `eei` is the execution environment interface.
+    ///
+    /// Panics if `config.ext` is not a valid ISA string for the hart (see [`ISAString::for_hart`]),
+    /// use [`try_new`](Self::try_new) to get the error instead.
     pub fn new(x: [i32; N], pc: u32, config: RVConfig, eei: EEI) -> Self {
+        let ext = config.ext.clone();
+        Self::try_new(x, pc, config, eei).unwrap_or_else(|e| panic!("Invalid ISA string \"{}\": {}", ext, e))
+    }
+
+    /// Creates a new RV32I/E hart like [`new`](Self::new),
+    /// or returns an error if `config.ext` is not a valid ISA string for the hart.
+    ///
+    /// Panics if `config.pmp_regions` is not 0, 16 or 64.
+    pub fn try_new(x: [i32; N], pc: u32, config: RVConfig, eei: EEI) -> Result<Self, ISAStringError> {
+        assert!(matches!(config.pmp_regions, 0 | 16 | 64), "Invalid number of PMP regions");
+        let isa = Self::parse_isa(&config.ext)?;
         let mut core = Self {
             x,
+            f: [0; 32],
             pc,
+            mode: PrivilegeMode::Machine,
             inst: Instruction32::empty(ISA::UNKNOWN, 0u16.into(), 0),
+            csr: CSRFile::new(32, &isa),
+            tlb: TLB::new(),
+            reservation: None,
+            trap: None,
+            debug: Debugger::new(),
+            commit_log: None,
+            accesses: None,
+            rvfi: None,
             config,
+            isa,
             eei,
             execute: [RVI::UNKNOWN; ISA::_SIZE as usize],
             disassemble: [RV32::<EEI, N>::disassemble_UNKNOWN; ISA::_SIZE as usize],
         };
         core.x[0] = 0.into();
         core.load_isa();
-        core
+        Ok(core)
     }
 }
 
-impl<EEI: ExecutionEnvironmentInterface<u64>> RV64I<EEI> {
-    /// Creates a new RV64I hart.
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> RV64<EEI, N> {
+    /// Creates a new RV64I/E hart.
     ///
     /// `x` is the initial state of the registers. `x[0]` will be set to 0 anyway. and must stay at 0.
     /// `pc` is the initial program counter value.
-    /// `ext` is a string containing the ISA extensions to be used.
+    /// `config` is the configuration of the hart.
     /// `eei` is the execution environment interface.
-    pub fn new(x: [i64; 32], pc: u64, config: RVConfig, eei: EEI) -> Self {
+    ///
+    /// Panics if `config.ext` is not a valid ISA string for the hart (see [`ISAString::for_hart`]),
+    /// use [`try_new`](Self::try_new) to get the error instead.
+    pub fn new(x: [i64; N], pc: u64, config: RVConfig, eei: EEI) -> Self {
+        let ext = config.ext.clone();
+        Self::try_new(x, pc, config, eei).unwrap_or_else(|e| panic!("Invalid ISA string \"{}\": {}", ext, e))
+    }
+
+    /// Creates a new RV64I/E hart like [`new`](Self::new),
+    /// or returns an error if `config.ext` is not a valid ISA string for the hart.
+    ///
+    /// Panics if `config.pmp_regions` is not 0, 16 or 64.
+    pub fn try_new(x: [i64; N], pc: u64, config: RVConfig, eei: EEI) -> Result<Self, ISAStringError> {
+        assert!(matches!(config.pmp_regions, 0 | 16 | 64), "Invalid number of PMP regions");
+        let isa = Self::parse_isa(&config.ext)?;
         let mut core = Self {
             x,
+            f: [0; 32],
             pc,
+            mode: PrivilegeMode::Machine,
             inst: Instruction64::empty(ISA::UNKNOWN, 0u16.into(), 0),
+            csr: CSRFile::new(64, &isa),
+            tlb: TLB::new(),
+            reservation: None,
+            trap: None,
+            debug: Debugger::new(),
+            commit_log: None,
+            accesses: None,
+            rvfi: None,
             config,
+            isa,
             eei,
             execute: [RVI::UNKNOWN; ISA::_SIZE as usize],
-            disassemble: [RV64I::<EEI>::disassemble_UNKNOWN; ISA::_SIZE as usize],
+            disassemble: [RV64::<EEI, N>::disassemble_UNKNOWN; ISA::_SIZE as usize],
         };
         core.x[0] = 0.into();
         core.load_isa();
-        core
+        Ok(core)
     }
 }
 
@@ -130,14 +340,92 @@ impl<EEI: ExecutionEnvironmentInterface<u32>, const N: usize> LoadISA for RV32<E
     fn load_isa(&mut self) {
         self.load_execute_i32();
         self.load_disassemble_i32();
+
+        if self.isa.has("M") {
+            self.load_execute_m32();
+            self.load_disassemble_m32();
+        }
+
+        if self.isa.has("A") {
+            self.load_execute_a32();
+            self.load_disassemble_a32();
+        }
+
+        if self.isa.has("F") {
+            self.load_execute_f32();
+            self.load_disassemble_f32();
+        }
+
+        if self.isa.has("D") {
+            self.load_execute_d32();
+            self.load_disassemble_d32();
+        }
+
+        if self.isa.has("Zicsr") {
+            self.load_execute_zicsr();
+            self.load_disassemble_zicsr();
+        }
+
+        if self.isa.has("Zifencei") {
+            self.load_execute_zifencei();
+            self.load_disassemble_zifencei();
+        }
+
+        if self.config.privileged {
+            self.load_execute_privileged();
+            self.load_disassemble_privileged();
+        }
     }
 }
 
-impl<EEI: ExecutionEnvironmentInterface<u64>> LoadISA for RV64I<EEI> {
+impl<EEI: ExecutionEnvironmentInterface<u64>, const N: usize> LoadISA for RV64<EEI, N> {
     fn load_isa(&mut self) {
         self.load_execute_i32();
         self.load_disassemble_i32();
         self.load_execute_i64();
         self.load_disassemble_i64();
+
+        if self.isa.has("M") {
+            self.load_execute_m32();
+            self.load_disassemble_m32();
+            self.load_execute_m64();
+            self.load_disassemble_m64();
+        }
+
+        if self.isa.has("A") {
+            self.load_execute_a32();
+            self.load_disassemble_a32();
+            self.load_execute_a64();
+            self.load_disassemble_a64();
+        }
+
+        if self.isa.has("F") {
+            self.load_execute_f32();
+            self.load_disassemble_f32();
+            self.load_execute_f64();
+            self.load_disassemble_f64();
+        }
+
+        if self.isa.has("D") {
+            self.load_execute_d32();
+            self.load_disassemble_d32();
+            self.load_execute_d64();
+            self.load_disassemble_d64();
+        }
+
+        if self.isa.has("Zicsr") {
+            self.load_execute_zicsr();
+            self.load_disassemble_zicsr();
+        }
+
+        if self.isa.has("Zifencei") {
+            self.load_execute_zifencei();
+            self.load_disassemble_zifencei();
+        }
+
+        if self.config.privileged {
+            self.load_execute_privileged();
+            self.load_disassemble_privileged();
+        }
     }
 }
@@ -0,0 +1,114 @@
+//! Physical memory protection.
+//!
+//! The number of implemented entries is set by [`RVConfig::pmp_regions`]. The grain is 4 bytes.
+
+use crate::common::types::*;
+use crate::public::ExecutionEnvironmentInterface;
+use crate::rvi::*;
+use mmu::AccessType;
+
+const PMP_R: u8 = 1 << 0;
+const PMP_W: u8 = 1 << 1;
+const PMP_X: u8 = 1 << 2;
+const PMP_A: u8 = 0b11 << 3;
+const PMP_L: u8 = 1 << 7;
+
+const PMP_TOR: u8 = 1 << 3;
+const PMP_NA4: u8 = 2 << 3;
+const PMP_NAPOT: u8 = 3 << 3;
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Returns the value of the `pmpcfg` register of the given index, or `None` if it does not exist for this XLEN.
+    pub(super) fn read_pmpcfg(&self, index: usize) -> Option<u64> {
+        if Self::XLEN == 64 && !index.is_multiple_of(2) {
+            return None;
+        }
+
+        let first = index * 4;
+        Some((0..Self::XLEN / 8).rev().fold(0, |value, i| value << 8 | self.csr.pmpcfg[first + i] as u64))
+    }
+
+    /// Writes the `pmpcfg` register of the given index. Returns false if it does not exist for this XLEN.
+    pub(super) fn write_pmpcfg(&mut self, index: usize, value: u64) -> bool {
+        if Self::XLEN == 64 && !index.is_multiple_of(2) {
+            return false;
+        }
+
+        for i in 0..Self::XLEN / 8 {
+            let entry = index * 4 + i;
+            if entry >= self.config.pmp_regions || self.csr.pmpcfg[entry] & PMP_L != 0 {
+                continue;
+            }
+
+            let mut cfg = (value >> (i * 8)) as u8 & (PMP_R | PMP_W | PMP_X | PMP_A | PMP_L);
+            if cfg & PMP_R == 0 {
+                cfg &= !PMP_W; // R=0 W=1 is reserved.
+            }
+            self.csr.pmpcfg[entry] = cfg;
+        }
+        true
+    }
+
+    /// Writes the `pmpaddr` register of the given index.
+    pub(super) fn write_pmpaddr(&mut self, index: usize, value: u64) {
+        if index >= self.config.pmp_regions || self.csr.pmpcfg[index] & PMP_L != 0 {
+            return;
+        }
+        // The address is also locked when the next entry is a locked TOR entry.
+        if index + 1 < self.config.pmp_regions && self.csr.pmpcfg[index + 1] & (PMP_L | PMP_A) == PMP_L | PMP_TOR {
+            return;
+        }
+
+        let mask = if Self::XLEN == 32 { 0xFFFF_FFFF } else { 0x3F_FFFF_FFFF_FFFF };
+        self.csr.pmpaddr[index] = value & mask;
+    }
+
+    /// Returns the range of physical addresses `[start, end)` matched by the given entry, or `None` if it is disabled.
+    fn pmp_range(&self, entry: usize) -> Option<(u64, u64)> {
+        let addr = self.csr.pmpaddr[entry];
+        match self.csr.pmpcfg[entry] & PMP_A {
+            PMP_TOR => {
+                let start = if entry == 0 { 0 } else { self.csr.pmpaddr[entry - 1] << 2 };
+                Some((start, addr << 2))
+            },
+            PMP_NA4 => Some((addr << 2, (addr << 2) + 4)),
+            PMP_NAPOT => {
+                let ones = addr.trailing_ones();
+                let start = (addr & !((1 << ones) - 1)) << 2;
+                Some((start, start + (8 << ones)))
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns true if the physical memory protection allows the given access of `size` bytes at `paddr` in the given mode.
+    pub(super) fn pmp_check(&self, paddr: u64, size: u64, access: AccessType, mode: PrivilegeMode) -> bool {
+        // An access to the last bytes of the address space ends at 2^64.
+        let (paddr, end) = (paddr as u128, paddr as u128 + size as u128);
+        for entry in 0..self.config.pmp_regions {
+            let (start, stop) = match self.pmp_range(entry) {
+                Some((start, stop)) => (start as u128, stop as u128),
+                None => continue,
+            };
+
+            if paddr >= stop || end <= start {
+                continue;
+            }
+            if paddr < start || end > stop {
+                return false; // Only some bytes of the access match the entry.
+            }
+
+            let cfg = self.csr.pmpcfg[entry];
+            if mode == PrivilegeMode::Machine && cfg & PMP_L == 0 {
+                return true;
+            }
+            return match access {
+                AccessType::Fetch => cfg & PMP_X != 0,
+                AccessType::Load => cfg & PMP_R != 0,
+                AccessType::Store => cfg & PMP_W != 0,
+            };
+        }
+
+        mode == PrivilegeMode::Machine || self.config.pmp_regions == 0
+    }
+}
@@ -0,0 +1,130 @@
+//! Trap handling of the privileged architecture.
+
+use crate::common::types::*;
+use crate::public::{ExecutionEnvironmentInterface, Traps};
+use crate::rvi::*;
+use csr::*;
+
+/// Interrupts in decreasing priority order.
+const INTERRUPTS: [(u64, Traps); 6] = [
+    (MIP_MEIP, Traps::MachineExternalInterrupt),
+    (MIP_MSIP, Traps::MachineSoftwareInterrupt),
+    (MIP_MTIP, Traps::MachineTimerInterrupt),
+    (MIP_SEIP, Traps::SupervisorExternalInterrupt),
+    (MIP_SSIP, Traps::SupervisorSoftwareInterrupt),
+    (MIP_STIP, Traps::SupervisorTimerInterrupt),
+];
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Raises the given trap with the given trap value (bad address or instruction bits).
+    ///
+    /// If [`RVConfig::privileged`] is true, the trap is taken by the hart in machine mode, or in supervisor mode if delegated.
+    /// Otherwise it is sent to [`ExecutionEnvironmentInterface::trap`] and execution continues at the next instruction.
+    pub(super) fn trap(&mut self, trap: Traps, tval: U) {
+        self.reservation = None;
+        let epc = if trap.is_interrupt() { self.pc } else { self.inst.pc };
+        self.trap = Some(StepResult::Trap { cause: trap, tval, pc: epc });
+        if !self.config.privileged {
+            return self.eei.trap(trap, tval, epc);
+        }
+
+        let code = trap.exception_code() as u64;
+        let epc = epc.as_u64();
+        let deleg = if trap.is_interrupt() { self.csr.mideleg } else { self.csr.medeleg };
+
+        if self.mode <= PrivilegeMode::Supervisor && deleg & 1 << code != 0 {
+            self.csr.sepc = epc;
+            self.csr.scause = trap.cause(Self::XLEN);
+            self.csr.stval = tval.as_u64();
+
+            let sie = self.csr.mstatus & MSTATUS_SIE != 0;
+            self.csr.mstatus &= !(MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP);
+            if sie {
+                self.csr.mstatus |= MSTATUS_SPIE;
+            }
+            if self.mode == PrivilegeMode::Supervisor {
+                self.csr.mstatus |= MSTATUS_SPP;
+            }
+
+            self.mode = PrivilegeMode::Supervisor;
+            self.pc = U::from_u64(Self::trap_vector(self.csr.stvec, trap.is_interrupt(), code));
+        } else {
+            self.csr.mepc = epc;
+            self.csr.mcause = trap.cause(Self::XLEN);
+            self.csr.mtval = tval.as_u64();
+
+            let mie = self.csr.mstatus & MSTATUS_MIE != 0;
+            self.csr.mstatus &= !(MSTATUS_MIE | MSTATUS_MPIE | MSTATUS_MPP);
+            if mie {
+                self.csr.mstatus |= MSTATUS_MPIE;
+            }
+            self.csr.mstatus |= (self.mode as u64) << 11;
+
+            self.mode = PrivilegeMode::Machine;
+            self.pc = U::from_u64(Self::trap_vector(self.csr.mtvec, trap.is_interrupt(), code));
+        }
+    }
+
+    /// Returns the address of the trap handler given the value of `xtvec`.
+    fn trap_vector(tvec: u64, interrupt: bool, code: u64) -> u64 {
+        let base = tvec & !0b11;
+        if tvec & 0b11 == 1 && interrupt {
+            base + 4 * code
+        } else {
+            base
+        }
+    }
+
+    /// Raises an illegal instruction exception for the current instruction.
+    pub(super) fn illegal_instruction(&mut self) {
+        self.trap(Traps::IllegalInstruction, self.inst.opcode.into());
+    }
+
+    /// Takes the highest-priority pending and enabled interrupt, if any.
+    /// Returns true if an interrupt has been taken.
+    pub(super) fn take_interrupt(&mut self) -> bool {
+        let pending = self.csr.mip & self.csr.mie;
+        if pending == 0 {
+            return false;
+        }
+
+        let m_enabled = self.mode < PrivilegeMode::Machine || self.csr.mstatus & MSTATUS_MIE != 0;
+        let s_enabled = self.mode < PrivilegeMode::Supervisor ||
+            self.mode == PrivilegeMode::Supervisor && self.csr.mstatus & MSTATUS_SIE != 0;
+
+        let mut enabled = if m_enabled { pending & !self.csr.mideleg } else { 0 };
+        if enabled == 0 && s_enabled {
+            enabled = pending & self.csr.mideleg;
+        }
+
+        for (bit, interrupt) in INTERRUPTS.iter() {
+            if enabled & bit != 0 {
+                self.trap(*interrupt, 0u32.into());
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Sets or clears the pending bit of the given interrupt in the `mip` register.
+    /// Has no effect if `interrupt` is not an interrupt.
+    pub fn set_interrupt_pending(&mut self, interrupt: Traps, pending: bool) {
+        if interrupt.is_interrupt() {
+            let bit = 1 << interrupt.exception_code();
+            if pending {
+                self.csr.mip |= bit;
+            } else {
+                self.csr.mip &= !bit;
+            }
+        }
+    }
+
+    /// The least-privileged mode supported by the hart, used by xRET to clear `xPP`.
+    pub(super) fn least_privileged_mode(&self) -> PrivilegeMode {
+        if self.has_extension('U') {
+            PrivilegeMode::User
+        } else {
+            PrivilegeMode::Machine
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! RISC-V Formal Interface records of the retired instructions.
+//!
+//! When enabled with [`RVI::set_rvfi_callback`], every retired instruction and every exception produces an [`RvfiRecord`]
+//! with the signals of RVFI: the instruction, the PC before and after it, the integer registers it read and wrote,
+//! its memory accesses, and the state of the hart. Interrupts do not produce records, they set `intr` on the record
+//! of the first instruction of the handler.
+//!
+//! [`RvfiRecord::to_packet`] converts a record to the execution packet of RVFI-DII (version 1) used by TestRIG,
+//! and [`RVI::set_rvfi_dump`] writes the packets of the hart to a writer.
+
+use std::convert::TryInto;
+use std::io::{self, Write};
+
+use crate::common::types::*;
+use crate::public::ExecutionEnvironmentInterface;
+use crate::rvi::*;
+
+/// Size in bytes of an RVFI-DII execution packet.
+pub const RVFI_PACKET_SIZE: usize = 88;
+
+/// Signals of RVFI for one instruction. Register values are zero-extended to 64 bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RvfiRecord {
+    /// Index of the record, starting at 0.
+    pub order: u64,
+    /// The instruction bits, 0 if it could not be fetched.
+    pub insn: u64,
+    /// True if the instruction raised an exception.
+    pub trap: bool,
+    /// True for the last record of a trace. Never set by the hart.
+    pub halt: bool,
+    /// True for the first instruction of an interrupt handler.
+    pub intr: bool,
+    /// The privilege mode in which the instruction was executed.
+    pub mode: u8,
+    /// The XLEN of the hart: 1 for 32 bits, 2 for 64 bits.
+    pub ixl: u8,
+    pub rs1_addr: u8,
+    pub rs2_addr: u8,
+    pub rs1_rdata: u64,
+    pub rs2_rdata: u64,
+    /// The written integer register, 0 if none.
+    pub rd_addr: u8,
+    pub rd_wdata: u64,
+    pub pc_rdata: u64,
+    /// The address of the next instruction.
+    pub pc_wdata: u64,
+    /// The address of the load and store, which are at the same address for AMOs.
+    pub mem_addr: u64,
+    /// The bytes read from `mem_addr`, one bit per byte.
+    pub mem_rmask: u8,
+    /// The bytes written to `mem_addr`, one bit per byte.
+    pub mem_wmask: u8,
+    pub mem_rdata: u64,
+    pub mem_wdata: u64,
+}
+
+impl RvfiRecord {
+    /// Returns the RVFI-DII execution packet of the record. `mode` and `ixl` are not part of it.
+    pub fn to_packet(&self) -> [u8; RVFI_PACKET_SIZE] {
+        let mut packet = [0; RVFI_PACKET_SIZE];
+        let fields = [
+            self.order, self.pc_rdata, self.pc_wdata, self.insn, self.rs1_rdata, self.rs2_rdata,
+            self.rd_wdata, self.mem_addr, self.mem_rdata, self.mem_wdata,
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            packet[i * 8..i * 8 + 8].copy_from_slice(&field.to_le_bytes());
+        }
+        packet[80..].copy_from_slice(&[
+            self.mem_rmask, self.mem_wmask, self.rs1_addr, self.rs2_addr, self.rd_addr,
+            self.trap as u8, self.halt as u8, self.intr as u8,
+        ]);
+        packet
+    }
+
+    /// Parses an RVFI-DII execution packet. `mode` and `ixl` are set to 0.
+    pub fn from_packet(packet: &[u8; RVFI_PACKET_SIZE]) -> Self {
+        let field = |i: usize| u64::from_le_bytes(packet[i * 8..i * 8 + 8].try_into().unwrap());
+        Self {
+            order: field(0),
+            pc_rdata: field(1),
+            pc_wdata: field(2),
+            insn: field(3),
+            rs1_rdata: field(4),
+            rs2_rdata: field(5),
+            rd_wdata: field(6),
+            mem_addr: field(7),
+            mem_rdata: field(8),
+            mem_wdata: field(9),
+            mem_rmask: packet[80],
+            mem_wmask: packet[81],
+            rs1_addr: packet[82],
+            rs2_addr: packet[83],
+            rd_addr: packet[84],
+            trap: packet[85] != 0,
+            halt: packet[86] != 0,
+            intr: packet[87] != 0,
+            mode: 0,
+            ixl: 0,
+        }
+    }
+
+    /// Writes the RVFI-DII execution packet of the record.
+    pub fn write_packet<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_packet())
+    }
+}
+
+pub(super) struct Rvfi {
+    callback: Box<dyn FnMut(&RvfiRecord) + Send>,
+    order: u64,
+    /// An interrupt has been taken since the last record.
+    interrupted: bool,
+}
+
+/// Writer of the packets of [`RVI::set_rvfi_dump`], which ends the trace with a halt packet when dropped.
+struct PacketWriter<W: Write>(W);
+
+impl<W: Write> Drop for PacketWriter<W> {
+    fn drop(&mut self) {
+        RvfiRecord { halt: true, ..RvfiRecord::default() }.write_packet(&mut self.0).ok();
+        self.0.flush().ok();
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Calls `callback` with the RVFI record of every retired instruction and exception. See the [module documentation](self).
+    /// Enables the recording of the memory accesses, see [`accesses`](Self::accesses).
+    pub fn set_rvfi_callback<F: FnMut(&RvfiRecord) + Send + 'static>(&mut self, callback: F) {
+        self.rvfi = Some(Rvfi { callback: Box::new(callback), order: 0, interrupted: false });
+        self.record_accesses(true);
+    }
+
+    /// Writes the RVFI-DII execution packets of the hart to `writer`, followed by a halt packet
+    /// when the callback is cleared or the hart dropped. Errors of the writer are ignored.
+    pub fn set_rvfi_dump<W: Write + Send + 'static>(&mut self, writer: W) {
+        let mut writer = PacketWriter(writer);
+        self.set_rvfi_callback(move |record| { record.write_packet(&mut writer.0).ok(); });
+    }
+
+    /// Disables the RVFI records.
+    pub fn clear_rvfi_callback(&mut self) {
+        self.rvfi = None;
+    }
+
+    /// Sends the record of the given result of a step executed in `mode`, `x` being the registers before the step.
+    pub(super) fn rvfi_step(&mut self, mode: PrivilegeMode, x: &[S; N], result: &StepResult<U, S>) {
+        let (pc, trap) = match *result {
+            StepResult::Trap { cause, .. } if cause.is_interrupt() => {
+                self.rvfi.as_mut().unwrap().interrupted = true;
+                return;
+            },
+            StepResult::Trap { pc, .. } => (pc, true),
+            StepResult::Retired { pc, .. } => (pc, false),
+        };
+        let inst = self.inst;
+        let (rs1, rs2) = inst.x_sources();
+        let mut record = RvfiRecord {
+            insn: inst.opcode as u64,
+            trap,
+            mode: mode as u8,
+            ixl: if Self::XLEN == 32 { 1 } else { 2 },
+            rs1_addr: rs1,
+            rs2_addr: rs2,
+            rs1_rdata: x[rs1 as usize].as_u().as_u64(),
+            rs2_rdata: x[rs2 as usize].as_u().as_u64(),
+            pc_rdata: pc.as_u64(),
+            pc_wdata: self.pc.as_u64(),
+            ..RvfiRecord::default()
+        };
+        if !trap && inst.writes_x_register() && inst.rd != 0 {
+            record.rd_addr = inst.rd;
+            record.rd_wdata = self.x[inst.rd as usize].as_u().as_u64();
+        }
+        for access in self.accesses() {
+            let mask = ((1u16 << access.size) - 1) as u8;
+            record.mem_addr = access.addr;
+            if access.store {
+                record.mem_wmask = mask;
+                record.mem_wdata = access.data;
+            } else {
+                record.mem_rmask = mask;
+                record.mem_rdata = access.data;
+            }
+        }
+
+        let rvfi = self.rvfi.as_mut().unwrap();
+        record.order = rvfi.order;
+        record.intr = std::mem::take(&mut rvfi.interrupted);
+        rvfi.order += 1;
+        (rvfi.callback)(&record);
+    }
+}
@@ -0,0 +1,171 @@
+//! Commit log of the hart, in the format of Spike.
+//!
+//! When enabled with [`RVI::set_commit_log`], every retired instruction is written like `spike --log-commits` does:
+//! the privilege mode, the PC, the raw instruction, the value of the written integer, floating-point and CSR registers,
+//! the address of the loads and the address and value of the stores. Only the CSR written by a Zicsr instruction is logged,
+//! not the CSRs updated as a side effect. Writes to `x0` are not logged.
+//!
+//! ```text
+//! core   0: 3 0x0000000080000000 (0x00000297) x5  0x0000000080000000
+//! core   0: 3 0x0000000080000008 (0x0062a023) mem 0x0000000080001000 0x00000000
+//! ```
+//!
+//! With `disassembly`, each retired instruction is also preceded by a line with its disassembly and each trap by its cause,
+//! like with `spike -l`. Errors of the writer are ignored.
+
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::common::{csr::{get_csr_name, MHARTID}, get_instruction_length, isa::ISA, types::*};
+use crate::public::{ExecutionEnvironmentInterface, Traps};
+use crate::rvi::*;
+
+/// Memory access performed by an instruction. AMOs perform a load and a store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Access {
+    /// The virtual address.
+    pub addr: u64,
+    /// The size in bytes.
+    pub size: u64,
+    /// The loaded or stored value.
+    pub data: u64,
+    pub store: bool,
+}
+
+pub(super) struct CommitLog {
+    writer: Box<dyn Write + Send>,
+    disassembly: bool,
+}
+
+/// Formats `value` in hexadecimal on `bits` bits.
+fn hex(bits: usize, value: u64) -> String {
+    let value = if bits < 64 { value & ((1 << bits) - 1) } else { value };
+    format!("0x{:01$x}", value, bits / 4)
+}
+
+/// Name of the trap in Spike.
+fn trap_name(trap: Traps) -> &'static str {
+    match trap {
+        Traps::InstructionAddressMisaligned => "trap_instruction_address_misaligned",
+        Traps::InstructionAccessFault => "trap_instruction_access_fault",
+        Traps::IllegalInstruction => "trap_illegal_instruction",
+        Traps::Breakpoint => "trap_breakpoint",
+        Traps::LoadAddressMisaligned => "trap_load_address_misaligned",
+        Traps::LoadAccessFault => "trap_load_access_fault",
+        Traps::StoreAMOAddressMisaligned => "trap_store_address_misaligned",
+        Traps::StoreAMOAccessFault => "trap_store_access_fault",
+        Traps::EnvironmentCallFromUMode => "trap_user_ecall",
+        Traps::EnvironmentCallFromSMode => "trap_supervisor_ecall",
+        Traps::EnvironmentCallFromMMode => "trap_machine_ecall",
+        Traps::InstructionPageFault => "trap_instruction_page_fault",
+        Traps::LoadPageFault => "trap_load_page_fault",
+        Traps::StoreAMOPageFault => "trap_store_page_fault",
+        _ => "interrupt",
+    }
+}
+
+impl<U: Unsigned<S>, S: Signed<U>, EEI: ExecutionEnvironmentInterface<U>, const N: usize> RVI<U, S, EEI, N> {
+    /// Writes the commit log of the hart to `writer`, with the disassembly of the instructions if `disassembly` is true.
+    /// See the [module documentation](self).
+    /// Enables the recording of the memory accesses, see [`accesses`](Self::accesses).
+    pub fn set_commit_log<W: Write + Send + 'static>(&mut self, writer: W, disassembly: bool) {
+        self.commit_log = Some(CommitLog { writer: Box::new(writer), disassembly });
+        self.record_accesses(true);
+    }
+
+    /// Disables the commit log and returns its writer, flushed.
+    pub fn take_commit_log(&mut self) -> Option<Box<dyn Write + Send>> {
+        let mut writer = self.commit_log.take()?.writer;
+        writer.flush().ok();
+        Some(writer)
+    }
+
+    /// Enables or disables the recording of the memory accesses of each step.
+    pub fn record_accesses(&mut self, enable: bool) {
+        self.accesses = if enable { Some(Vec::new()) } else { None };
+    }
+
+    /// Returns the memory accesses performed by the last step, loads first, if their recording is enabled.
+    pub fn accesses(&self) -> &[Access] {
+        self.accesses.as_deref().unwrap_or(&[])
+    }
+
+    /// Records a memory access of the current step if the recording is enabled.
+    #[inline(always)]
+    pub(super) fn log_access(&mut self, vaddr: U, size: u64, data: u64, store: bool) {
+        if let Some(accesses) = &mut self.accesses {
+            accesses.push(Access { addr: vaddr.as_u64(), size, data, store });
+        }
+    }
+
+    /// Writes the given result of a step executed in `mode`.
+    pub(super) fn log_step(&mut self, mode: PrivilegeMode, result: &StepResult<U, S>) {
+        let disassembly = match &self.commit_log {
+            Some(log) => log.disassembly,
+            None => return,
+        };
+        let id = self.read_csr(MHARTID).map_or(0, |id| id.as_u64());
+        let mut line = String::new();
+
+        match *result {
+            StepResult::Retired { pc, inst } => {
+                if disassembly {
+                    self.log_disassembly(&mut line, id);
+                }
+                let length = get_instruction_length(inst.opcode as u16) as usize * 8;
+                write!(line, "core{:4}: {} {} ({})", id, mode as u8, hex(Self::XLEN, pc.as_u64()), hex(length, inst.opcode as u64)).unwrap();
+                if inst.writes_x_register() && inst.rd != 0 {
+                    write!(line, " x{:<2} {}", inst.rd, hex(Self::XLEN, self.x[inst.rd as usize].as_u64())).unwrap();
+                }
+                if inst.writes_f_register() {
+                    let flen = if self.has_extension('D') { 64 } else { 32 };
+                    write!(line, " f{:<2} {}", inst.rd, hex(flen, self.f[inst.rd as usize])).unwrap();
+                }
+                if let Some(csr) = Self::written_csr(inst) {
+                    let value = self.read_csr(csr).map_or(0, |value| value.as_u64());
+                    write!(line, " c{}_{} {}", csr, get_csr_name(csr), hex(Self::XLEN, value)).unwrap();
+                }
+                for access in self.accesses().iter().filter(|access| !access.store) {
+                    write!(line, " mem {}", hex(Self::XLEN, access.addr)).unwrap();
+                }
+                for access in self.accesses().iter().filter(|access| access.store) {
+                    write!(line, " mem {} {}", hex(Self::XLEN, access.addr), hex(access.size as usize * 8, access.data)).unwrap();
+                }
+                line.push('\n');
+            },
+            StepResult::Trap { cause, tval, pc } if disassembly => {
+                if cause.is_interrupt() {
+                    writeln!(line, "core {:3}: interrupt #{}, epc {}", id, cause.exception_code(), hex(64, pc.as_s().as_u64())).unwrap();
+                } else {
+                    // Instruction fetch faults happen before the instruction is known.
+                    if !matches!(cause, Traps::InstructionAccessFault | Traps::InstructionPageFault) {
+                        self.log_disassembly(&mut line, id);
+                    }
+                    writeln!(line, "core {:3}: exception {}, epc {}", id, trap_name(cause), hex(64, pc.as_s().as_u64())).unwrap();
+                    if tval != 0u32.into() {
+                        writeln!(line, "core {:3}:           tval {}", id, hex(64, tval.as_s().as_u64())).unwrap();
+                    }
+                }
+            },
+            StepResult::Trap { .. } => (),
+        }
+
+        self.commit_log.as_mut().unwrap().writer.write_all(line.as_bytes()).ok();
+    }
+
+    /// Writes the line of `spike -l` of the current instruction, with its address sign-extended to 64 bits.
+    fn log_disassembly(&self, line: &mut String, id: u64) {
+        let disassembly = self.disassemble_instruction(self.inst);
+        writeln!(line, "core {:3}: {} ({}) {}", id, hex(64, self.inst.pc.as_s().as_u64()), hex(32, self.inst.opcode as u64), disassembly).unwrap();
+    }
+
+    /// Returns the CSR written by the given Zicsr instruction.
+    fn written_csr(inst: Instruction<U, S>) -> Option<u16> {
+        match inst.inst {
+            ISA::CSRRW | ISA::CSRRWI => (),
+            ISA::CSRRS | ISA::CSRRSI | ISA::CSRRC | ISA::CSRRCI if inst.rs1 != 0 => (),
+            _ => return None,
+        }
+        Some(inst.imm.as_u16() & 0xFFF)
+    }
+}
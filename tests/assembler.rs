@@ -1,3 +1,4 @@
+use dyriscvic::common::decoder::expand_compressed;
 use dyriscvic::rvi::assembler::*;
 
 #[test]
@@ -208,3 +209,291 @@ fn assembler_i64() {
     let subw_ = 0b0100000_11101_11100_000_11011_0111011u32;
     assert_eq!(subw, subw_, "SUBW {:X} {:X}", subw, subw_);
 }
+
+#[test]
+fn assembler_m32() {
+    let div = DIV(5, 19, 28);
+    let div_ = 0b0000001_11100_10011_100_00101_0110011u32;
+    assert_eq!(div, div_, "DIV {:X} {:X}", div, div_);
+
+    let divu = DIVU(26, 25, 3);
+    let divu_ = 0b0000001_00011_11001_101_11010_0110011u32;
+    assert_eq!(divu, divu_, "DIVU {:X} {:X}", divu, divu_);
+
+    let mul = MUL(9, 4, 16);
+    let mul_ = 0b0000001_10000_00100_000_01001_0110011u32;
+    assert_eq!(mul, mul_, "MUL {:X} {:X}", mul, mul_);
+
+    let mulh = MULH(25, 15, 16);
+    let mulh_ = 0b0000001_10000_01111_001_11001_0110011u32;
+    assert_eq!(mulh, mulh_, "MULH {:X} {:X}", mulh, mulh_);
+
+    let mulhsu = MULHSU(21, 13, 26);
+    let mulhsu_ = 0b0000001_11010_01101_010_10101_0110011u32;
+    assert_eq!(mulhsu, mulhsu_, "MULHSU {:X} {:X}", mulhsu, mulhsu_);
+
+    let mulhu = MULHU(7, 4, 16);
+    let mulhu_ = 0b0000001_10000_00100_011_00111_0110011u32;
+    assert_eq!(mulhu, mulhu_, "MULHU {:X} {:X}", mulhu, mulhu_);
+
+    let rem = REM(1, 29, 27);
+    let rem_ = 0b0000001_11011_11101_110_00001_0110011u32;
+    assert_eq!(rem, rem_, "REM {:X} {:X}", rem, rem_);
+
+    let remu = REMU(13, 14, 20);
+    let remu_ = 0b0000001_10100_01110_111_01101_0110011u32;
+    assert_eq!(remu, remu_, "REMU {:X} {:X}", remu, remu_);
+}
+
+#[test]
+fn assembler_m64() {
+    let divuw = DIVUW(25, 25, 1);
+    let divuw_ = 0b0000001_00001_11001_101_11001_0111011u32;
+    assert_eq!(divuw, divuw_, "DIVUW {:X} {:X}", divuw, divuw_);
+
+    let divw = DIVW(23, 15, 9);
+    let divw_ = 0b0000001_01001_01111_100_10111_0111011u32;
+    assert_eq!(divw, divw_, "DIVW {:X} {:X}", divw, divw_);
+
+    let mulw = MULW(24, 26, 8);
+    let mulw_ = 0b0000001_01000_11010_000_11000_0111011u32;
+    assert_eq!(mulw, mulw_, "MULW {:X} {:X}", mulw, mulw_);
+
+    let remuw = REMUW(19, 31, 4);
+    let remuw_ = 0b0000001_00100_11111_111_10011_0111011u32;
+    assert_eq!(remuw, remuw_, "REMUW {:X} {:X}", remuw, remuw_);
+
+    let remw = REMW(29, 11, 1);
+    let remw_ = 0b0000001_00001_01011_110_11101_0111011u32;
+    assert_eq!(remw, remw_, "REMW {:X} {:X}", remw, remw_);
+}
+
+#[test]
+fn assembler_a32() {
+    let amoadd = AMOADD_W(5, 19, 28, false, false);
+    let amoadd_ = 0b00000_0_0_11100_10011_010_00101_0101111u32;
+    assert_eq!(amoadd, amoadd_, "AMOADD.W {:X} {:X}", amoadd, amoadd_);
+
+    let amoswap = AMOSWAP_W(26, 25, 3, true, true);
+    let amoswap_ = 0b00001_1_1_00011_11001_010_11010_0101111u32;
+    assert_eq!(amoswap, amoswap_, "AMOSWAP.W {:X} {:X}", amoswap, amoswap_);
+
+    let amomaxu = AMOMAXU_W(9, 4, 16, true, false);
+    let amomaxu_ = 0b11100_1_0_10000_00100_010_01001_0101111u32;
+    assert_eq!(amomaxu, amomaxu_, "AMOMAXU.W {:X} {:X}", amomaxu, amomaxu_);
+
+    let lr = LR_W(1, 2, false, false);
+    let lr_ = 0b00010_0_0_00000_00010_010_00001_0101111u32;
+    assert_eq!(lr, lr_, "LR.W {:X} {:X}", lr, lr_);
+
+    let sc = SC_W(3, 4, 5, false, true);
+    let sc_ = 0b00011_0_1_00101_00100_010_00011_0101111u32;
+    assert_eq!(sc, sc_, "SC.W {:X} {:X}", sc, sc_);
+}
+
+#[test]
+fn assembler_a64() {
+    let amoand = AMOAND_D(31, 30, 29, false, false);
+    let amoand_ = 0b01100_0_0_11101_11110_011_11111_0101111u32;
+    assert_eq!(amoand, amoand_, "AMOAND.D {:X} {:X}", amoand, amoand_);
+
+    let amomin = AMOMIN_D(7, 8, 9, false, false);
+    let amomin_ = 0b10000_0_0_01001_01000_011_00111_0101111u32;
+    assert_eq!(amomin, amomin_, "AMOMIN.D {:X} {:X}", amomin, amomin_);
+
+    let lr = LR_D(10, 11, true, false);
+    let lr_ = 0b00010_1_0_00000_01011_011_01010_0101111u32;
+    assert_eq!(lr, lr_, "LR.D {:X} {:X}", lr, lr_);
+
+    let sc = SC_D(12, 13, 14, false, false);
+    let sc_ = 0b00011_0_0_01110_01101_011_01100_0101111u32;
+    assert_eq!(sc, sc_, "SC.D {:X} {:X}", sc, sc_);
+}
+
+#[test]
+fn assembler_f32() {
+    let fadd = FADD_S(1, 2, 3, 7);
+    let fadd_ = 0b0000000_00011_00010_111_00001_1010011u32;
+    assert_eq!(fadd, fadd_, "FADD.S {:X} {:X}", fadd, fadd_);
+
+    let fmadd = FMADD_S(4, 5, 6, 7, 0);
+    let fmadd_ = 0b00111_00_00110_00101_000_00100_1000011u32;
+    assert_eq!(fmadd, fmadd_, "FMADD.S {:X} {:X}", fmadd, fmadd_);
+
+    let fsw = FSW(8, 9, 0x7FC);
+    let fsw_ = 0b0111111_01000_01001_010_11100_0100111u32;
+    assert_eq!(fsw, fsw_, "FSW {:X} {:X}", fsw, fsw_);
+
+    let fcvt = FCVT_WU_S(10, 11, 1);
+    let fcvt_ = 0b1100000_00001_01011_001_01010_1010011u32;
+    assert_eq!(fcvt, fcvt_, "FCVT.WU.S {:X} {:X}", fcvt, fcvt_);
+
+    let fmv = FMV_X_W(12, 13);
+    let fmv_ = 0b1110000_00000_01101_000_01100_1010011u32;
+    assert_eq!(fmv, fmv_, "FMV.X.W {:X} {:X}", fmv, fmv_);
+
+    let fclass = FCLASS_S(14, 15);
+    let fclass_ = 0b1110000_00000_01111_001_01110_1010011u32;
+    assert_eq!(fclass, fclass_, "FCLASS.S {:X} {:X}", fclass, fclass_);
+
+    let fcvt = FCVT_S_LU(16, 17, 4);
+    let fcvt_ = 0b1101000_00011_10001_100_10000_1010011u32;
+    assert_eq!(fcvt, fcvt_, "FCVT.S.LU {:X} {:X}", fcvt, fcvt_);
+}
+
+#[test]
+fn assembler_d32() {
+    let fnmadd = FNMADD_D(1, 2, 3, 4, 2);
+    let fnmadd_ = 0b00100_01_00011_00010_010_00001_1001111u32;
+    assert_eq!(fnmadd, fnmadd_, "FNMADD.D {:X} {:X}", fnmadd, fnmadd_);
+
+    let fld = FLD(5, 6, 8);
+    let fld_ = 0b000000001000_00110_011_00101_0000111u32;
+    assert_eq!(fld, fld_, "FLD {:X} {:X}", fld, fld_);
+
+    let fcvt = FCVT_S_D(7, 8, 7);
+    let fcvt_ = 0b0100000_00001_01000_111_00111_1010011u32;
+    assert_eq!(fcvt, fcvt_, "FCVT.S.D {:X} {:X}", fcvt, fcvt_);
+
+    let fsgnjx = FSGNJX_D(11, 12, 13);
+    let fsgnjx_ = 0b0010001_01101_01100_010_01011_1010011u32;
+    assert_eq!(fsgnjx, fsgnjx_, "FSGNJX.D {:X} {:X}", fsgnjx, fsgnjx_);
+
+    let fmv = FMV_D_X(9, 10);
+    let fmv_ = 0b1111001_00000_01010_000_01001_1010011u32;
+    assert_eq!(fmv, fmv_, "FMV.D.X {:X} {:X}", fmv, fmv_);
+}
+
+#[test]
+fn assembler_c() {
+    let addi = C_ADDI(2, -16i32 as u32);
+    assert_eq!(addi, 0x1141, "C.ADDI {:X}", addi);
+
+    let addi16sp = C_ADDI16SP(-48i32 as u32);
+    assert_eq!(addi16sp, 0x7179, "C.ADDI16SP {:X}", addi16sp);
+
+    let addi4spn = C_ADDI4SPN(8, 48);
+    assert_eq!(addi4spn, 0x1800, "C.ADDI4SPN {:X}", addi4spn);
+
+    let sdsp = C_SDSP(1, 8);
+    assert_eq!(sdsp, 0xE406, "C.SDSP {:X}", sdsp);
+
+    let ldsp = C_LDSP(1, 8);
+    assert_eq!(ldsp, 0x60A2, "C.LDSP {:X}", ldsp);
+
+    let swsp = C_SWSP(1, 12);
+    assert_eq!(swsp, 0xC606, "C.SWSP {:X}", swsp);
+
+    let lwsp = C_LWSP(1, 12);
+    assert_eq!(lwsp, 0x40B2, "C.LWSP {:X}", lwsp);
+
+    let li = C_LI(15, 0);
+    assert_eq!(li, 0x4781, "C.LI {:X}", li);
+
+    let mv = C_MV(10, 15);
+    assert_eq!(mv, 0x853E, "C.MV {:X}", mv);
+
+    let jr = C_JR(1);
+    assert_eq!(jr, 0x8082, "C.JR {:X}", jr);
+
+    assert_eq!(C_NOP(), 0x0001);
+    assert_eq!(C_EBREAK(), 0x9002);
+}
+
+#[test]
+fn assembler_c_expansion() {
+    let rv32 = [
+        (C_JAL(-2048i32 as u32), JAL(1, -2048i32 as u32)),
+        (C_FLW(8, 15, 124), FLW(8, 15, 124)),
+        (C_FSW(9, 14, 64), FSW(9, 14, 64)),
+        (C_FLWSP(31, 252), FLW(31, 2, 252)),
+        (C_FSWSP(30, 4), FSW(30, 2, 4)),
+        (C_SRAI(8, 31), SRAI(8, 8, 31)),
+    ];
+    for (c, i) in rv32 {
+        assert_eq!(expand_compressed(c, 32), Some(i), "RV32 {:X} {:X}", c, i);
+    }
+
+    let rv64 = [
+        (C_ADD(31, 1), ADD(31, 31, 1)),
+        (C_ADDI(3, 31), ADDI(3, 3, 31)),
+        (C_ADDI16SP(496), ADDI(2, 2, 496)),
+        (C_ADDI4SPN(15, 1020), ADDI(15, 2, 1020)),
+        (C_ADDIW(4, -32i32 as u32), ADDIW(4, 4, -32i32 as u32)),
+        (C_ADDW(8, 9), ADDW(8, 8, 9)),
+        (C_AND(10, 11), AND(10, 10, 11)),
+        (C_ANDI(12, -1i32 as u32), ANDI(12, 12, -1i32 as u32)),
+        (C_BEQZ(13, -256i32 as u32), BEQ(13, 0, -256i32 as u32)),
+        (C_BNEZ(14, 254), BNE(14, 0, 254)),
+        (C_EBREAK(), EBREAK()),
+        (C_FLD(8, 9, 248), FLD(8, 9, 248)),
+        (C_FLDSP(5, 504), FLD(5, 2, 504)),
+        (C_FSD(15, 8, 136), FSD(15, 8, 136)),
+        (C_FSDSP(6, 456), FSD(6, 2, 456)),
+        (C_J(2046), JAL(0, 2046)),
+        (C_JALR(5), JALR(1, 5, 0)),
+        (C_JR(6), JALR(0, 6, 0)),
+        (C_LD(9, 10, 200), LD(9, 10, 200)),
+        (C_LDSP(7, 320), LD(7, 2, 320)),
+        (C_LI(8, -5i32 as u32), ADDI(8, 0, -5i32 as u32)),
+        (C_LUI(9, 0xFFFE_0000), LUI(9, 0xFFFE_0000)),
+        (C_LW(11, 12, 68), LW(11, 12, 68)),
+        (C_LWSP(10, 156), LW(10, 2, 156)),
+        (C_MV(11, 12), ADD(11, 0, 12)),
+        (C_NOP(), ADDI(0, 0, 0)),
+        (C_OR(13, 14), OR(13, 13, 14)),
+        (C_SD(15, 8, 8), SD(8, 15, 8)),
+        (C_SDSP(16, 256), SD(2, 16, 256)),
+        (C_SLLI(17, 63), SLLI(17, 17, 63)),
+        (C_SRAI(9, 40), SRAI(9, 9, 40)),
+        (C_SRLI(10, 33), SRLI(10, 10, 33)),
+        (C_SUB(11, 12), SUB(11, 11, 12)),
+        (C_SUBW(13, 14), SUBW(13, 13, 14)),
+        (C_SW(15, 8, 124), SW(15, 8, 124)),
+        (C_SWSP(18, 192), SW(18, 2, 192)),
+        (C_XOR(9, 10), XOR(9, 9, 10)),
+    ];
+    for (c, i) in rv64 {
+        assert_eq!(expand_compressed(c, 64), Some(i), "RV64 {:X} {:X}", c, i);
+    }
+}
+
+#[test]
+fn assembler_zicsr() {
+    let csrrc = CSRRC(1, 0x300, 2);
+    let csrrc_ = 0b001100000000_00010_011_00001_1110011u32;
+    assert_eq!(csrrc, csrrc_, "CSRRC {:X} {:X}", csrrc, csrrc_);
+
+    let csrrci = CSRRCI(3, 0xC00, 31);
+    let csrrci_ = 0b110000000000_11111_111_00011_1110011u32;
+    assert_eq!(csrrci, csrrci_, "CSRRCI {:X} {:X}", csrrci, csrrci_);
+
+    let csrrs = CSRRS(4, 0x340, 5);
+    let csrrs_ = 0b001101000000_00101_010_00100_1110011u32;
+    assert_eq!(csrrs, csrrs_, "CSRRS {:X} {:X}", csrrs, csrrs_);
+
+    let csrrsi = CSRRSI(6, 0xF14, 7);
+    let csrrsi_ = 0b111100010100_00111_110_00110_1110011u32;
+    assert_eq!(csrrsi, csrrsi_, "CSRRSI {:X} {:X}", csrrsi, csrrsi_);
+
+    let csrrw = CSRRW(0, 0x001, 9);
+    let csrrw_ = 0b000000000001_01001_001_00000_1110011u32;
+    assert_eq!(csrrw, csrrw_, "CSRRW {:X} {:X}", csrrw, csrrw_);
+
+    let csrrwi = CSRRWI(10, 0xB02, 16);
+    let csrrwi_ = 0b101100000010_10000_101_01010_1110011u32;
+    assert_eq!(csrrwi, csrrwi_, "CSRRWI {:X} {:X}", csrrwi, csrrwi_);
+}
+
+#[test]
+fn assembler_zifencei() {
+    assert_eq!(FENCE_I(), 0x0000_100F, "FENCE.I");
+}
+
+#[test]
+fn assembler_privileged() {
+    assert_eq!(MRET(), 0x3020_0073, "MRET");
+    assert_eq!(SFENCE_VMA(10, 11), 0x12B5_0073, "SFENCE_VMA");
+    assert_eq!(SRET(), 0x1020_0073, "SRET");
+    assert_eq!(WFI(), 0x1050_0073, "WFI");
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dyriscvic::bus::*;
+use dyriscvic::public::*;
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::config;
+
+/// Records the accesses and returns the offset on reads.
+#[derive(Default)]
+struct Recorder {
+    accesses: Vec<(char, u64, u64)>,
+}
+
+impl Device for Recorder {
+    fn read_8(&mut self, offset: u64) -> Result<u8, MemoryFault> {
+        self.accesses.push(('r', offset, 8));
+        Ok(offset as u8)
+    }
+
+    fn write_8(&mut self, offset: u64, data: u8) -> Result<(), MemoryFault> {
+        self.accesses.push(('w', offset, data as u64));
+        Ok(())
+    }
+
+    fn read_32(&mut self, offset: u64) -> Result<u32, MemoryFault> {
+        self.accesses.push(('r', offset, 32));
+        Ok(offset as u32 | 0x1000)
+    }
+
+    fn write_64(&mut self, offset: u64, data: u64) -> Result<(), MemoryFault> {
+        self.accesses.push(('W', offset, data));
+        Ok(())
+    }
+}
+
+#[test]
+fn bus_mapping() {
+    let mut bus = Bus::new();
+    bus.map_ram(0x1000, 0x1000).unwrap();
+    bus.map_rom(0x3000, vec![1, 2, 3, 4]).unwrap();
+    bus.map_device(0x2000, 0x100, Recorder::default()).unwrap();
+    bus.map_ram(0xFFFF_FFFF_FFFF_F000, 0x1000).unwrap();
+
+    assert_eq!(bus.map_ram(0x1FFF, 2), Err(MapError::Overlap { base: 0x1FFF, mapped: 0x1000 }));
+    assert_eq!(bus.map_ram(0x0, 0x1001), Err(MapError::Overlap { base: 0x0, mapped: 0x1000 }));
+    assert_eq!(bus.map_ram(0x2080, 0x10), Err(MapError::Overlap { base: 0x2080, mapped: 0x2000 }));
+    assert_eq!(bus.map_ram(0x4000, 0), Err(MapError::InvalidSize { base: 0x4000, size: 0 }));
+    assert_eq!(bus.map_rom(0xFFFF_FFFF_FFFF_FFFF, vec![0; 2]), Err(MapError::InvalidSize { base: 0xFFFF_FFFF_FFFF_FFFF, size: 2 }));
+    bus.map_ram(0x2100, 0x100).unwrap();
+
+    assert_eq!(bus.memory(0x3000, 4), Some(&[1, 2, 3, 4][..]));
+    assert_eq!(bus.memory(0x3001, 4), None);
+    assert_eq!(bus.memory(0x2000, 4), None);
+    assert!(bus.device::<Recorder>(0x2000).is_some());
+    assert!(bus.device::<Recorder>(0x1000).is_none());
+}
+
+#[test]
+fn bus_accesses() {
+    let mut bus = Bus::new();
+    bus.map_ram(0x1000, 0x10).unwrap();
+    bus.map_rom(0x2000, vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]).unwrap();
+    bus.map_device(0x3000, 0x100, Recorder::default()).unwrap();
+
+    MemoryAccess::<u32>::set_64(&mut bus, 0x1008u32, 0x0102_0304_0506_0708).unwrap();
+    MemoryAccess::<u32>::set_16(&mut bus, 0x1000u32, 0xABCD).unwrap();
+    MemoryAccess::<u64>::set_8(&mut bus, 0x1002u64, 0xEF).unwrap();
+    assert_eq!(bus.memory(0x1000, 4), Some(&[0xCD, 0xAB, 0xEF, 0][..]));
+    assert_eq!(MemoryAccess::<u64>::get_32(&mut bus, 0x100Cu64), Ok(0x0102_0304));
+    assert_eq!(MemoryAccess::<u32>::get_64(&mut bus, 0x2000u32), Ok(0x8877_6655_4433_2211));
+    assert_eq!(MemoryAccess::<u32>::get_16(&mut bus, 0x2006u32), Ok(0x8877));
+
+    assert_eq!(MemoryAccess::<u32>::set_8(&mut bus, 0x2000u32, 0), Err(MemoryFault::AccessFault));
+    assert_eq!(MemoryAccess::<u32>::get_8(&mut bus, 0x1010u32), Err(MemoryFault::AccessFault));
+    assert_eq!(MemoryAccess::<u32>::get_32(&mut bus, 0x100Eu32), Err(MemoryFault::AccessFault));
+    assert_eq!(MemoryAccess::<u64>::get_64(&mut bus, 0xFFFF_FFFF_FFFF_FFF8u64), Err(MemoryFault::AccessFault));
+
+    assert_eq!(MemoryAccess::<u32>::get_32(&mut bus, 0x3010u32), Ok(0x1010));
+    assert_eq!(MemoryAccess::<u32>::get_16(&mut bus, 0x3020u32), Ok(0x2120));
+    assert_eq!(MemoryAccess::<u32>::get_32(&mut bus, 0x3022u32), Err(MemoryFault::Misaligned));
+    MemoryAccess::<u32>::set_64(&mut bus, 0x3008u32, 0xDEAD_BEEF).unwrap();
+    MemoryAccess::<u32>::set_16(&mut bus, 0x3030u32, 0x1234).unwrap();
+    assert_eq!(MemoryAccess::<u32>::set_16(&mut bus, 0x30FFu32, 0), Err(MemoryFault::AccessFault));
+    assert_eq!(bus.device::<Recorder>(0x3000).unwrap().accesses, [
+        ('r', 0x10, 32),
+        ('r', 0x21, 8),
+        ('r', 0x20, 8),
+        ('W', 0x08, 0xDEAD_BEEF),
+        ('w', 0x30, 0x34),
+        ('w', 0x31, 0x12),
+    ]);
+}
+
+#[test]
+fn bus_hart() {
+    let traps = Rc::new(RefCell::new(Vec::new()));
+    let handler_traps = traps.clone();
+    let mut bus = Bus::new();
+    bus.map_ram(0x8000_0000, 0x100).unwrap();
+    bus.map_device(0x1_0000_0000, 0x10, Recorder::default()).unwrap();
+    bus.set_trap_handler(move |trap, tval, pc| handler_traps.borrow_mut().push((trap, tval, pc)));
+
+    let memory = bus.memory_mut(0x8000_0000, 18).unwrap();
+    memory[0..2].copy_from_slice(&C_LI(1, 1).to_le_bytes());
+    memory[2..6].copy_from_slice(&SLLI(1, 1, 32).to_le_bytes());
+    memory[6..10].copy_from_slice(&SD(1, 1, 8).to_le_bytes());
+    memory[10..14].copy_from_slice(&LW(2, 1, 4).to_le_bytes());
+    memory[14..18].copy_from_slice(&LW(3, 0, 0).to_le_bytes());
+
+    let mut rv64 = RV64I::new([0; 32], 0x8000_0000, config("rv64ic"), bus);
+    assert!(rv64.run(4).is_none());
+    assert_eq!(rv64.x[2], 0x1004);
+    assert!(matches!(rv64.single_step(), StepResult::Trap { cause: Traps::LoadAccessFault, tval: 0, pc: 0x8000_000E }));
+    assert_eq!(*traps.borrow(), [(Traps::LoadAccessFault, 0, 0x8000_000E)]);
+    assert_eq!(rv64.eei().device::<Recorder>(0x1_0000_0000).unwrap().accesses, [('W', 8, 0x1_0000_0000), ('r', 4, 32)]);
+}
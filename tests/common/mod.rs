@@ -0,0 +1,214 @@
+//! Fixtures shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::convert::TryInto;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use dyriscvic::bus::Bus;
+use dyriscvic::public::*;
+use dyriscvic::rvi::{MisalignedAccess, RVConfig};
+
+/// 64 KiB of memory which panics on traps.
+pub struct Memory {
+    pub memory: Vec<u8>,
+}
+
+impl Memory {
+    pub fn new(program: &[u32]) -> Self {
+        let mut memory = Self { memory: vec![0; 0x10000] };
+        memory.load(0, program);
+        memory
+    }
+
+    pub fn load(&mut self, addr: usize, program: &[u32]) {
+        for (i, inst) in program.iter().enumerate() {
+            self.memory[addr + i * 4..addr + i * 4 + 4].copy_from_slice(&inst.to_le_bytes());
+        }
+    }
+
+    pub fn load_64(&mut self, addr: usize, data: u64) {
+        self.memory[addr..addr + 8].copy_from_slice(&data.to_le_bytes());
+    }
+
+    fn bytes<const N: usize>(&mut self, addr: u64) -> Result<&mut [u8; N], MemoryFault> {
+        let addr = addr as usize;
+        self.memory.get_mut(addr..addr + N).map(|bytes| bytes.try_into().unwrap()).ok_or(MemoryFault::AccessFault)
+    }
+}
+
+impl<ADDR: Into<u64>> MemoryAccess<ADDR> for Memory {
+    fn get_8(&mut self, addr: ADDR) -> Result<u8, MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| u8::from_le_bytes(*bytes))
+    }
+
+    fn get_16(&mut self, addr: ADDR) -> Result<u16, MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| u16::from_le_bytes(*bytes))
+    }
+
+    fn get_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| u32::from_le_bytes(*bytes))
+    }
+
+    fn get_64(&mut self, addr: ADDR) -> Result<u64, MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| u64::from_le_bytes(*bytes))
+    }
+
+    fn set_8(&mut self, addr: ADDR, data: u8) -> Result<(), MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| *bytes = data.to_le_bytes())
+    }
+
+    fn set_16(&mut self, addr: ADDR, data: u16) -> Result<(), MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| *bytes = data.to_le_bytes())
+    }
+
+    fn set_32(&mut self, addr: ADDR, data: u32) -> Result<(), MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| *bytes = data.to_le_bytes())
+    }
+
+    fn set_64(&mut self, addr: ADDR, data: u64) -> Result<(), MemoryFault> {
+        self.bytes(addr.into()).map(|bytes| *bytes = data.to_le_bytes())
+    }
+
+    fn get_opcode_32(&mut self, addr: ADDR) -> Result<u32, MemoryFault> {
+        self.get_32(addr)
+    }
+}
+
+impl<ADDR: Into<u64>> ExecutionEnvironmentInterface<ADDR> for Memory {
+    fn trap(&mut self, trap: Traps, tval: ADDR, pc: ADDR) {
+        panic!("Unexpected trap {:?} at {:#x}, tval {:#x}", trap, pc.into(), tval.into());
+    }
+}
+
+/// Returns a bus with 4 KiB of RAM at address 0 containing the given program.
+pub fn bus(program: &[u32]) -> Bus {
+    let mut bus = Bus::new();
+    bus.map_ram(0, 0x1000).unwrap();
+    for (i, inst) in program.iter().enumerate() {
+        bus.memory_mut(4 * i as u64, 4).unwrap().copy_from_slice(&inst.to_le_bytes());
+    }
+    bus
+}
+
+/// Returns the configuration of an unprivileged hart with the given ISA string.
+pub fn config(ext: &str) -> RVConfig {
+    RVConfig {
+        ext: String::from(ext),
+        abi_name: true,
+        privileged: false,
+        pmp_regions: 0,
+        misaligned_access: MisalignedAccess::Emulate,
+    }
+}
+
+/// Builds a little-endian RISC-V executable with the given segments (address, data, memory size)
+/// and symbols (name, value, size, type).
+pub fn build_elf(elf64: bool, flags: u32, entry: u64, segments: &[(u64, &[u8], u64)], symbols: &[(&str, u64, u64, u8)]) -> Vec<u8> {
+    let word = if elf64 { 8 } else { 4 };
+    let ehsize = if elf64 { 64 } else { 52 };
+    let phentsize = if elf64 { 56 } else { 32 };
+    let shentsize = if elf64 { 64 } else { 40 };
+    let symsize = if elf64 { 24 } else { 16 };
+    let push = |elf: &mut Vec<u8>, value: u64, size: usize| elf.extend_from_slice(&value.to_le_bytes()[..size]);
+
+    let data_offset = ehsize + phentsize * segments.len();
+    let symtab_offset = data_offset + segments.iter().map(|(_, data, _)| data.len()).sum::<usize>();
+    let strtab_offset = symtab_offset + symsize * (symbols.len() + 1);
+    let mut strtab = vec![0u8];
+    let mut names = Vec::new();
+    for (name, ..) in symbols {
+        names.push(strtab.len() as u64);
+        strtab.extend_from_slice(name.as_bytes());
+        strtab.push(0);
+    }
+    let shoff = strtab_offset + strtab.len();
+
+    let mut elf = b"\x7FELF".to_vec();
+    elf.extend_from_slice(&[if elf64 { 2 } else { 1 }, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    push(&mut elf, 2, 2); // ET_EXEC
+    push(&mut elf, 243, 2);
+    push(&mut elf, 1, 4);
+    push(&mut elf, entry, word);
+    push(&mut elf, ehsize as u64, word);
+    push(&mut elf, shoff as u64, word);
+    push(&mut elf, flags as u64, 4);
+    for value in [ehsize, phentsize, segments.len(), shentsize, 3, 0] {
+        push(&mut elf, value as u64, 2);
+    }
+
+    let mut offset = data_offset as u64;
+    for (addr, data, memsz) in segments {
+        push(&mut elf, 1, 4);
+        if elf64 {
+            push(&mut elf, 7, 4);
+        }
+        for value in [offset, *addr, *addr, data.len() as u64, *memsz] {
+            push(&mut elf, value, word);
+        }
+        if !elf64 {
+            push(&mut elf, 7, 4);
+        }
+        push(&mut elf, 4, word);
+        offset += data.len() as u64;
+    }
+    for (_, data, _) in segments {
+        elf.extend_from_slice(data);
+    }
+
+    elf.extend(std::iter::repeat_n(0, symsize));
+    for ((_, value, size, kind), name) in symbols.iter().zip(names) {
+        push(&mut elf, name, 4);
+        if elf64 {
+            elf.extend_from_slice(&[0x10 | kind, 0, 1, 0]);
+            push(&mut elf, *value, 8);
+            push(&mut elf, *size, 8);
+        } else {
+            push(&mut elf, *value, 4);
+            push(&mut elf, *size, 4);
+            elf.extend_from_slice(&[0x10 | kind, 0, 1, 0]);
+        }
+    }
+    elf.extend_from_slice(&strtab);
+
+    elf.extend(std::iter::repeat_n(0, shentsize));
+    for (kind, offset, size, link, entsize) in [(2, symtab_offset, strtab_offset - symtab_offset, 2, symsize), (3, strtab_offset, strtab.len(), 0, 0)] {
+        push(&mut elf, 0, 4);
+        push(&mut elf, kind, 4);
+        for value in [0, 0, offset as u64, size as u64] {
+            push(&mut elf, value, word);
+        }
+        push(&mut elf, link, 4);
+        push(&mut elf, 0, 4);
+        push(&mut elf, 1, word);
+        push(&mut elf, entsize as u64, word);
+    }
+    elf
+}
+
+/// Writer shared with the hart, to read the output while the hart owns it.
+#[derive(Clone, Default)]
+pub struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    /// Returns a copy of the bytes written so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Returns the text written since the last call and clears it.
+    pub fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::Path;
+
+use dyriscvic::compliance::*;
+use dyriscvic::common::isa_string::ISAStringError;
+use dyriscvic::elf::{ElfError, ElfFile, EF_RISCV_RVE};
+
+mod common;
+use common::build_elf;
+
+/// Runs every test of `tests/compliance` and compares its signature with the reference. See `tests/compliance/README.md`.
+#[test]
+fn arch_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/compliance");
+    let mut tests: Vec<_> = fs::read_dir(&dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "elf"))
+        .collect();
+    tests.sort();
+    assert!(!tests.is_empty(), "No test in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for test in &tests {
+        let reference = fs::read_to_string(test.with_extension("reference_output"))
+            .unwrap_or_else(|e| panic!("No reference signature for {}: {}", test.display(), e));
+        let ext = fs::read_to_string(test.with_extension("isa")).ok();
+        let name = test.file_stem().unwrap().to_string_lossy();
+
+        match run_test(&fs::read(test).unwrap(), ext.as_deref().map(str::trim), 10_000_000) {
+            Ok(signature) if format_signature(&signature) == reference => (),
+            Ok(signature) => {
+                let signature = format_signature(&signature);
+                let line = signature.lines().zip(reference.lines()).position(|(a, b)| a != b)
+                    .unwrap_or_else(|| signature.lines().count().min(reference.lines().count()));
+                failures.push(format!("{}: signature differs from the reference at line {}", name, line + 1));
+            },
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+    }
+    assert!(failures.is_empty(), "{} of {} tests failed:\n{}", failures.len(), tests.len(), failures.join("\n"));
+}
+
+#[test]
+fn signature_format() {
+    assert_eq!(format_signature(&[0xDEAD_BEEF, 0x1, 0xFFFF_FFFE]), "deadbeef\n00000001\nfffffffe\n");
+    assert_eq!(format_signature(&[]), "");
+}
+
+#[test]
+fn sparse_segments() {
+    let elf = build_elf(true, 0, 0x8000_0000, &[(0x8000_0000, &[1, 2], 0x2000), (0x2_0000_0010, &[3], 1)], &[("tohost", 0x8000_1000, 8, 1)]);
+    let eei = load(&ElfFile::parse(&elf).unwrap()).unwrap();
+    assert_eq!(eei.memory.memory(0x8000_0000, 2), Some(&[1, 2][..]));
+    assert_eq!(eei.memory.memory(0x8000_2000, 1), None);
+    assert_eq!(eei.memory.memory(0x2_0000_0010, 1), Some(&[3][..]));
+    assert!(eei.memory.memory(0x2_0000_0000, 0x1000 + RAM_MARGIN).is_some());
+    assert_eq!(eei.memory.memory(0x2_0000_1000 + RAM_MARGIN, 1), None);
+
+    let elf = build_elf(true, 0, 0, &[(u64::MAX - 1, &[], 4)], &[("tohost", 0, 8, 1)]);
+    assert_eq!(load(&ElfFile::parse(&elf).unwrap()).err(), Some(ComplianceError::Elf(ElfError::AddressOutOfRange(u64::MAX - 1))));
+}
+
+#[test]
+fn invalid_isa() {
+    let symbols = [("tohost", 0x1000, 8, 1), ("begin_signature", 0x1008, 0, 1), ("end_signature", 0x1008, 0, 1)];
+    let elf = build_elf(false, EF_RISCV_RVE, 0, &[(0, &[0; 4], 0x1010)], &symbols);
+    let error = ISAStringError::IncompatibleBase { extension: String::from("D"), base: 'E' };
+    assert_eq!(run_test(&elf, Some("D"), 10), Err(ComplianceError::Elf(ElfError::InvalidISA(error))));
+}
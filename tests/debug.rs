@@ -0,0 +1,72 @@
+use dyriscvic::public::Traps;
+use dyriscvic::rvi::{*, assembler::*, debug::*};
+
+mod common;
+use common::{bus, config};
+
+#[test]
+fn breakpoints() {
+    let program = [ADDI(1, 1, 1), ADDI(2, 2, 1), JAL(0, -8i32 as u32)];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), bus(&program));
+
+    assert_eq!(rv32.run_until_stop(10), StopReason::InstructionLimit);
+    assert_eq!(rv32.x[1], 4);
+    assert!(rv32.add_breakpoint(4));
+    assert!(!rv32.add_breakpoint(4));
+    assert_eq!(rv32.run_until_stop(10), StopReason::Breakpoint { pc: 4 });
+    assert_eq!(rv32.pc, 4);
+    assert_eq!(rv32.x[2], 3);
+
+    // Resuming executes the instruction at the breakpoint.
+    assert_eq!(rv32.run_until_stop(10), StopReason::Breakpoint { pc: 4 });
+    assert_eq!(rv32.x[2], 4);
+    assert_eq!(rv32.run_until_stop(1), StopReason::InstructionLimit);
+    assert_eq!(rv32.pc, 8);
+    assert!(rv32.remove_breakpoint(4));
+    assert!(!rv32.remove_breakpoint(4));
+    assert_eq!(rv32.run_until_stop(9), StopReason::InstructionLimit);
+
+    let id = rv32.add_condition(|hart| hart.x[1] == 10);
+    let other = rv32.add_condition(|hart| hart.x[2] == 100);
+    assert_eq!(rv32.run_until_stop(100), StopReason::Condition { id, pc: 4 });
+    assert_eq!(rv32.x[1], 10);
+    // The condition is still true after the next instruction.
+    assert_eq!(rv32.run_until_stop(100), StopReason::Condition { id, pc: 8 });
+    assert!(rv32.remove_condition(id));
+    assert!(!rv32.remove_condition(id));
+    assert_eq!(rv32.run_until_stop(1000), StopReason::Condition { id: other, pc: 8 });
+    assert_eq!(rv32.x[2], 100);
+    rv32.clear_breakpoints();
+    assert_eq!(rv32.run_until_stop(100), StopReason::InstructionLimit);
+}
+
+#[test]
+fn watchpoints() {
+    let program = [
+        ADDI(1, 0, 0x100),
+        SW(1, 1, 0),
+        LW(2, 1, 4),
+        SH(1, 1, 0x23),
+        LB(3, 1, 0x24),
+        AMOADD_W(4, 1, 1, false, false),
+        0,
+    ];
+    let mut rv32 = RV32I::new([0; 32], 0, config("A"), bus(&program));
+    rv32.add_watchpoint(0x100, 4, WatchKind::Write);
+    rv32.add_watchpoint(0x104, 4, WatchKind::Read);
+    rv32.add_watchpoint(0x124, 1, WatchKind::Access);
+
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Write, addr: 0x100, pc: 4 });
+    assert_eq!(rv32.pc, 8);
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Read, addr: 0x104, pc: 8 });
+    // Misaligned store covering the access watchpoint.
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Access, addr: 0x123, pc: 12 });
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Access, addr: 0x124, pc: 16 });
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Write, addr: 0x100, pc: 20 });
+    assert_eq!(rv32.run_until_stop(10), StopReason::Trap { cause: Traps::IllegalInstruction, tval: 0, pc: 24 });
+
+    assert!(rv32.remove_watchpoint(0x100, 4, WatchKind::Write));
+    assert!(!rv32.remove_watchpoint(0x100, 4, WatchKind::Read));
+    rv32.pc = 0;
+    assert_eq!(rv32.run_until_stop(10), StopReason::Watchpoint { kind: WatchKind::Read, addr: 0x104, pc: 8 });
+}
@@ -2,6 +2,7 @@
 
 use dyriscvic::common::instruction::*;
 use dyriscvic::common::isa::*;
+use dyriscvic::rvi::assembler::*;
 
 fn type_r(inst: &Instruction64, isa: ISA, rs2: u8, rs1: u8, rd: u8) {
     assert_eq!(inst.inst, isa, "ISA {:?} {:?}", inst.inst, isa);
@@ -84,9 +85,9 @@ fn decode_i32() {
     let ecall_ = Instruction64::from_opcode_32(0, ecall);
     assert_eq!(ecall_.inst, ISA::ECALL);
 
-    let fence = 0b1010_0101_0110_11001_000_01111_0001111u32;
+    let fence = 0b0000_0101_0110_11001_000_01111_0001111u32;
     let fence_ = Instruction64::from_opcode_32(0, fence);
-    type_i(&fence_, ISA::FENCE, 0b1111_1010_0101_0110i16 as i64, 25, 15);
+    type_i(&fence_, ISA::FENCE, 0b0101_0110, 25, 15);
 
     let lui = 0b11101000101000111101_11011_0110111u32;
     let lui_ = Instruction64::from_opcode_32(0, lui);
@@ -255,3 +256,554 @@ fn decode_i64() {
     let subw_ = Instruction64::from_opcode_32(0, subw);
     type_r(&subw_, ISA::SUBW, 22, 17, 19);
 }
+
+#[test]
+fn decode_m32() {
+    let div = 0b0000001_10101_00001_100_00001_0110011u32;
+    let div_ = Instruction64::from_opcode_32(0, div);
+    type_r(&div_, ISA::DIV, 21, 1, 1);
+
+    let divu = 0b0000001_11111_00001_101_10010_0110011u32;
+    let divu_ = Instruction64::from_opcode_32(0, divu);
+    type_r(&divu_, ISA::DIVU, 31, 1, 18);
+
+    let mul = 0b0000001_10110_01101_000_11101_0110011u32;
+    let mul_ = Instruction64::from_opcode_32(0, mul);
+    type_r(&mul_, ISA::MUL, 22, 13, 29);
+
+    let mulh = 0b0000001_11000_01110_001_00111_0110011u32;
+    let mulh_ = Instruction64::from_opcode_32(0, mulh);
+    type_r(&mulh_, ISA::MULH, 24, 14, 7);
+
+    let mulhsu = 0b0000001_01000_10001_010_00001_0110011u32;
+    let mulhsu_ = Instruction64::from_opcode_32(0, mulhsu);
+    type_r(&mulhsu_, ISA::MULHSU, 8, 17, 1);
+
+    let mulhu = 0b0000001_11111_01111_011_11001_0110011u32;
+    let mulhu_ = Instruction64::from_opcode_32(0, mulhu);
+    type_r(&mulhu_, ISA::MULHU, 31, 15, 25);
+
+    let rem = 0b0000001_01000_10010_110_10000_0110011u32;
+    let rem_ = Instruction64::from_opcode_32(0, rem);
+    type_r(&rem_, ISA::REM, 8, 18, 16);
+
+    let remu = 0b0000001_10110_01000_111_01100_0110011u32;
+    let remu_ = Instruction64::from_opcode_32(0, remu);
+    type_r(&remu_, ISA::REMU, 22, 8, 12);
+}
+
+#[test]
+fn decode_m64() {
+    let divuw = 0b0000001_01111_11001_101_01000_0111011u32;
+    let divuw_ = Instruction64::from_opcode_32(0, divuw);
+    type_r(&divuw_, ISA::DIVUW, 15, 25, 8);
+
+    let divw = 0b0000001_11110_01010_100_11111_0111011u32;
+    let divw_ = Instruction64::from_opcode_32(0, divw);
+    type_r(&divw_, ISA::DIVW, 30, 10, 31);
+
+    let mulw = 0b0000001_11011_01110_000_00001_0111011u32;
+    let mulw_ = Instruction64::from_opcode_32(0, mulw);
+    type_r(&mulw_, ISA::MULW, 27, 14, 1);
+
+    let remuw = 0b0000001_11110_10010_111_11110_0111011u32;
+    let remuw_ = Instruction64::from_opcode_32(0, remuw);
+    type_r(&remuw_, ISA::REMUW, 30, 18, 30);
+
+    let remw = 0b0000001_00110_00100_110_10101_0111011u32;
+    let remw_ = Instruction64::from_opcode_32(0, remw);
+    type_r(&remw_, ISA::REMW, 6, 4, 21);
+}
+
+#[test]
+fn decode_a32() {
+    let amoxor = 0b00100_1_1_10101_00001_010_00001_0101111u32;
+    let amoxor_ = Instruction64::from_opcode_32(0, amoxor);
+    type_r(&amoxor_, ISA::AMOXOR_W, 21, 1, 1);
+
+    let amomin = 0b10000_0_0_11111_00001_010_10010_0101111u32;
+    let amomin_ = Instruction64::from_opcode_32(0, amomin);
+    type_r(&amomin_, ISA::AMOMIN_W, 31, 1, 18);
+
+    let lr = 0b00010_0_0_00000_01101_010_11101_0101111u32;
+    let lr_ = Instruction64::from_opcode_32(0, lr);
+    type_r(&lr_, ISA::LR_W, 0, 13, 29);
+
+    let lr = 0b00010_0_0_00001_01101_010_11101_0101111u32;
+    let lr_ = Instruction64::from_opcode_32(0, lr);
+    assert_eq!(lr_.inst, ISA::UNKNOWN);
+
+    let unknown = 0b00101_0_0_00001_01101_010_11101_0101111u32;
+    let unknown_ = Instruction64::from_opcode_32(0, unknown);
+    assert_eq!(unknown_.inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_a64() {
+    let amoor = 0b01000_0_0_11000_01110_011_00111_0101111u32;
+    let amoor_ = Instruction64::from_opcode_32(0, amoor);
+    type_r(&amoor_, ISA::AMOOR_D, 24, 14, 7);
+
+    let amomaxu = 0b11100_0_1_00010_00011_011_00100_0101111u32;
+    let amomaxu_ = Instruction64::from_opcode_32(0, amomaxu);
+    type_r(&amomaxu_, ISA::AMOMAXU_D, 2, 3, 4);
+
+    let sc = 0b00011_0_0_00101_00110_011_00111_0101111u32;
+    let sc_ = Instruction64::from_opcode_32(0, sc);
+    type_r(&sc_, ISA::SC_D, 5, 6, 7);
+
+    let unknown = 0b00011_0_0_00101_00110_100_00111_0101111u32;
+    let unknown_ = Instruction64::from_opcode_32(0, unknown);
+    assert_eq!(unknown_.inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_f32() {
+    let fadd = 0b0000000_00011_00010_111_00001_1010011u32;
+    let fadd_ = Instruction64::from_opcode_32(0, fadd);
+    type_r(&fadd_, ISA::FADD_S, 3, 2, 1);
+
+    let fmadd = 0b00111_00_00110_00101_000_00100_1000011u32;
+    let fmadd_ = Instruction64::from_opcode_32(0, fmadd);
+    type_r(&fmadd_, ISA::FMADD_S, 6, 5, 4);
+    assert_eq!(fmadd_.rs3, 7);
+
+    let flw = 0b111111111100_01001_010_01000_0000111u32;
+    let flw_ = Instruction64::from_opcode_32(0, flw);
+    type_i(&flw_, ISA::FLW, -4, 9, 8);
+
+    let fsw = 0b0111111_01000_01001_010_11100_0100111u32;
+    let fsw_ = Instruction64::from_opcode_32(0, fsw);
+    type_s_b(&fsw_, ISA::FSW, 0x7FC, 8, 9);
+
+    let fle = 0b1010000_01101_01100_000_01011_1010011u32;
+    let fle_ = Instruction64::from_opcode_32(0, fle);
+    type_r(&fle_, ISA::FLE_S, 13, 12, 11);
+
+    let fclass = 0b1110000_00000_01111_001_01110_1010011u32;
+    let fclass_ = Instruction64::from_opcode_32(0, fclass);
+    type_r(&fclass_, ISA::FCLASS_S, 0, 15, 14);
+
+    let fsqrt = 0b0101100_00001_01111_000_01110_1010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fsqrt).inst, ISA::UNKNOWN);
+
+    let fadd_h = 0b0000010_00011_00010_111_00001_1010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fadd_h).inst, ISA::UNKNOWN);
+
+    let fmadd_q = 0b00111_11_00110_00101_000_00100_1000011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fmadd_q).inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_f64() {
+    let fcvt = 0b1101000_00011_10001_100_10000_1010011u32;
+    let fcvt_ = Instruction64::from_opcode_32(0, fcvt);
+    type_r(&fcvt_, ISA::FCVT_S_LU, 3, 17, 16);
+
+    let fcvt = 0b1100000_00010_00001_001_00010_1010011u32;
+    let fcvt_ = Instruction64::from_opcode_32(0, fcvt);
+    type_r(&fcvt_, ISA::FCVT_L_S, 2, 1, 2);
+
+    let fcvt = 0b1100000_00100_00001_001_00010_1010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fcvt).inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_d32() {
+    let fnmadd = 0b00100_01_00011_00010_010_00001_1001111u32;
+    let fnmadd_ = Instruction64::from_opcode_32(0, fnmadd);
+    type_r(&fnmadd_, ISA::FNMADD_D, 3, 2, 1);
+    assert_eq!(fnmadd_.rs3, 4);
+
+    let fld = 0b000000001000_00110_011_00101_0000111u32;
+    let fld_ = Instruction64::from_opcode_32(0, fld);
+    type_i(&fld_, ISA::FLD, 8, 6, 5);
+
+    let fcvt = 0b0100000_00001_01000_111_00111_1010011u32;
+    let fcvt_ = Instruction64::from_opcode_32(0, fcvt);
+    type_r(&fcvt_, ISA::FCVT_S_D, 1, 8, 7);
+
+    let fcvt = 0b0100001_00000_01000_111_00111_1010011u32;
+    let fcvt_ = Instruction64::from_opcode_32(0, fcvt);
+    type_r(&fcvt_, ISA::FCVT_D_S, 0, 8, 7);
+
+    let fsgnjx = 0b0010001_01101_01100_010_01011_1010011u32;
+    let fsgnjx_ = Instruction64::from_opcode_32(0, fsgnjx);
+    type_r(&fsgnjx_, ISA::FSGNJX_D, 13, 12, 11);
+
+    let fsgnj = 0b0010001_01101_01100_011_01011_1010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fsgnj).inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_d64() {
+    let fmv = 0b1111001_00000_01010_000_01001_1010011u32;
+    let fmv_ = Instruction64::from_opcode_32(0, fmv);
+    type_r(&fmv_, ISA::FMV_D_X, 0, 10, 9);
+
+    let fmv = 0b1110001_00000_01010_000_01001_1010011u32;
+    let fmv_ = Instruction64::from_opcode_32(0, fmv);
+    type_r(&fmv_, ISA::FMV_X_D, 0, 10, 9);
+
+    let fcvt = 0b1101001_00011_00001_111_00010_1010011u32;
+    let fcvt_ = Instruction64::from_opcode_32(0, fcvt);
+    type_r(&fcvt_, ISA::FCVT_D_LU, 3, 1, 2);
+}
+
+#[test]
+fn decode_c() {
+    let addi = Instruction64::from_opcode_16(0, 0x1141);
+    type_i(&addi, ISA::ADDI, -16, 2, 2);
+    assert_eq!(addi.opcode, 0x1141);
+
+    let sdsp = Instruction64::from_opcode_16(0, 0xE406);
+    type_s_b(&sdsp, ISA::SD, 8, 1, 2);
+
+    let jal = 0b001_0_1_00_1_1_1_000_0_01u16;
+    let jal_32 = Instruction32::from_opcode_16(0, jal);
+    assert_eq!(jal_32.inst, ISA::JAL);
+    assert_eq!(jal_32.rd, 1);
+    assert_eq!(jal_32.imm, 1232);
+    let addiw_64 = Instruction64::from_opcode_16(0, jal);
+    type_i(&addiw_64, ISA::ADDIW, 16, 19, 19);
+
+    let flw = 0b011_001_111_10_010_00u16;
+    assert_eq!(Instruction32::from_opcode_16(0, flw).inst, ISA::FLW);
+    assert_eq!(Instruction64::from_opcode_16(0, flw).inst, ISA::LD);
+
+    let fswsp = 0b111_000100_00011_10u16;
+    assert_eq!(Instruction32::from_opcode_16(0, fswsp).inst, ISA::FSW);
+    assert_eq!(Instruction64::from_opcode_16(0, fswsp).inst, ISA::SD);
+
+    let subw = 0b100_1_11_000_00_001_01u16;
+    assert_eq!(Instruction32::from_opcode_16(0, subw).inst, ISA::UNKNOWN);
+    type_r(&Instruction64::from_opcode_16(0, subw), ISA::SUBW, 9, 8, 8);
+
+    let slli32 = 0b000_1_00001_00000_10u16;
+    assert_eq!(Instruction32::from_opcode_16(0, slli32).inst, ISA::UNKNOWN);
+    type_i(&Instruction64::from_opcode_16(0, slli32), ISA::SLLI, 32, 1, 1);
+
+    let reserved = [
+        0x0000, // illegal instruction
+        0b000_00000000_010_00, // C.ADDI4SPN with nzuimm = 0
+        0b100_000_000_00_000_00, // reserved quadrant 0 opcode
+        0b011_0_00010_00000_01, // C.ADDI16SP with nzimm = 0
+        0b011_0_00011_00000_01, // C.LUI with nzimm = 0
+        0b010_0_00000_00000_10, // C.LWSP with rd = 0
+        0b100_0_00000_00000_10, // C.JR with rs1 = 0
+        0b100_1_11_000_10_000_01, // reserved CA
+    ];
+    for opcode in reserved {
+        assert_eq!(Instruction64::from_opcode_16(0, opcode).inst, ISA::UNKNOWN, "{:016b}", opcode);
+    }
+}
+
+#[test]
+fn decode_zicsr() {
+    let csrrc = 0b001100000000_00010_011_00001_1110011u32;
+    let csrrc_ = Instruction64::from_opcode_32(0, csrrc);
+    type_i(&csrrc_, ISA::CSRRC, 0x300, 2, 1);
+
+    let csrrci = 0b110000000000_11111_111_00011_1110011u32;
+    let csrrci_ = Instruction64::from_opcode_32(0, csrrci);
+    type_i(&csrrci_, ISA::CSRRCI, 0xC00, 31, 3);
+
+    let csrrs = 0b001101000000_00101_010_00100_1110011u32;
+    let csrrs_ = Instruction64::from_opcode_32(0, csrrs);
+    type_i(&csrrs_, ISA::CSRRS, 0x340, 5, 4);
+
+    let csrrsi = 0b111100010100_00111_110_00110_1110011u32;
+    let csrrsi_ = Instruction64::from_opcode_32(0, csrrsi);
+    type_i(&csrrsi_, ISA::CSRRSI, 0xF14, 7, 6);
+
+    let csrrw = 0b000000000001_01001_001_00000_1110011u32;
+    let csrrw_ = Instruction64::from_opcode_32(0, csrrw);
+    type_i(&csrrw_, ISA::CSRRW, 0x001, 9, 0);
+
+    let csrrwi = 0b101100000010_10000_101_01010_1110011u32;
+    let csrrwi_ = Instruction64::from_opcode_32(0, csrrwi);
+    type_i(&csrrwi_, ISA::CSRRWI, 0xB02, 16, 10);
+
+    let reserved = 0b000000000000_00000_100_00000_1110011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, reserved).inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_zifencei() {
+    let fence_i = 0b000000000000_00000_001_00000_0001111u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fence_i).inst, ISA::FENCE_I);
+
+    let reserved = 0b000000000000_00000_010_00000_0001111u32;
+    assert_eq!(Instruction64::from_opcode_32(0, reserved).inst, ISA::UNKNOWN);
+}
+
+#[test]
+fn decode_hints() {
+    let hint = |opcode: u32| Instruction64::from_opcode_32(0, opcode).is_hint();
+    let compressed_hint = |opcode: u16| Instruction64::from_opcode_16(0, opcode).is_hint();
+
+    for opcode in [LUI(0, 0x1000), AUIPC(0, 0), ADDI(0, 1, 0), ADDI(0, 0, 5), SLTIU(0, 3, 1), SRAI(0, 1, 3),
+                   ADD(0, 0, 2), SUB(0, 1, 2), SLTU(0, 1, 2), ADDIW(0, 1, 1), SRAW(0, 1, 2), FENCE(0, 0, 0, 0xF, 0), 0x0100_000F] {
+        assert!(hint(opcode), "{:#010x}", opcode);
+    }
+    for opcode in [ADDI(0, 0, 0), ADDI(1, 0, 5), LW(0, 1, 0), JAL(0, 8), JALR(0, 1, 0), FENCE(0, 0, 0xF, 0xF, 0), ECALL(), CSRRS(0, 0xC00, 0)] {
+        assert!(!hint(opcode), "{:#010x}", opcode);
+    }
+
+    for opcode in [C_ADDI(0, 3), C_ADDI(8, 0), C_LI(0, 1), C_LUI(0, 0x1000), C_MV(0, 8), C_ADD(0, 8), C_SLLI(8, 0), C_SLLI(0, 1), C_SRLI(8, 0), C_SRAI(8, 0)] {
+        assert!(compressed_hint(opcode), "{:#06x}", opcode);
+    }
+    for opcode in [C_NOP(), C_ADDI(8, 3), C_SLLI(8, 1), C_SRAI(8, 1), C_ANDI(8, 0), C_LW(8, 9, 0)] {
+        assert!(!compressed_hint(opcode), "{:#06x}", opcode);
+    }
+}
+
+#[test]
+fn decode_privileged() {
+    let mret = 0b001100000010_00000_000_00000_1110011u32;
+    let mret_ = Instruction64::from_opcode_32(0, mret);
+    assert_eq!(mret_.inst, ISA::MRET);
+
+    let sfence_vma = 0b0001001_01011_01010_000_00000_1110011u32;
+    let sfence_vma_ = Instruction64::from_opcode_32(0, sfence_vma);
+    assert_eq!(sfence_vma_.inst, ISA::SFENCE_VMA);
+    assert_eq!(sfence_vma_.rs1, 10);
+    assert_eq!(sfence_vma_.rs2, 11);
+
+    let sret = 0b000100000010_00000_000_00000_1110011u32;
+    let sret_ = Instruction64::from_opcode_32(0, sret);
+    assert_eq!(sret_.inst, ISA::SRET);
+
+    let wfi = 0b000100000101_00000_000_00000_1110011u32;
+    let wfi_ = Instruction64::from_opcode_32(0, wfi);
+    assert_eq!(wfi_.inst, ISA::WFI);
+}
+
+/// Reference encodings as (instruction, mask, match, defined on RV32), from the riscv-opcodes repository.
+/// Every instruction is defined on RV64, and SLLI, SRLI and SRAI have a 5-bit shift amount on RV32.
+const REFERENCE: [(ISA, u32, u32, bool); 161] = [
+    (ISA::LUI, 0x0000_007F, 0x0000_0037, true),
+    (ISA::AUIPC, 0x0000_007F, 0x0000_0017, true),
+    (ISA::JAL, 0x0000_007F, 0x0000_006F, true),
+    (ISA::JALR, 0x0000_707F, 0x0000_0067, true),
+    (ISA::BEQ, 0x0000_707F, 0x0000_0063, true),
+    (ISA::BNE, 0x0000_707F, 0x0000_1063, true),
+    (ISA::BLT, 0x0000_707F, 0x0000_4063, true),
+    (ISA::BGE, 0x0000_707F, 0x0000_5063, true),
+    (ISA::BLTU, 0x0000_707F, 0x0000_6063, true),
+    (ISA::BGEU, 0x0000_707F, 0x0000_7063, true),
+    (ISA::LB, 0x0000_707F, 0x0000_0003, true),
+    (ISA::LH, 0x0000_707F, 0x0000_1003, true),
+    (ISA::LW, 0x0000_707F, 0x0000_2003, true),
+    (ISA::LBU, 0x0000_707F, 0x0000_4003, true),
+    (ISA::LHU, 0x0000_707F, 0x0000_5003, true),
+    (ISA::SB, 0x0000_707F, 0x0000_0023, true),
+    (ISA::SH, 0x0000_707F, 0x0000_1023, true),
+    (ISA::SW, 0x0000_707F, 0x0000_2023, true),
+    (ISA::ADDI, 0x0000_707F, 0x0000_0013, true),
+    (ISA::SLTI, 0x0000_707F, 0x0000_2013, true),
+    (ISA::SLTIU, 0x0000_707F, 0x0000_3013, true),
+    (ISA::XORI, 0x0000_707F, 0x0000_4013, true),
+    (ISA::ORI, 0x0000_707F, 0x0000_6013, true),
+    (ISA::ANDI, 0x0000_707F, 0x0000_7013, true),
+    (ISA::SLLI, 0xFC00_707F, 0x0000_1013, true),
+    (ISA::SRLI, 0xFC00_707F, 0x0000_5013, true),
+    (ISA::SRAI, 0xFC00_707F, 0x4000_5013, true),
+    (ISA::ADD, 0xFE00_707F, 0x0000_0033, true),
+    (ISA::SUB, 0xFE00_707F, 0x4000_0033, true),
+    (ISA::SLL, 0xFE00_707F, 0x0000_1033, true),
+    (ISA::SLT, 0xFE00_707F, 0x0000_2033, true),
+    (ISA::SLTU, 0xFE00_707F, 0x0000_3033, true),
+    (ISA::XOR, 0xFE00_707F, 0x0000_4033, true),
+    (ISA::SRL, 0xFE00_707F, 0x0000_5033, true),
+    (ISA::SRA, 0xFE00_707F, 0x4000_5033, true),
+    (ISA::OR, 0xFE00_707F, 0x0000_6033, true),
+    (ISA::AND, 0xFE00_707F, 0x0000_7033, true),
+    (ISA::FENCE, 0xF000_707F, 0x0000_000F, true),
+    (ISA::FENCE, 0xFFF0_707F, 0x8330_000F, true), // FENCE.TSO
+    (ISA::ECALL, 0xFFFF_FFFF, 0x0000_0073, true),
+    (ISA::EBREAK, 0xFFFF_FFFF, 0x0010_0073, true),
+
+    (ISA::LD, 0x0000_707F, 0x0000_3003, false),
+    (ISA::LWU, 0x0000_707F, 0x0000_6003, false),
+    (ISA::SD, 0x0000_707F, 0x0000_3023, false),
+    (ISA::ADDIW, 0x0000_707F, 0x0000_001B, false),
+    (ISA::SLLIW, 0xFE00_707F, 0x0000_101B, false),
+    (ISA::SRLIW, 0xFE00_707F, 0x0000_501B, false),
+    (ISA::SRAIW, 0xFE00_707F, 0x4000_501B, false),
+    (ISA::ADDW, 0xFE00_707F, 0x0000_003B, false),
+    (ISA::SUBW, 0xFE00_707F, 0x4000_003B, false),
+    (ISA::SLLW, 0xFE00_707F, 0x0000_103B, false),
+    (ISA::SRLW, 0xFE00_707F, 0x0000_503B, false),
+    (ISA::SRAW, 0xFE00_707F, 0x4000_503B, false),
+
+    (ISA::MUL, 0xFE00_707F, 0x0200_0033, true),
+    (ISA::MULH, 0xFE00_707F, 0x0200_1033, true),
+    (ISA::MULHSU, 0xFE00_707F, 0x0200_2033, true),
+    (ISA::MULHU, 0xFE00_707F, 0x0200_3033, true),
+    (ISA::DIV, 0xFE00_707F, 0x0200_4033, true),
+    (ISA::DIVU, 0xFE00_707F, 0x0200_5033, true),
+    (ISA::REM, 0xFE00_707F, 0x0200_6033, true),
+    (ISA::REMU, 0xFE00_707F, 0x0200_7033, true),
+    (ISA::MULW, 0xFE00_707F, 0x0200_003B, false),
+    (ISA::DIVW, 0xFE00_707F, 0x0200_403B, false),
+    (ISA::DIVUW, 0xFE00_707F, 0x0200_503B, false),
+    (ISA::REMW, 0xFE00_707F, 0x0200_603B, false),
+    (ISA::REMUW, 0xFE00_707F, 0x0200_703B, false),
+
+    (ISA::LR_W, 0xF9F0_707F, 0x1000_202F, true),
+    (ISA::SC_W, 0xF800_707F, 0x1800_202F, true),
+    (ISA::AMOSWAP_W, 0xF800_707F, 0x0800_202F, true),
+    (ISA::AMOADD_W, 0xF800_707F, 0x0000_202F, true),
+    (ISA::AMOXOR_W, 0xF800_707F, 0x2000_202F, true),
+    (ISA::AMOAND_W, 0xF800_707F, 0x6000_202F, true),
+    (ISA::AMOOR_W, 0xF800_707F, 0x4000_202F, true),
+    (ISA::AMOMIN_W, 0xF800_707F, 0x8000_202F, true),
+    (ISA::AMOMAX_W, 0xF800_707F, 0xA000_202F, true),
+    (ISA::AMOMINU_W, 0xF800_707F, 0xC000_202F, true),
+    (ISA::AMOMAXU_W, 0xF800_707F, 0xE000_202F, true),
+    (ISA::LR_D, 0xF9F0_707F, 0x1000_302F, false),
+    (ISA::SC_D, 0xF800_707F, 0x1800_302F, false),
+    (ISA::AMOSWAP_D, 0xF800_707F, 0x0800_302F, false),
+    (ISA::AMOADD_D, 0xF800_707F, 0x0000_302F, false),
+    (ISA::AMOXOR_D, 0xF800_707F, 0x2000_302F, false),
+    (ISA::AMOAND_D, 0xF800_707F, 0x6000_302F, false),
+    (ISA::AMOOR_D, 0xF800_707F, 0x4000_302F, false),
+    (ISA::AMOMIN_D, 0xF800_707F, 0x8000_302F, false),
+    (ISA::AMOMAX_D, 0xF800_707F, 0xA000_302F, false),
+    (ISA::AMOMINU_D, 0xF800_707F, 0xC000_302F, false),
+    (ISA::AMOMAXU_D, 0xF800_707F, 0xE000_302F, false),
+
+    (ISA::FLW, 0x0000_707F, 0x0000_2007, true),
+    (ISA::FSW, 0x0000_707F, 0x0000_2027, true),
+    (ISA::FMADD_S, 0x0600_007F, 0x0000_0043, true),
+    (ISA::FMSUB_S, 0x0600_007F, 0x0000_0047, true),
+    (ISA::FNMSUB_S, 0x0600_007F, 0x0000_004B, true),
+    (ISA::FNMADD_S, 0x0600_007F, 0x0000_004F, true),
+    (ISA::FADD_S, 0xFE00_007F, 0x0000_0053, true),
+    (ISA::FSUB_S, 0xFE00_007F, 0x0800_0053, true),
+    (ISA::FMUL_S, 0xFE00_007F, 0x1000_0053, true),
+    (ISA::FDIV_S, 0xFE00_007F, 0x1800_0053, true),
+    (ISA::FSQRT_S, 0xFFF0_007F, 0x5800_0053, true),
+    (ISA::FSGNJ_S, 0xFE00_707F, 0x2000_0053, true),
+    (ISA::FSGNJN_S, 0xFE00_707F, 0x2000_1053, true),
+    (ISA::FSGNJX_S, 0xFE00_707F, 0x2000_2053, true),
+    (ISA::FMIN_S, 0xFE00_707F, 0x2800_0053, true),
+    (ISA::FMAX_S, 0xFE00_707F, 0x2800_1053, true),
+    (ISA::FCVT_W_S, 0xFFF0_007F, 0xC000_0053, true),
+    (ISA::FCVT_WU_S, 0xFFF0_007F, 0xC010_0053, true),
+    (ISA::FMV_X_W, 0xFFF0_707F, 0xE000_0053, true),
+    (ISA::FEQ_S, 0xFE00_707F, 0xA000_2053, true),
+    (ISA::FLT_S, 0xFE00_707F, 0xA000_1053, true),
+    (ISA::FLE_S, 0xFE00_707F, 0xA000_0053, true),
+    (ISA::FCLASS_S, 0xFFF0_707F, 0xE000_1053, true),
+    (ISA::FCVT_S_W, 0xFFF0_007F, 0xD000_0053, true),
+    (ISA::FCVT_S_WU, 0xFFF0_007F, 0xD010_0053, true),
+    (ISA::FMV_W_X, 0xFFF0_707F, 0xF000_0053, true),
+    (ISA::FCVT_L_S, 0xFFF0_007F, 0xC020_0053, false),
+    (ISA::FCVT_LU_S, 0xFFF0_007F, 0xC030_0053, false),
+    (ISA::FCVT_S_L, 0xFFF0_007F, 0xD020_0053, false),
+    (ISA::FCVT_S_LU, 0xFFF0_007F, 0xD030_0053, false),
+
+    (ISA::FLD, 0x0000_707F, 0x0000_3007, true),
+    (ISA::FSD, 0x0000_707F, 0x0000_3027, true),
+    (ISA::FMADD_D, 0x0600_007F, 0x0200_0043, true),
+    (ISA::FMSUB_D, 0x0600_007F, 0x0200_0047, true),
+    (ISA::FNMSUB_D, 0x0600_007F, 0x0200_004B, true),
+    (ISA::FNMADD_D, 0x0600_007F, 0x0200_004F, true),
+    (ISA::FADD_D, 0xFE00_007F, 0x0200_0053, true),
+    (ISA::FSUB_D, 0xFE00_007F, 0x0A00_0053, true),
+    (ISA::FMUL_D, 0xFE00_007F, 0x1200_0053, true),
+    (ISA::FDIV_D, 0xFE00_007F, 0x1A00_0053, true),
+    (ISA::FSQRT_D, 0xFFF0_007F, 0x5A00_0053, true),
+    (ISA::FSGNJ_D, 0xFE00_707F, 0x2200_0053, true),
+    (ISA::FSGNJN_D, 0xFE00_707F, 0x2200_1053, true),
+    (ISA::FSGNJX_D, 0xFE00_707F, 0x2200_2053, true),
+    (ISA::FMIN_D, 0xFE00_707F, 0x2A00_0053, true),
+    (ISA::FMAX_D, 0xFE00_707F, 0x2A00_1053, true),
+    (ISA::FCVT_S_D, 0xFFF0_007F, 0x4010_0053, true),
+    (ISA::FCVT_D_S, 0xFFF0_007F, 0x4200_0053, true),
+    (ISA::FEQ_D, 0xFE00_707F, 0xA200_2053, true),
+    (ISA::FLT_D, 0xFE00_707F, 0xA200_1053, true),
+    (ISA::FLE_D, 0xFE00_707F, 0xA200_0053, true),
+    (ISA::FCLASS_D, 0xFFF0_707F, 0xE200_1053, true),
+    (ISA::FCVT_W_D, 0xFFF0_007F, 0xC200_0053, true),
+    (ISA::FCVT_WU_D, 0xFFF0_007F, 0xC210_0053, true),
+    (ISA::FCVT_D_W, 0xFFF0_007F, 0xD200_0053, true),
+    (ISA::FCVT_D_WU, 0xFFF0_007F, 0xD210_0053, true),
+    (ISA::FCVT_L_D, 0xFFF0_007F, 0xC220_0053, false),
+    (ISA::FCVT_LU_D, 0xFFF0_007F, 0xC230_0053, false),
+    (ISA::FMV_X_D, 0xFFF0_707F, 0xE200_0053, false),
+    (ISA::FCVT_D_L, 0xFFF0_007F, 0xD220_0053, false),
+    (ISA::FCVT_D_LU, 0xFFF0_007F, 0xD230_0053, false),
+    (ISA::FMV_D_X, 0xFFF0_707F, 0xF200_0053, false),
+
+    (ISA::CSRRW, 0x0000_707F, 0x0000_1073, true),
+    (ISA::CSRRS, 0x0000_707F, 0x0000_2073, true),
+    (ISA::CSRRC, 0x0000_707F, 0x0000_3073, true),
+    (ISA::CSRRWI, 0x0000_707F, 0x0000_5073, true),
+    (ISA::CSRRSI, 0x0000_707F, 0x0000_6073, true),
+    (ISA::CSRRCI, 0x0000_707F, 0x0000_7073, true),
+
+    (ISA::FENCE_I, 0x0000_707F, 0x0000_100F, true),
+
+    (ISA::SRET, 0xFFFF_FFFF, 0x1020_0073, true),
+    (ISA::MRET, 0xFFFF_FFFF, 0x3020_0073, true),
+    (ISA::WFI, 0xFFFF_FFFF, 0x1050_0073, true),
+    (ISA::SFENCE_VMA, 0xFE00_7FFF, 0x1200_0073, true),
+];
+
+fn reference_decode(opcode: u32, xlen: usize, candidates: &[(ISA, u32, u32, bool)]) -> ISA {
+    let mut found = candidates.iter().filter(|(inst, mask, matches, rv32)| {
+        let mask = if xlen == 32 && matches!(inst, ISA::SLLI | ISA::SRLI | ISA::SRAI) { mask | 0x0200_0000 } else { *mask };
+        opcode & mask == *matches && (xlen == 64 || *rv32)
+    });
+    let inst = found.next().map_or(ISA::UNKNOWN, |(inst, ..)| *inst);
+    assert!(found.next().is_none(), "{:#010x} matches several instructions", opcode);
+    inst
+}
+
+#[test]
+fn decode_sweep() {
+    // The fields that select the instruction are the major opcode, funct3 and bits 31:20.
+    // rd and rs1 only matter for the SYSTEM instructions, so they are swept with their extreme values.
+    for major in (0..32).map(|op| op << 2 | 0b11) {
+        let candidates: Vec<_> = REFERENCE.iter().copied().filter(|(_, _, matches, _)| matches & 0x7F == major).collect();
+        for upper in 0..0x1000 {
+            for funct3 in 0..8 {
+                for regs in [0, 0x1F << 15, 0x1F << 7, 0x1F << 15 | 0x1F << 7] {
+                    let opcode = upper << 20 | funct3 << 12 | regs | major;
+                    for xlen in [32, 64] {
+                        assert_eq!(ISA::from_opcode_32(opcode, xlen), reference_decode(opcode, xlen, &candidates), "RV{} {:#010x}", xlen, opcode);
+                    }
+                }
+            }
+        }
+    }
+
+    for opcode in [0x0000_0000, 0xFFFF_FFFF, 0x0000_0001, 0x0000_007E] {
+        assert_eq!(ISA::from_opcode_32(opcode, 64), ISA::UNKNOWN);
+    }
+}
+
+#[test]
+fn decode_xlen() {
+    let ld = 0b000000000000_00001_011_00010_0000011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, ld).inst, ISA::LD);
+    assert_eq!(Instruction32::from_opcode_32(0, ld).inst, ISA::UNKNOWN);
+
+    let slli_32 = 0b0000001_00001_00010_001_00011_0010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, slli_32).inst, ISA::SLLI);
+    assert_eq!(Instruction32::from_opcode_32(0, slli_32).inst, ISA::UNKNOWN);
+
+    let srli_reserved = 0b1000000_00001_00010_101_00011_0010011u32;
+    assert_eq!(Instruction64::from_opcode_32(0, srli_reserved).inst, ISA::UNKNOWN);
+
+    let fence_tso = 0x8330_000Fu32;
+    assert_eq!(Instruction64::from_opcode_32(0, fence_tso).inst, ISA::FENCE);
+    let fence_reserved = 0b1010_0101_0110_00000_000_00000_0001111u32;
+    assert_eq!(Instruction64::from_opcode_32(0, fence_reserved).inst, ISA::UNKNOWN);
+
+    let jalr_reserved = 0b000000000000_00001_001_00000_1100111u32;
+    assert_eq!(Instruction64::from_opcode_32(0, jalr_reserved).inst, ISA::UNKNOWN);
+}
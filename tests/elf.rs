@@ -0,0 +1,88 @@
+use std::convert::TryInto;
+
+use dyriscvic::elf::*;
+use dyriscvic::public::*;
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::{build_elf, config, Memory};
+
+fn words(program: &[u32]) -> Vec<u8> {
+    program.iter().flat_map(|inst| inst.to_le_bytes()).collect()
+}
+
+#[test]
+fn load_elf32() {
+    let text = words(&[LW(1, 0, 0x204), ADDI(1, 1, 1), SW(1, 0, 0x208)]);
+    let data = words(&[0xDEAD_BEEF, 41]);
+    let elf = build_elf(false, 0, 0x100, &[(0x100, &text, 12), (0x200, &data, 0x10)], &[("_start", 0x100, 12, 2), ("counter", 0x204, 4, 1)]);
+
+    let elf = ElfFile::parse(&elf).unwrap();
+    assert_eq!((elf.xlen, elf.entry, elf.segments.len()), (32, 0x100, 2));
+    assert_eq!(elf.segments[1], Segment { offset: 128, vaddr: 0x200, paddr: 0x200, filesz: 8, memsz: 0x10, flags: 7 });
+    assert_eq!(elf.symbol("counter"), Some(&Symbol { name: String::from("counter"), value: 0x204, size: 4, kind: SymbolType::Object }));
+    assert_eq!(elf.symbol("_start").unwrap().kind, SymbolType::Function);
+    assert_eq!(elf.symbol("main"), None);
+    elf.check_config(32, &config("")).unwrap();
+
+    let mut memory = Memory { memory: vec![0xFF; 0x400] };
+    let entry = elf.load::<u32, _>(&mut memory).unwrap();
+    assert_eq!(&memory.memory[0x200..0x212], &[0xEF, 0xBE, 0xAD, 0xDE, 41, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF]);
+
+    let mut rv32 = RV32I::new([0; 32], entry as u32, config(""), memory);
+    assert!(rv32.run(3).is_none());
+    assert_eq!(rv32.x[1], 42);
+}
+
+#[test]
+fn load_elf64() {
+    let elf = build_elf(true, EF_RISCV_RVC | EF_RISCV_FLOAT_ABI_DOUBLE, 0x8000_0000, &[(0x10, &[1, 2, 3], 4)], &[("tohost", 0x8000_1000, 8, 1)]);
+    let elf = ElfFile::parse(&elf).unwrap();
+    assert_eq!((elf.xlen, elf.entry, elf.flags), (64, 0x8000_0000, 5));
+    assert_eq!(elf.symbol("tohost").unwrap().value, 0x8000_1000);
+
+    elf.check_config(64, &config("rv64gc")).unwrap();
+    elf.check_config(64, &config("DC")).unwrap();
+    assert_eq!(elf.check_config(32, &config("DC")), Err(ElfError::XlenMismatch { elf: 64, hart: 32 }));
+    assert_eq!(elf.check_config(64, &config("rv64imafd")), Err(ElfError::MissingExtension(String::from("C"))));
+    assert_eq!(elf.check_config(64, &config("rv64imafc")), Err(ElfError::MissingExtension(String::from("D"))));
+
+    let mut memory = Memory { memory: vec![0xFF; 0x20] };
+    assert_eq!(elf.load::<u64, _>(&mut memory), Ok(0x8000_0000));
+    assert_eq!(&memory.memory[0x10..0x15], &[1, 2, 3, 0, 0xFF]);
+    assert_eq!(elf.load::<u32, _>(&mut Memory { memory: vec![0; 0x10] }), Err(ElfError::MemoryFault(0x10, MemoryFault::AccessFault)));
+}
+
+#[test]
+fn elf_errors() {
+    let rve = build_elf(false, EF_RISCV_RVE, 0, &[], &[]);
+    let elf = ElfFile::parse(&rve).unwrap();
+    elf.check_config(32, &config("rv32em")).unwrap();
+    elf.check_config(32, &config("M")).unwrap();
+    assert_eq!(elf.check_config(32, &config("rv32im")), Err(ElfError::BaseMismatch { rve: true }));
+    let ilp32 = ElfFile::parse(&build_elf(false, 0, 0, &[], &[])).unwrap();
+    assert_eq!(ilp32.check_config(32, &config("rv32e")), Err(ElfError::BaseMismatch { rve: false }));
+
+    let high = ElfFile::parse(&build_elf(true, 0, 0, &[(0x1_0000_0000, &[1], 1)], &[])).unwrap();
+    assert_eq!(high.load::<u32, _>(&mut Memory { memory: vec![0; 0x10] }), Err(ElfError::AddressOutOfRange(0x1_0000_0000)));
+
+    let mut x86 = build_elf(true, 0, 0, &[], &[]);
+    x86[18] = 62;
+    assert_eq!(ElfFile::parse(&x86).unwrap_err(), ElfError::NotRiscV(62));
+    assert_eq!(ElfFile::parse(b"\x7FELF\x03").unwrap_err(), ElfError::InvalidClass(3));
+    assert_eq!(ElfFile::parse(b"MZ").unwrap_err(), ElfError::InvalidMagic);
+    let mut truncated = build_elf(false, 0, 0, &[(0, &[1, 2, 3, 4], 4)], &[]);
+    truncated.truncate(60);
+    assert_eq!(ElfFile::parse(&truncated).unwrap_err(), ElfError::Truncated);
+
+    // Symbol table and string table at the end of the address space.
+    let symtab = build_elf(true, 0, 0, &[], &[("main", 0x100, 4, 2)]);
+    let shoff = u64::from_le_bytes(symtab[40..48].try_into().unwrap()) as usize;
+    for (section, offset) in [(1, u64::MAX - 8), (2, u64::MAX)] {
+        let mut malformed = symtab.clone();
+        let sh_offset = shoff + 64 * section + 24;
+        malformed[sh_offset..sh_offset + 8].copy_from_slice(&offset.to_le_bytes());
+        assert_eq!(ElfFile::parse(&malformed).unwrap_err(), ElfError::Truncated);
+    }
+    assert_eq!(ElfFile::parse(&build_elf(false, EF_RISCV_FLOAT_ABI_QUAD, 0, &[], &[])).unwrap().check_config(32, &config("")), Err(ElfError::UnsupportedFloatABI));
+}
@@ -0,0 +1,186 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use dyriscvic::gdb::GdbStub;
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::{bus, config};
+
+/// Minimal GDB client.
+struct Client {
+    stream: TcpStream,
+    no_ack: bool,
+}
+
+impl Client {
+    fn byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn send(&mut self, data: &str) {
+        let checksum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        write!(self.stream, "${}#{:02x}", data, checksum).unwrap();
+        if !self.no_ack {
+            assert_eq!(self.byte(), b'+');
+        }
+    }
+
+    fn receive(&mut self) -> String {
+        while self.byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+        let checksum = [self.byte(), self.byte()];
+        let expected = format!("{:02x}", data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)));
+        assert_eq!(&checksum, expected.as_bytes());
+        if !self.no_ack {
+            self.stream.write_all(b"+").unwrap();
+        }
+        String::from_utf8(data).unwrap()
+    }
+
+    fn command(&mut self, data: &str) -> String {
+        self.send(data);
+        self.receive()
+    }
+
+    /// Reads the whole target description by chunks.
+    fn target_xml(&mut self) -> String {
+        let mut xml = String::new();
+        loop {
+            let chunk = self.command(&format!("qXfer:features:read:target.xml:{:x},400", xml.len()));
+            xml.push_str(&chunk[1..]);
+            if chunk.starts_with('l') {
+                return xml;
+            }
+            assert!(chunk.starts_with('m'));
+        }
+    }
+}
+
+/// Starts a client running `session` and returns the stream of the stub and the thread of the client.
+fn connect<F: FnOnce(&mut Client) + Send + 'static>(session: F) -> (TcpStream, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let mut client = Client { stream: TcpStream::connect(addr).unwrap(), no_ack: false };
+        session(&mut client);
+    });
+    (listener.accept().unwrap().0, client)
+}
+
+#[test]
+fn gdb_rv32() {
+    let program = [ADDI(1, 0, 5), ADDI(2, 0, 7), SW(2, 0, 0x100), LW(3, 0, 0x100), ADD(4, 1, 3), JAL(0, 0)];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), bus(&program));
+    let (stream, client) = connect(|client| {
+        assert!(client.command("qSupported:multiprocess+;swbreak+").starts_with("PacketSize=4000;qXfer:features:read+"));
+        assert_eq!(client.command("QStartNoAckMode"), "OK");
+        client.no_ack = true;
+
+        let xml = client.target_xml();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<architecture>riscv:rv32</architecture>"));
+        assert!(xml.contains("<reg name=\"pc\" bitsize=\"32\" type=\"code_ptr\" regnum=\"32\"/>"));
+        assert!(xml.contains("<reg name=\"mscratch\" bitsize=\"32\" type=\"int\" regnum=\"897\" group=\"csr\"/>"));
+        assert!(!xml.contains("riscv.fpu"));
+        assert_eq!(client.command("qXfer:features:read:target.xml:0,10"), format!("m{}", &xml[..16]));
+
+        assert_eq!(client.command("?"), "T05");
+        assert_eq!(client.command("g"), "0".repeat(33 * 8));
+        assert_eq!(client.command("s"), "T05");
+        assert_eq!(client.command("p20"), "04000000");
+        assert_eq!(client.command("p1"), "05000000");
+
+        assert_eq!(client.command("Z2,100,4"), "OK");
+        assert_eq!(client.command("Z3,100,4"), "OK");
+        assert_eq!(client.command("c"), "T05watch:100;");
+        assert_eq!(client.command("p20"), "0c000000");
+        assert_eq!(client.command("c"), "T05rwatch:100;");
+        assert_eq!(client.command("p3"), "07000000");
+        assert_eq!(client.command("z2,100,4"), "OK");
+        assert_eq!(client.command("z3,100,4"), "OK");
+
+        assert_eq!(client.command("Z1,14,4"), "OK");
+        assert_eq!(client.command("c"), "T05hwbreak:;");
+        assert_eq!(client.command("p20"), "14000000");
+        assert_eq!(client.command("p4"), "0c000000");
+        assert_eq!(client.command("z1,14,4"), "OK");
+        assert_eq!(client.command("Z0,4,4"), "OK");
+        client.send("c");
+        thread::sleep(Duration::from_millis(50));
+        client.stream.write_all(&[0x03]).unwrap();
+        assert_eq!(client.receive(), "T02");
+        assert_eq!(client.command("p20"), "14000000");
+        assert_eq!(client.command("c0"), "T05swbreak:;");
+        assert_eq!(client.command("p20"), "04000000");
+        assert_eq!(client.command("s"), "T05");
+        assert_eq!(client.command("p20"), "08000000");
+        assert_eq!(client.command("z0,4,4"), "OK");
+
+        assert_eq!(client.command("P1=efbeadde"), "OK");
+        assert_eq!(client.command("P0=01000000"), "OK");
+        assert_eq!(client.command("p0"), "00000000");
+        assert_eq!(client.command("m100,4"), "07000000");
+        assert_eq!(client.command("M100,4:78563412"), "OK");
+        assert_eq!(client.command("m100,4"), "78563412");
+        assert_eq!(client.command("mfff,2"), "E14");
+        assert_eq!(client.command("m0,ffffffffffffffff"), "E01");
+        assert_eq!(client.command("p1000"), "E01");
+        assert_eq!(client.command("vMustReplyEmpty"), "");
+        assert_eq!(client.command("D"), "OK");
+    });
+    GdbStub::new(&mut rv32).serve(stream.try_clone().unwrap(), stream).unwrap();
+    client.join().unwrap();
+    assert_eq!(rv32.x[1], 0xDEAD_BEEFu32 as i32);
+    assert_eq!(rv32.x[0], 0);
+}
+
+#[test]
+fn gdb_rv64() {
+    let program = [ADDI(1, 0, -1i32 as u32), 0];
+    let mut rv64 = RV64I::new([0; 32], 0, RVConfig { privileged: true, ..config("rv64imafdc") }, bus(&program));
+    let (stream, client) = connect(|client| {
+        let xml = client.target_xml();
+        assert!(xml.contains("<architecture>riscv:rv64</architecture>"));
+        assert!(xml.contains("<reg name=\"ft0\" bitsize=\"64\" type=\"ieee_double\" regnum=\"33\"/>"));
+        assert!(xml.contains("<reg name=\"fcsr\" bitsize=\"32\" type=\"int\" regnum=\"68\"/>"));
+        assert!(xml.contains("<reg name=\"mstatus\" bitsize=\"64\" type=\"int\" regnum=\"833\" group=\"csr\"/>"));
+
+        assert_eq!(client.command("s"), "T05");
+        assert_eq!(client.command("p1"), "ffffffffffffffff");
+        assert_eq!(client.command("P21=000000000000f03f"), "OK");
+        assert_eq!(client.command("p21"), "000000000000f03f");
+        assert_eq!(client.command("P381=2a00000000000000"), "OK");
+        assert_eq!(client.command("p381"), "2a00000000000000");
+        assert_eq!(client.command("p43"), "00000000");
+        assert_eq!(client.command("p2"), "0000000000000000");
+        client.send("k");
+    });
+    GdbStub::new(&mut rv64).serve(stream.try_clone().unwrap(), stream).unwrap();
+    client.join().unwrap();
+    assert_eq!(rv64.f[0], 1f64.to_bits());
+    assert_eq!(rv64.read_csr_by_name("mscratch"), Some(42));
+}
+
+#[test]
+fn gdb_trap_signals() {
+    let program = [ADDI(1, 0, 1), 0];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), bus(&program));
+    let (stream, client) = connect(|client| {
+        assert_eq!(client.command("c"), "T04");
+        assert_eq!(client.command("p20"), "08000000");
+        client.send("k");
+    });
+    GdbStub::new(&mut rv32).serve(stream.try_clone().unwrap(), stream).unwrap();
+    client.join().unwrap();
+}
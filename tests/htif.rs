@@ -0,0 +1,84 @@
+use dyriscvic::htif::{self, Htif};
+use dyriscvic::public::*;
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::{bus, config};
+
+const TOHOST: u64 = 0x400;
+const FROMHOST: u64 = 0x440;
+
+#[test]
+fn htif_exit_rv32() {
+    // write_tohost of riscv-tests: the low word then the high word.
+    let program = [ADDI(3, 0, 1), SW(3, 0, TOHOST as u32), SW(0, 0, TOHOST as u32 + 4), JAL(0, -12i32 as u32)];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Htif::new(bus(&program), TOHOST, None));
+    rv32.run(2);
+    assert_eq!(rv32.eei().exit_code(), None);
+    rv32.single_step();
+    assert!(rv32.eei().passed());
+    assert_eq!(MemoryAccess::<u32>::get_64(rv32.eei_mut(), TOHOST as u32), Ok(0));
+
+    let program = [ADDI(3, 0, 5 << 1 | 1), SW(3, 0, TOHOST as u32), SW(0, 0, TOHOST as u32 + 4), JAL(0, -12i32 as u32)];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Htif::new(bus(&program), TOHOST, None));
+    assert_eq!(htif::run(&mut rv32, 100), Some(5));
+    assert!(!rv32.eei().passed());
+    assert_eq!(rv32.pc, 12);
+
+    let program = [JAL(0, 0)];
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Htif::new(bus(&program), TOHOST, None));
+    assert_eq!(htif::run(&mut rv32, 100), None);
+}
+
+#[test]
+fn htif_syscalls_rv64() {
+    let program = [
+        ADDI(10, 0, 0x600),
+        SD(0, 10, TOHOST as u32), // write(1, "hello", 5)
+        ADDI(10, 0, 0x680),
+        SD(0, 10, TOHOST as u32), // Unknown system call.
+        ADDI(5, 0, 0x101),
+        SLLI(5, 5, 48),
+        ADDI(5, 5, '!' as u32),
+        SD(0, 5, TOHOST as u32), // putchar('!')
+        ADDI(6, 0, 3 << 1 | 1),
+        SD(0, 6, TOHOST as u32),
+        JAL(0, 0),
+    ];
+    let mut bus = bus(&program);
+    for (i, arg) in [64u64, 1, 0x700, 5].iter().enumerate() {
+        bus.memory_mut(0x600 + 8 * i as u64, 8).unwrap().copy_from_slice(&arg.to_le_bytes());
+    }
+    bus.memory_mut(0x680, 8).unwrap().copy_from_slice(&1234u64.to_le_bytes());
+    bus.memory_mut(0x700, 5).unwrap().copy_from_slice(b"hello");
+
+    let mut rv64 = RV64I::new([0; 32], 0, config(""), Htif::new(bus, TOHOST, Some(FROMHOST)));
+    rv64.run(2);
+    assert_eq!(rv64.eei().console, b"hello");
+    assert_eq!(rv64.eei().memory.memory(0x600, 8), Some(&5u64.to_le_bytes()[..]));
+    assert_eq!(rv64.eei().memory.memory(FROMHOST, 8), Some(&1u64.to_le_bytes()[..]));
+    rv64.run(2);
+    assert_eq!(rv64.eei().memory.memory(0x680, 8), Some(&(-38i64).to_le_bytes()[..]));
+    rv64.run(4);
+    assert_eq!(rv64.eei().console, b"hello!");
+    assert_eq!(rv64.eei().memory.memory(FROMHOST, 8), Some(&0x0101_0000_0000_0000u64.to_le_bytes()[..]));
+    assert_eq!(htif::run(&mut rv64, 100), Some(3));
+}
+
+#[test]
+fn htif_faults() {
+    let program = [ADDI(10, 0, 0x600), SD(0, 10, TOHOST as u32), JAL(0, 0)];
+    let mut memory = bus(&program);
+    for (i, arg) in [64u64, 1, u64::MAX - 1, 5].iter().enumerate() {
+        memory.memory_mut(0x600 + 8 * i as u64, 8).unwrap().copy_from_slice(&arg.to_le_bytes());
+    }
+    let mut rv64 = RV64I::new([0; 32], 0, config(""), Htif::new(memory, TOHOST, None));
+    rv64.run(2);
+    assert!(rv64.eei().console.is_empty());
+    assert_eq!(rv64.eei().memory.memory(0x600, 8), Some(&(-14i64).to_le_bytes()[..]));
+
+    // tohost at the end of the address space.
+    let mut htif = Htif::new(bus(&[]), u64::MAX - 3, None);
+    assert_eq!(MemoryAccess::<u64>::set_64(&mut htif, 0, 1), Ok(()));
+    assert_eq!(htif.exit_code(), None);
+}
@@ -0,0 +1,1036 @@
+use dyriscvic::common::{*, isa_string::ISAStringError};
+use dyriscvic::public::*;
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::{bus, config, Memory};
+
+#[test]
+fn execute_m32() {
+    let program = [
+        MUL(3, 1, 2),
+        MULH(4, 1, 2),
+        MULHSU(5, 1, 2),
+        MULHU(6, 1, 2),
+        DIV(7, 1, 0),
+        DIVU(8, 1, 0),
+        REM(9, 1, 0),
+        REMU(10, 1, 0),
+        DIV(11, 12, 13),
+        REM(14, 12, 13),
+    ];
+    let mut x = [0i32; 32];
+    x[1] = -7;
+    x[2] = 3;
+    x[12] = i32::MIN;
+    x[13] = -1;
+    let mut rv32 = RV32I::new(x, 0, config("M"), Memory::new(&program));
+    for _ in 0..program.len() {
+        rv32.single_step();
+    }
+
+    assert_eq!(rv32.x[3], -21);
+    assert_eq!(rv32.x[4], -1);
+    assert_eq!(rv32.x[5], -1);
+    assert_eq!(rv32.x[6], 2);
+    assert_eq!(rv32.x[7], -1);
+    assert_eq!(rv32.x[8], -1);
+    assert_eq!(rv32.x[9], -7);
+    assert_eq!(rv32.x[10], -7);
+    assert_eq!(rv32.x[11], i32::MIN);
+    assert_eq!(rv32.x[14], 0);
+}
+
+#[test]
+fn execute_m64() {
+    let program = [
+        MULH(3, 1, 2),
+        MULHU(4, 1, 2),
+        MULW(5, 1, 2),
+        DIVW(6, 12, 13),
+        DIVUW(7, 1, 0),
+        REMW(8, 12, 13),
+        REMUW(9, 2, 0),
+    ];
+    let mut x = [0i64; 32];
+    x[1] = -1;
+    x[2] = 0x7FFF_FFFF;
+    x[12] = i32::MIN as i64;
+    x[13] = -1;
+    let mut rv64 = RV64I::new(x, 0, config("M"), Memory::new(&program));
+    for _ in 0..program.len() {
+        rv64.single_step();
+    }
+
+    assert_eq!(rv64.x[3], -1);
+    assert_eq!(rv64.x[4], 0x7FFF_FFFE);
+    assert_eq!(rv64.x[5], -0x7FFF_FFFF);
+    assert_eq!(rv64.x[6], i32::MIN as i64);
+    assert_eq!(rv64.x[7], -1);
+    assert_eq!(rv64.x[8], 0);
+    assert_eq!(rv64.x[9], 0x7FFF_FFFF);
+}
+
+#[test]
+fn execute_a32() {
+    let program = [
+        AMOADD_W(3, 1, 2, false, false),
+        AMOMIN_W(4, 1, 2, true, false),
+        AMOMAXU_W(5, 1, 2, false, true),
+        LR_W(6, 1, false, false),
+        SC_W(7, 1, 2, false, false),
+        SC_W(8, 1, 2, false, false), // no reservation
+        LR_W(9, 1, false, false),
+        SW(2, 1, 4),                 // same reservation set
+        SC_W(10, 1, 2, false, false),
+        AMOSWAP_W(11, 1, 0, false, false),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x100, &[5]);
+    let mut x = [0i32; 32];
+    x[1] = 0x100;
+    x[2] = -3;
+    let mut rv32 = RV32I::new(x, 0, config("A"), memory);
+    for _ in 0..program.len() {
+        rv32.single_step();
+    }
+
+    assert_eq!(rv32.x[3], 5);
+    assert_eq!(rv32.x[4], 2);
+    assert_eq!(rv32.x[5], -3);
+    assert_eq!(rv32.x[6], -3);
+    assert_eq!((rv32.x[7], rv32.x[8]), (0, 1));
+    assert_eq!((rv32.x[9], rv32.x[10]), (-3, 1));
+    assert_eq!(rv32.x[11], -3);
+    assert_eq!(rv32.read_csr_by_name("misa"), Some(0x4000_0101));
+}
+
+#[test]
+fn execute_a64() {
+    let program = [
+        AMOADD_D(3, 1, 2, false, false),
+        AMOXOR_D(4, 1, 2, false, false),
+        AMOADD_W(5, 1, 2, false, false),
+        LR_D(6, 1, true, true),
+        SC_D(7, 1, 0, true, true),
+        LD(8, 1, 0),
+        AMOMAX_D(9, 1, 2, false, false),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load_64(0x100, 0x1_0000_0000);
+    let mut x = [0i64; 32];
+    x[1] = 0x100;
+    x[2] = 0xFFFF_FFFF;
+    let mut rv64 = RV64I::new(x, 0, config("A"), memory);
+    for _ in 0..program.len() {
+        rv64.single_step();
+    }
+
+    assert_eq!(rv64.x[3], 0x1_0000_0000);
+    assert_eq!(rv64.x[4], 0x1_FFFF_FFFF);
+    assert_eq!(rv64.x[5], 0);
+    assert_eq!(rv64.x[6], 0x1_FFFF_FFFF);
+    assert_eq!(rv64.x[7], 0);
+    assert_eq!(rv64.x[8], 0);
+    assert_eq!(rv64.x[9], 0);
+}
+
+#[test]
+fn memory_faults() {
+    let program = [
+        LW(1, 7, 1),
+        SW(2, 8, 3),
+        LW(3, 8, 3),
+        LW(4, 5, 0),
+        SW(2, 5, 0),
+        LW(4, 6, 0),
+    ];
+    let mut x = [0i32; 32];
+    x[2] = 0x1234_5678;
+    x[5] = 0x10000;
+    x[6] = 0xFFFE;
+    x[7] = 0x1000;
+    x[8] = 0x2000;
+    let mut memory = Memory::new(&program);
+    memory.load(0x1000, &[0x4433_2211, 0x8877_6655]);
+    let mut conf = config("");
+    conf.privileged = true;
+    let mut rv32 = RV32I::new(x, 0, conf.clone(), memory);
+
+    assert!(rv32.run(3).is_none());
+    assert_eq!((rv32.x[1], rv32.x[3]), (0x5544_3322, 0x1234_5678));
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::LoadAccessFault, tval: 0x10000, pc: 12 }));
+    assert_eq!(rv32.read_csr_by_name("mtval"), Some(0x10000));
+
+    rv32.pc = 16;
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::StoreAMOAccessFault, tval: 0x10000, pc: 16 }));
+
+    rv32.pc = 20;
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::LoadAccessFault, tval: 0xFFFE, pc: 20 }));
+
+    conf.misaligned_access = MisalignedAccess::Trap;
+    let mut rv32 = RV32I::new(x, 0, conf, Memory::new(&program));
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::LoadAddressMisaligned, tval: 0x1001, pc: 0 }));
+    assert_eq!(rv32.read_csr_by_name("mcause"), Some(4));
+    rv32.pc = 4;
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::StoreAMOAddressMisaligned, tval: 0x2003, pc: 4 }));
+    assert_eq!(rv32.x[1], 0);
+}
+
+#[test]
+fn load_to_x0() {
+    let program = [
+        LW(0, 0, 0x100),
+        LBU(0, 1, 0),
+        ADDI(0, 0, 5), // HINT
+        LD(0, 2, 0),
+    ];
+    let mut x = [0i64; 32];
+    x[1] = 0x10000;
+    x[2] = 0x103;
+    let mut conf = config("");
+    conf.privileged = true;
+    conf.misaligned_access = MisalignedAccess::Trap;
+    let mut rv64 = RV64I::new(x, 0, conf, Memory::new(&program));
+
+    assert!(matches!(rv64.single_step(), StepResult::Retired { pc: 0, .. }));
+    assert!(matches!(rv64.single_step(), StepResult::Trap { cause: Traps::LoadAccessFault, tval: 0x10000, pc: 4 }));
+    rv64.pc = 8;
+    assert!(matches!(rv64.single_step(), StepResult::Retired { pc: 8, .. }));
+    assert!(matches!(rv64.single_step(), StepResult::Trap { cause: Traps::LoadAddressMisaligned, tval: 0x103, pc: 12 }));
+    assert_eq!(rv64.x[0], 0);
+}
+
+#[test]
+fn trap_causes() {
+    for trap in Traps::ALL {
+        assert_eq!(Traps::from_cause(trap.cause(32), 32), Some(trap));
+        assert_eq!(Traps::from_cause(trap.cause(64), 64), Some(trap));
+    }
+    assert_eq!(Traps::EnvironmentCallFromSMode.cause(64), 9);
+    assert_eq!(Traps::MachineExternalInterrupt.cause(32), 0x8000_000B);
+    assert_eq!(Traps::from_cause(0x8000_0000_0000_0005, 64), Some(Traps::SupervisorTimerInterrupt));
+    assert_eq!(Traps::from_cause(0x8000_0005, 64), None);
+    assert_eq!(Traps::from_cause(10, 32), None);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected trap InstructionAddressMisaligned at 0x4, tval 0xa")]
+fn trap_value() {
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Memory::new(&[ADDI(0, 0, 0), BEQ(0, 0, 6)]));
+    rv32.single_step();
+    rv32.single_step();
+}
+
+#[test]
+#[should_panic(expected = "StoreAMOAddressMisaligned")]
+fn a_misaligned() {
+    let mut x = [0i32; 32];
+    x[1] = 0x102;
+    let mut rv32 = RV32I::new(x, 0, config("A"), Memory::new(&[AMOOR_W(3, 1, 2, false, false)]));
+    rv32.single_step();
+}
+
+#[test]
+#[should_panic(expected = "IllegalInstruction")]
+fn m_requires_extension() {
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Memory::new(&[MUL(1, 2, 3)]));
+    rv32.single_step();
+}
+
+#[test]
+fn execute_f32() {
+    let program = [
+        FLW(1, 1, 0),
+        FLW(2, 1, 4),
+        FLW(3, 1, 8),
+        FLW(4, 1, 12),
+        FADD_S(5, 1, 2, 7),
+        CSRRS(2, 0x001, 0), // fflags
+        FADD_S(6, 3, 4, 7),
+        CSRRS(3, 0x001, 0),
+        FMADD_S(7, 1, 2, 4, 7),
+        FNMSUB_S(8, 1, 2, 4, 7),
+        FCVT_W_S(10, 3, 1),
+        CSRRWI(0, 0x001, 0),
+        FCVT_WU_S(11, 3, 1),
+        FEQ_S(12, 1, 1),
+        FLT_S(13, 3, 1),
+        FLE_S(14, 1, 3),
+        FCLASS_S(15, 3),
+        FMV_X_W(16, 1),
+        FSGNJN_S(9, 1, 1),
+        FMIN_S(10, 1, 3),
+        FSW(5, 1, 16),
+        LW(17, 1, 16),
+        CSRRS(18, 0x001, 0),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x100, &[2.5f32.to_bits(), 1.0f32.to_bits(), (-1235.1f32).to_bits(), 1.1f32.to_bits()]);
+    let mut x = [0i32; 32];
+    x[1] = 0x100;
+    let mut rv32 = RV32I::new(x, 0, config("FZicsr"), memory);
+    for _ in 0..program.len() {
+        rv32.single_step();
+    }
+
+    let boxed = |value: f32| 0xFFFF_FFFF_0000_0000 | value.to_bits() as u64;
+    assert_eq!(rv32.f[5], boxed(3.5));
+    assert_eq!(rv32.x[2], 0);
+    assert_eq!(rv32.f[6], boxed(-1234.0));
+    assert_eq!(rv32.x[3], 1); // NX
+    assert_eq!(rv32.f[7], boxed(2.5 + 1.1));
+    assert_eq!(rv32.f[8], boxed(1.1 - 2.5));
+    assert_eq!(rv32.x[10], -1235);
+    assert_eq!(rv32.x[11], 0);
+    assert_eq!((rv32.x[12], rv32.x[13], rv32.x[14]), (1, 1, 0));
+    assert_eq!(rv32.x[15], 1 << 1);
+    assert_eq!(rv32.x[16], 0x4020_0000);
+    assert_eq!(rv32.f[9], boxed(-2.5));
+    assert_eq!(rv32.f[10], boxed(-1235.1));
+    assert_eq!(rv32.x[17], 3.5f32.to_bits() as i32);
+    assert_eq!(rv32.x[18], 0b10000); // NV
+}
+
+#[test]
+fn execute_d64() {
+    let program = [
+        FMV_D_X(1, 1),
+        FMV_D_X(2, 2),
+        FDIV_D(3, 1, 2, 7),
+        FSQRT_D(4, 1, 7),
+        FMSUB_D(5, 1, 2, 3, 7),
+        FCVT_S_D(6, 1, 7),
+        FCVT_D_S(7, 6, 7),
+        FCVT_D_L(8, 12, 7),
+        FCVT_L_D(10, 1, 3),
+        FMV_D_X(9, 14),
+        FCVT_W_D(15, 9, 1),
+        FCVT_WU_D(16, 9, 1),
+        FMV_X_D(17, 4),
+        FSD(3, 20, 0),
+        FLD(10, 20, 0),
+        FEQ_D(19, 3, 10),
+        FCVT_L_S(18, 6, 1),
+        FADD_S(11, 1, 1, 7),
+        FCVT_S_LU(12, 21, 2),
+    ];
+    let mut x = [0i64; 32];
+    x[1] = std::f64::consts::PI.to_bits() as i64;
+    x[2] = std::f64::consts::E.to_bits() as i64;
+    x[12] = -5;
+    x[14] = 1e10f64.to_bits() as i64;
+    x[20] = 0x100;
+    x[21] = -1;
+    let mut rv64 = RV64I::new(x, 0, config("FDZicsr"), Memory::new(&program));
+    for _ in 0..program.len() {
+        rv64.single_step();
+    }
+
+    use std::f64::consts::{E, PI};
+    assert_eq!(rv64.f[3], (PI / E).to_bits());
+    assert_eq!(rv64.f[4], PI.sqrt().to_bits());
+    assert_eq!(rv64.f[5], PI.mul_add(E, -(PI / E)).to_bits());
+    assert_eq!(rv64.f[6], 0xFFFF_FFFF_0000_0000 | (PI as f32).to_bits() as u64);
+    assert_eq!(rv64.f[7], (PI as f32 as f64).to_bits());
+    assert_eq!(rv64.f[8], (-5.0f64).to_bits());
+    assert_eq!(rv64.x[10], 4);
+    assert_eq!(rv64.x[15], i32::MAX as i64);
+    assert_eq!(rv64.x[16], -1); // 32-bit results are sign-extended.
+    assert_eq!(rv64.x[17], PI.sqrt().to_bits() as i64);
+    assert_eq!(rv64.x[19], 1);
+    assert_eq!(rv64.x[18], 3);
+    assert_eq!(rv64.f[11], 0xFFFF_FFFF_7FC0_0000); // f1 is not NaN-boxed.
+    assert_eq!(rv64.f[12], 0xFFFF_FFFF_0000_0000 | 0x5F7F_FFFF); // 2^64 - 1 rounded down
+    assert_eq!(rv64.read_csr_by_name("fflags"), Some(0b10001));
+}
+
+#[test]
+fn fp_state() {
+    let program = [
+        FADD_D(1, 2, 3, 7),
+        FADD_D(1, 2, 3, 7),
+        FADD_D(1, 2, 3, 5),
+        FADD_D(1, 2, 3, 7),
+    ];
+    let mut config = config("FDZicsr");
+    config.privileged = true;
+    let mut rv64 = RV64I::new([0; 32], 0, config, Memory::new(&program));
+    assert_eq!(rv64.read_csr_by_name("mstatus").unwrap() & 0x6000, 0x2000);
+
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("mstatus").unwrap() & (1 << 63 | 0x6000), 1 << 63 | 0x6000);
+
+    // FS is Off.
+    let mstatus = rv64.read_csr_by_name("mstatus").unwrap();
+    rv64.write_csr_by_name("mstatus", mstatus & !0x6000);
+    rv64.write_csr_by_name("mtvec", 0x1000);
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("mcause"), Some(2));
+    assert_eq!(rv64.read_csr_by_name("fcsr"), Some(0));
+
+    // Reserved static and dynamic rounding modes.
+    rv64.write_csr_by_name("mstatus", mstatus);
+    rv64.pc = 8;
+    rv64.write_csr_by_name("mcause", 0);
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("mcause"), Some(2));
+
+    rv64.write_csr_by_name("frm", 6);
+    rv64.pc = 12;
+    rv64.write_csr_by_name("mcause", 0);
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("mcause"), Some(2));
+    assert_eq!(rv64.read_csr_by_name("fcsr"), Some(6 << 5));
+}
+
+#[test]
+#[should_panic(expected = "IllegalInstruction")]
+fn f_requires_extension() {
+    let mut rv32 = RV32I::new([0; 32], 0, config("M"), Memory::new(&[FADD_S(1, 2, 3, 7)]));
+    rv32.single_step();
+}
+
+/// Packs a program made of 16-bits parcels into words.
+fn parcels(program: &[u16]) -> Vec<u32> {
+    program.chunks(2).map(|p| p[0] as u32 | (*p.get(1).unwrap_or(&0) as u32) << 16).collect()
+}
+
+#[test]
+fn execute_c() {
+    let addi = ADDI(11, 10, 3);
+    let program = parcels(&[
+        C_LI(10, 5),
+        C_ADDI(10, -1i32 as u32),
+        C_JAL(6),
+        C_NOP(),
+        C_NOP(),
+        addi as u16, (addi >> 16) as u16,
+        C_MV(12, 11),
+        C_BNEZ(12, 4),
+        C_EBREAK(),
+    ]);
+    let mut rv32 = RV32I::new([0; 32], 0, config("C"), Memory::new(&program));
+    for _ in 0..6 {
+        rv32.single_step();
+    }
+
+    assert_eq!(rv32.pc, 20);
+    assert_eq!(rv32.x[1], 6);
+    assert_eq!(rv32.x[10], 4);
+    assert_eq!(rv32.x[11], 7);
+    assert_eq!(rv32.x[12], 7);
+
+    let program = parcels(&[
+        C_LI(9, -1i32 as u32),
+        C_SDSP(9, 8),
+        C_LDSP(10, 8),
+        C_ADDIW(10, 1),
+        C_ADDI4SPN(8, 16),
+        C_SD(9, 8, 0),
+        C_LW(11, 8, 4),
+        C_JALR(12),
+        C_EBREAK(),
+        C_EBREAK(),
+        C_SRLI(11, 32),
+    ]);
+    let mut x = [0i64; 32];
+    x[2] = 0x1000;
+    x[12] = 20;
+    let mut rv64 = RV64I::new(x, 0, config("C"), Memory::new(&program));
+    for _ in 0..9 {
+        rv64.single_step();
+    }
+
+    assert_eq!(rv64.pc, 22);
+    assert_eq!(rv64.x[1], 16);
+    assert_eq!(rv64.x[8], 0x1010);
+    assert_eq!(rv64.x[10], 0);
+    assert_eq!(rv64.x[11], 0xFFFF_FFFF);
+}
+
+#[test]
+#[should_panic(expected = "IllegalInstruction")]
+fn c_requires_extension() {
+    let mut rv32 = RV32I::new([0; 32], 0, config(""), Memory::new(&parcels(&[C_NOP(), C_NOP()])));
+    rv32.single_step();
+}
+
+#[test]
+#[should_panic(expected = "base E cannot be used with 32 integer registers")]
+fn isa_string_rejected() {
+    RV64I::new([0; 32], 0, config("rv64emac"), Memory::new(&[]));
+}
+
+#[test]
+fn isa_string_error() {
+    let error = RV64I::try_new([0; 32], 0, config("rv64emac"), Memory::new(&[])).err();
+    assert_eq!(error, Some(ISAStringError::BaseMismatch { base: 'E', registers: 32 }));
+    assert!(RV32I::try_new([0; 32], 0, config("rv64i"), Memory::new(&[])).is_err());
+}
+
+#[test]
+fn isa_string_extensions() {
+    let mut rv64 = RV64I::new([0; 32], 0, config("rv64gc"), Memory::new(&[FENCE_I(), FADD_D(1, 2, 3, 7)]));
+    rv64.single_step();
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("misa"), Some(2 << 62 | 0x112D));
+}
+
+#[test]
+fn rve_registers() {
+    let program = [
+        ADD(15, 14, 13),
+        CSRRWI(1, 0x340, 31), // mscratch
+        ADD(16, 1, 2),
+        ADDI(1, 20, 0),
+        ADD(1, 2, 31),
+    ];
+    let mut x = [0i32; 16];
+    x[13] = 2;
+    x[14] = 3;
+    let mut conf = config("Zicsr");
+    conf.privileged = true;
+    let mut rv32 = RV32E::new(x, 0, conf.clone(), Memory::new(&program));
+
+    assert!(rv32.run(2).is_none());
+    assert_eq!(rv32.x[15], 5);
+    assert_eq!(rv32.read_csr_by_name("mscratch"), Some(31));
+    for (pc, opcode) in [(8, ADD(16, 1, 2)), (12, ADDI(1, 20, 0)), (16, ADD(1, 2, 31))] {
+        rv32.pc = pc;
+        match rv32.single_step() {
+            StepResult::Trap { cause: Traps::IllegalInstruction, tval, pc: epc } => assert_eq!((tval, epc), (opcode, pc)),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    let mut rv64 = RV64E::new([0; 16], 0, conf, Memory::new(&[ADDI(15, 0, -1i32 as u32), ADDIW(16, 15, 0)]));
+    rv64.single_step();
+    assert_eq!(rv64.x[15], -1);
+    assert_eq!(rv64.disassemble_instruction(rv64.inst), "addi a5, zero, -1");
+    assert!(matches!(rv64.single_step(), StepResult::Trap { cause: Traps::IllegalInstruction, .. }));
+    assert_eq!(rv64.read_csr_by_name("misa"), Some(2 << 62 | 1 << 4));
+}
+
+#[test]
+#[should_panic(expected = "extension D cannot be used with base E")]
+fn rve_without_d() {
+    RV32E::new([0; 16], 0, config("FD"), Memory::new(&[]));
+}
+
+#[test]
+fn execute_zicsr() {
+    let program = [
+        CSRRW(1, 0x340, 2), // mscratch
+        CSRRS(3, 0x340, 4),
+        CSRRC(5, 0x340, 6),
+        CSRRSI(7, 0x340, 0),
+        CSRRWI(0, 0x340, 31),
+        CSRRS(8, 0xC02, 0), // instret
+        CSRRS(9, 0x301, 0), // misa
+    ];
+    let mut x = [0i32; 32];
+    x[2] = 0x1234;
+    x[4] = 0x4300;
+    x[6] = 0x0204;
+    let mut rv32 = RV32I::new(x, 0, config("MZicsr"), Memory::new(&program));
+    for _ in 0..program.len() {
+        rv32.single_step();
+    }
+
+    assert_eq!(rv32.x[1], 0);
+    assert_eq!(rv32.x[3], 0x1234);
+    assert_eq!(rv32.x[5], 0x5334);
+    assert_eq!(rv32.x[7], 0x5130);
+    assert_eq!(rv32.x[8], 5);
+    assert_eq!(rv32.x[9], 0x4000_1100);
+    assert_eq!(rv32.read_csr_by_name("mscratch"), Some(31));
+
+    assert!(rv32.write_csr_by_name("mcycle", 0xFFFF_FFFF));
+    assert!(rv32.write_csr(0xB80, 1)); // mcycleh
+    assert_eq!(rv32.read_csr_by_name("cycle"), Some(0xFFFF_FFFF));
+    assert_eq!(rv32.read_csr_by_name("cycleh"), Some(1));
+    assert!(!rv32.write_csr_by_name("cycle", 0));
+    assert!(!rv32.write_csr_by_name("satp", 0));
+}
+
+#[test]
+#[should_panic(expected = "IllegalInstruction")]
+fn zicsr_read_only() {
+    let mut rv32 = RV32I::new([0; 32], 0, config("Zicsr"), Memory::new(&[CSRRWI(1, 0xC00, 1)]));
+    rv32.single_step();
+}
+
+#[test]
+fn machine_mode_traps() {
+    let program = [
+        ADDI(1, 0, 0x100),
+        CSRRW(0, 0x305, 1), // mtvec
+        ECALL(),
+        0x0000_000B, // custom-0
+        CSRRWI(0, 0xC00, 1), // cycle
+    ];
+    let handler = [
+        CSRRS(21, 0x342, 0), // mcause
+        CSRRS(22, 0x343, 0), // mtval
+        CSRRS(23, 0x341, 0), // mepc
+        ADDI(24, 23, 4),
+        CSRRW(0, 0x341, 24),
+        ADDI(20, 20, 1),
+        MRET(),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x100, &handler);
+    let mut conf = config("Zicsr");
+    conf.privileged = true;
+    let mut rv32 = RV32I::new([0; 32], 0, conf, memory);
+
+    for _ in 0..3 {
+        rv32.single_step();
+    }
+    assert_eq!(rv32.pc, 0x100);
+    assert_eq!(rv32.read_csr_by_name("mstatus"), Some(0x1800));
+
+    for _ in 0..7 {
+        rv32.single_step();
+    }
+    assert_eq!((rv32.x[20], rv32.x[21], rv32.x[22], rv32.x[23]), (1, 11, 0, 8));
+    assert_eq!(rv32.pc, 12);
+
+    for _ in 0..8 {
+        rv32.single_step();
+    }
+    assert_eq!((rv32.x[20], rv32.x[21], rv32.x[22], rv32.x[23]), (2, 2, 0xB, 12));
+    assert_eq!(rv32.pc, 16);
+
+    for _ in 0..8 {
+        rv32.single_step();
+    }
+    assert_eq!((rv32.x[20], rv32.x[21], rv32.x[22], rv32.x[23]), (3, 2, CSRRWI(0, 0xC00, 1) as i32, 16));
+    assert_eq!(rv32.pc, 20);
+}
+
+#[test]
+fn machine_mode_interrupts() {
+    let program = [
+        ADDI(1, 0, 0x201),
+        CSRRW(0, 0x305, 1), // mtvec, vectored
+        ADDI(1, 0, 0x80),
+        CSRRW(0, 0x304, 1), // mie.MTIE
+        CSRRSI(0, 0x300, 0x8), // mstatus.MIE
+        ADDI(2, 0, 1),
+    ];
+    let mut conf = config("Zicsr");
+    conf.privileged = true;
+    let mut rv32 = RV32I::new([0; 32], 0, conf, Memory::new(&program));
+
+    rv32.set_interrupt_pending(Traps::MachineTimerInterrupt, true);
+    assert!(rv32.run(5).is_none());
+    assert_eq!(rv32.pc, 20);
+
+    assert!(matches!(rv32.single_step(), StepResult::Trap { cause: Traps::MachineTimerInterrupt, tval: 0, pc: 20 }));
+    assert_eq!(rv32.x[2], 0);
+    assert_eq!(rv32.pc, 0x200 + 4 * 7);
+    assert_eq!(rv32.read_csr_by_name("mcause"), Some(0x8000_0007));
+    assert_eq!(rv32.read_csr_by_name("mepc"), Some(20));
+    assert_eq!(rv32.read_csr_by_name("mstatus"), Some(0x1880));
+}
+
+#[test]
+fn step_results() {
+    let program = [
+        ADDI(1, 0, 0x100),
+        CSRRW(0, 0x305, 1), // mtvec
+        ADDI(2, 0, 5),
+        EBREAK(),
+        ADDI(2, 2, 1),
+        ECALL(),
+    ];
+    let handler = [
+        CSRRS(3, 0x341, 0), // mepc
+        ADDI(3, 3, 4),
+        CSRRW(0, 0x341, 3),
+        MRET(),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x100, &handler);
+    let mut conf = config("Zicsr");
+    conf.privileged = true;
+    let mut rv32 = RV32I::new([0; 32], 0, conf, memory);
+
+    match rv32.single_step() {
+        StepResult::Retired { pc, inst } => {
+            assert_eq!(pc, 0);
+            assert_eq!((inst.inst, inst.rd, inst.imm), (isa::ISA::ADDI, 1, 0x100));
+        },
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    assert!(matches!(rv32.run(10), Some(StepResult::Trap { cause: Traps::Breakpoint, tval: 12, pc: 12 })));
+    assert_eq!((rv32.x[2], rv32.pc), (5, 0x100));
+    assert_eq!(rv32.read_csr_by_name("minstret"), Some(3));
+    assert_eq!(rv32.read_csr_by_name("mcycle"), Some(4));
+
+    let result = rv32.run_until(|result| matches!(result, StepResult::Trap { cause: Traps::EnvironmentCallFromMMode, .. }));
+    assert!(matches!(result, StepResult::Trap { cause: Traps::EnvironmentCallFromMMode, tval: 0, pc: 20 }));
+    assert_eq!(rv32.x[2], 6);
+    assert!(rv32.run(4).is_none());
+    assert_eq!(rv32.pc, 24);
+}
+
+#[test]
+fn supervisor_and_user_modes() {
+    let program = [
+        ADDI(1, 0, 0x100),
+        CSRRW(0, 0x302, 1), // medeleg: ecall from U-mode
+        ADDI(1, 0, 0x200),
+        CSRRW(0, 0x105, 1), // stvec
+        ADDI(1, 0, 0x300),
+        CSRRW(0, 0x305, 1), // mtvec
+        ADDI(1, 0, 0x100),
+        CSRRW(0, 0x341, 1), // mepc
+        ADDI(1, 0, 3),
+        SLLI(1, 1, 11),
+        CSRRC(0, 0x300, 1), // mstatus.MPP = U
+        MRET(),
+    ];
+    let user = [
+        ECALL(),
+        CSRRS(10, 0x300, 0), // mstatus
+    ];
+    let supervisor = [
+        CSRRS(5, 0x142, 0), // scause
+        CSRRS(6, 0x141, 0), // sepc
+        ADDI(6, 6, 4),
+        CSRRW(0, 0x141, 6),
+        SRET(),
+    ];
+    let machine = [
+        CSRRS(8, 0x342, 0), // mcause
+        CSRRS(9, 0x300, 0), // mstatus
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x100, &user);
+    memory.load(0x200, &supervisor);
+    memory.load(0x300, &machine);
+    let mut conf = config("SUZicsr");
+    conf.privileged = true;
+    let mut rv64 = RV64I::new([0; 32], 0, conf, memory);
+
+    for _ in 0..program.len() {
+        rv64.single_step();
+    }
+    assert_eq!(rv64.mode, PrivilegeMode::User);
+    assert_eq!(rv64.pc, 0x100);
+
+    rv64.single_step();
+    assert_eq!(rv64.mode, PrivilegeMode::Supervisor);
+    assert_eq!(rv64.pc, 0x200);
+
+    for _ in 0..supervisor.len() {
+        rv64.single_step();
+    }
+    assert_eq!((rv64.x[5], rv64.x[6]), (8, 0x104));
+    assert_eq!(rv64.mode, PrivilegeMode::User);
+    assert_eq!(rv64.pc, 0x104);
+
+    rv64.single_step();
+    assert_eq!(rv64.mode, PrivilegeMode::Machine);
+    assert_eq!(rv64.pc, 0x300);
+    assert_eq!(rv64.read_csr_by_name("mtval"), Some(CSRRS(10, 0x300, 0) as u64));
+
+    for _ in 0..machine.len() {
+        rv64.single_step();
+    }
+    assert_eq!(rv64.x[8], 2);
+    assert_eq!(rv64.x[9] as u64 & 0xF_0000_1800, 0xA_0000_0000); // MPP = U, SXL = UXL = 64
+
+    // Delegated supervisor timer interrupt, masked in S-mode while sstatus.SIE is clear.
+    assert!(rv64.write_csr_by_name("mideleg", 0x20));
+    assert!(rv64.write_csr_by_name("mie", 0x20));
+    rv64.mode = PrivilegeMode::Supervisor;
+    rv64.pc = 0x200;
+    rv64.set_interrupt_pending(Traps::SupervisorTimerInterrupt, true);
+    rv64.single_step();
+    assert_eq!(rv64.pc, 0x204);
+
+    rv64.mode = PrivilegeMode::User;
+    rv64.single_step();
+    assert_eq!(rv64.mode, PrivilegeMode::Supervisor);
+    assert_eq!(rv64.read_csr_by_name("scause"), Some(0x8000_0000_0000_0005));
+    assert_eq!(rv64.read_csr_by_name("sepc"), Some(0x204));
+    assert_eq!(rv64.read_csr_by_name("sip"), Some(0x20));
+}
+
+const PTE_V: u64 = 0x01;
+const PTE_R: u64 = 0x02;
+const PTE_W: u64 = 0x04;
+const PTE_X: u64 = 0x08;
+const PTE_U: u64 = 0x10;
+const PTE_A: u64 = 0x40;
+const PTE_D: u64 = 0x80;
+
+fn pte(addr: u64, flags: u64) -> u64 {
+    addr >> 12 << 10 | flags | PTE_V
+}
+
+#[test]
+fn virtual_memory_sv39() {
+    let program = [
+        LUI(1, 0x11000),
+        ADDI(2, 0, 42),
+        SD(1, 2, 0),
+        LD(3, 1, 0),
+        LUI(4, 0x205000),
+        LD(5, 4, 0),      // megapage
+        LUI(6, 0x12000),
+        SD(6, 2, 0),      // read-only page
+        LUI(7, 0x14000),
+        LD(8, 7, 0),      // user page
+        LUI(9, 0x15000),
+        LD(10, 9, 0),     // execute-only page
+        LUI(11, 0x13000),
+        LD(12, 11, 0),    // unmapped page
+        LUI(13, 0x16000),
+        LD(14, 13, 0x88), // PTE of the data page
+        SD(13, 15, 0x88),
+        LD(16, 1, 0),     // stale TLB entry
+        SFENCE_VMA(0, 0),
+        LD(17, 1, 0),
+    ];
+    let mut memory = Memory::new(&[]);
+    memory.load(0x4000, &program);
+    memory.load_64(0x1000, pte(0x2000, 0));                       // root
+    memory.load_64(0x2000, pte(0x3000, 0));                       // level 1
+    memory.load_64(0x2008, pte(0, PTE_R | PTE_A));                // megapage at 0x200000
+    memory.load_64(0x3000 + 0x10 * 8, pte(0x4000, PTE_R | PTE_X | PTE_A));
+    memory.load_64(0x3000 + 0x11 * 8, pte(0x5000, PTE_R | PTE_W));
+    memory.load_64(0x3000 + 0x12 * 8, pte(0x6000, PTE_R | PTE_A));
+    memory.load_64(0x3000 + 0x14 * 8, pte(0x7000, PTE_R | PTE_U | PTE_A));
+    memory.load_64(0x3000 + 0x15 * 8, pte(0x7000, PTE_X | PTE_A));
+    memory.load_64(0x3000 + 0x16 * 8, pte(0x3000, PTE_R | PTE_W | PTE_A | PTE_D));
+    memory.load_64(0x6000, 7);
+    memory.load_64(0x7000, 13);
+    let mut conf = config("SUZicsr");
+    conf.privileged = true;
+    let mut rv64 = RV64I::new([0; 32], 0x10000, conf, memory);
+    rv64.x[15] = pte(0x6000, PTE_R | PTE_W | PTE_A | PTE_D) as i64;
+
+    assert!(rv64.write_csr_by_name("mtvec", 0x8000));
+    assert!(rv64.write_csr_by_name("satp", 0xA << 60 | 1)); // Sv57 is not supported
+    assert_eq!(rv64.read_csr_by_name("satp"), Some(0));
+    assert!(rv64.write_csr_by_name("satp", 8 << 60 | 1));
+    rv64.mode = PrivilegeMode::Supervisor;
+
+    for _ in 0..6 {
+        rv64.single_step();
+    }
+    assert_eq!((rv64.x[3], rv64.x[5]), (42, 42));
+
+    let fault = |rv64: &mut RV64I<Memory>, cause: u64, tval: u64| {
+        let pc = rv64.pc;
+        rv64.single_step();
+        assert_eq!(rv64.mode, PrivilegeMode::Machine);
+        assert_eq!(rv64.pc, 0x8000);
+        assert_eq!(rv64.read_csr_by_name("mcause"), Some(cause));
+        assert_eq!(rv64.read_csr_by_name("mepc"), Some(pc));
+        assert_eq!(rv64.read_csr_by_name("mtval"), Some(tval));
+        rv64.mode = PrivilegeMode::Supervisor;
+        rv64.pc = pc;
+    };
+
+    rv64.single_step();
+    fault(&mut rv64, 15, 0x12000);
+    rv64.pc += 4;
+
+    rv64.single_step();
+    fault(&mut rv64, 13, 0x14000);
+    let mstatus = rv64.read_csr_by_name("mstatus").unwrap();
+    assert!(rv64.write_csr_by_name("mstatus", mstatus | 1 << 18)); // SUM
+    rv64.single_step();
+    assert_eq!(rv64.x[8], 13);
+
+    rv64.single_step();
+    fault(&mut rv64, 13, 0x15000);
+    assert!(rv64.write_csr_by_name("mstatus", mstatus | 1 << 19)); // MXR
+    rv64.single_step();
+    assert_eq!(rv64.x[10], 13);
+
+    rv64.single_step();
+    fault(&mut rv64, 13, 0x13000);
+    rv64.pc += 4;
+
+    for _ in 0..6 {
+        rv64.single_step();
+    }
+    assert_eq!(rv64.x[14] as u64 & (PTE_A | PTE_D), PTE_A | PTE_D);
+    assert_eq!((rv64.x[16], rv64.x[17]), (42, 7));
+
+    // Instruction fetch from a non-executable page.
+    rv64.pc = 0x11000;
+    fault(&mut rv64, 12, 0x11000);
+
+    // U-mode cannot access supervisor pages, and M-mode is not translated.
+    rv64.mode = PrivilegeMode::User;
+    rv64.pc = 0x10000;
+    rv64.single_step();
+    assert_eq!(rv64.read_csr_by_name("mcause"), Some(12));
+    assert_eq!(rv64.pc, 0x8000);
+}
+
+#[test]
+fn virtual_memory_sv32() {
+    let program = [
+        LUI(1, 0x11000),
+        LW(2, 1, 0),
+        JALR(0, 1, 0),
+    ];
+    let mut memory = Memory::new(&[]);
+    memory.load(0x4000, &program);
+    memory.load(0x1000, &[pte(0x2000, 0) as u32]);
+    memory.load(0x2000 + 0x10 * 4, &[pte(0x4000, PTE_X | PTE_A) as u32]);
+    memory.load(0x2000 + 0x11 * 4, &[pte(0x5000, PTE_R) as u32]);
+    memory.load(0x5000, &[0x1234_5678]);
+    let mut conf = config("SUZicsr");
+    conf.privileged = true;
+    let mut rv32 = RV32I::new([0; 32], 0x10000, conf, memory);
+
+    assert!(rv32.write_csr_by_name("mtvec", 0x8000));
+    assert!(rv32.write_csr_by_name("satp", 0x8000_0001));
+    assert_eq!(rv32.read_csr_by_name("satp"), Some(0x8000_0001));
+    rv32.mode = PrivilegeMode::Supervisor;
+
+    rv32.single_step();
+    rv32.single_step();
+    assert_eq!(rv32.x[2], 0x1234_5678);
+    assert_eq!(rv32.pc, 0x10008);
+
+    rv32.single_step();
+    rv32.single_step();
+    assert_eq!(rv32.mode, PrivilegeMode::Machine);
+    assert_eq!(rv32.read_csr_by_name("mcause"), Some(12));
+    assert_eq!(rv32.read_csr_by_name("mepc"), Some(0x11000));
+    assert_eq!(rv32.read_csr_by_name("mtval"), Some(0x11000));
+
+    // The code page is not accessible from U-mode.
+    rv32.mode = PrivilegeMode::User;
+    rv32.pc = 0x10000;
+    rv32.single_step();
+    assert_eq!(rv32.read_csr_by_name("mcause"), Some(12));
+    assert_eq!(rv32.read_csr_by_name("mepc"), Some(0x10000));
+}
+
+#[test]
+fn physical_memory_protection() {
+    let program = [
+        LUI(1, 0x2000),
+        LW(2, 1, 0),
+        SW(2, 1, 0),       // read-only region
+        LW(3, 1, -2i32 as u32), // partially matching
+        LUI(4, 0x4000),
+        LW(5, 4, 0),       // no matching region
+        LUI(6, 0x3000),
+        SW(2, 6, 0),
+        SW(2, 6, 4),       // locked read-only region
+        SW(2, 4, 0),
+    ];
+    let mut memory = Memory::new(&program);
+    memory.load(0x2000, &[0xABCD]);
+    let mut conf = config("SUZicsr");
+    conf.privileged = true;
+    conf.pmp_regions = 16;
+    let mut rv32 = RV32I::new([0; 32], 0, conf, memory);
+
+    assert!(rv32.write_csr_by_name("mtvec", 0x800));
+    assert!(rv32.write_csr_by_name("pmpaddr0", 0x400));  // TOR [0, 0x1000)
+    assert!(rv32.write_csr_by_name("pmpaddr1", 0x9FF));  // NAPOT [0x2000, 0x3000)
+    assert!(rv32.write_csr_by_name("pmpaddr2", 0xC00));  // NA4 0x3000
+    assert!(rv32.write_csr_by_name("pmpaddr3", 0xC01));  // NA4 0x3004
+    assert!(rv32.write_csr_by_name("pmpcfg0", 0x9193_190A)); // W TOR, R NAPOT, locked RW NA4, locked R NA4
+    assert_eq!(rv32.read_csr_by_name("pmpcfg0"), Some(0x9193_1908)); // W without R is reserved
+    assert!(rv32.write_csr_by_name("pmpcfg0", 0));
+    assert!(rv32.write_csr_by_name("pmpaddr3", 0));
+    assert_eq!(rv32.read_csr_by_name("pmpcfg0"), Some(0x9193_0000));
+    assert_eq!(rv32.read_csr_by_name("pmpaddr3"), Some(0xC01));
+    assert!(rv32.write_csr_by_name("pmpcfg0", 0x190D));
+    assert_eq!(rv32.read_csr_by_name("pmpcfg0"), Some(0x9193_190D));
+    assert_eq!(rv32.read_csr_by_name("pmpaddr63"), Some(0));
+    assert!(rv32.write_csr_by_name("pmpaddr16", 0x1234));
+    assert_eq!(rv32.read_csr_by_name("pmpaddr16"), Some(0)); // not implemented
+    rv32.mode = PrivilegeMode::User;
+
+    let fault = |rv32: &mut RV32I<Memory>, cause: u32, tval: u32| {
+        let pc = rv32.pc;
+        rv32.single_step();
+        assert_eq!(rv32.mode, PrivilegeMode::Machine);
+        assert_eq!(rv32.pc, 0x800);
+        assert_eq!(rv32.read_csr_by_name("mcause"), Some(cause));
+        assert_eq!(rv32.read_csr_by_name("mepc"), Some(pc));
+        assert_eq!(rv32.read_csr_by_name("mtval"), Some(tval));
+        rv32.pc = pc + 4;
+    };
+
+    rv32.single_step();
+    rv32.single_step();
+    assert_eq!(rv32.x[2], 0xABCD);
+    rv32.mode = PrivilegeMode::User;
+    fault(&mut rv32, 7, 0x2000);
+    rv32.mode = PrivilegeMode::User;
+    fault(&mut rv32, 5, 0x1FFE);
+    rv32.mode = PrivilegeMode::User;
+    rv32.single_step();
+    fault(&mut rv32, 5, 0x4000);
+
+    // Locked regions also apply to M-mode, unlocked ones do not.
+    rv32.single_step();
+    rv32.single_step();
+    fault(&mut rv32, 7, 0x3004);
+    rv32.single_step();
+    assert_eq!(rv32.pc, 0x28);
+
+    // Instruction fetch outside of an executable region.
+    rv32.mode = PrivilegeMode::User;
+    rv32.pc = 0x2000;
+    fault(&mut rv32, 1, 0x2000);
+
+    // Without PMP entries, every access is allowed and the registers are hardwired to 0.
+    let mut conf = config("SUZicsr");
+    conf.privileged = true;
+    let mut memory = Memory::new(&program[..2]);
+    memory.load(0x2000, &[0xABCD]);
+    let mut rv64 = RV64I::new([0; 32], 0, conf, memory);
+    assert!(rv64.write_csr_by_name("pmpaddr0", 0x400));
+    assert_eq!(rv64.read_csr_by_name("pmpaddr0"), Some(0));
+    assert_eq!(rv64.read_csr_by_name("pmpcfg1"), None);
+    rv64.mode = PrivilegeMode::User;
+    rv64.single_step();
+    rv64.single_step();
+    assert_eq!(rv64.x[2], 0xABCD);
+
+    // Accesses ending at the top of the address space, which is backed by RAM.
+    let top = |pmp_regions: usize| {
+        let mut memory = bus(&[LD(1, 0, -8i32 as u32)]);
+        memory.map_ram(0xFFFF_FFFF_FFFF_F000, 0x1000).unwrap();
+        memory.memory_mut(0xFFFF_FFFF_FFFF_FFF8, 8).unwrap().copy_from_slice(&0x1234u64.to_le_bytes());
+        let mut conf = config("SUZicsr");
+        conf.privileged = true;
+        conf.pmp_regions = pmp_regions;
+        let mut rv64 = RV64I::new([0; 32], 0, conf, memory);
+        assert!(rv64.write_csr_by_name("mtvec", 0x800));
+        rv64
+    };
+    for pmp_regions in [0, 16] {
+        let mut rv64 = top(pmp_regions);
+        rv64.single_step();
+        assert_eq!((rv64.pc, rv64.x[1]), (4, 0x1234));
+    }
+    // No entry matches it in U-mode.
+    let mut rv64 = top(16);
+    assert!(rv64.write_csr_by_name("pmpaddr0", 0x400));
+    assert!(rv64.write_csr_by_name("pmpcfg0", 0x0D)); // RX TOR [0, 0x1000)
+    rv64.mode = PrivilegeMode::User;
+    rv64.single_step();
+    assert_eq!(rv64.pc, 0x800);
+    assert_eq!(rv64.read_csr_by_name("mcause"), Some(5));
+    assert_eq!(rv64.read_csr_by_name("mtval"), Some(-8i64 as u64));
+}
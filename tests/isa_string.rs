@@ -0,0 +1,73 @@
+use dyriscvic::common::isa_string::*;
+
+fn names(isa: &ISAString) -> Vec<&str> {
+    isa.extensions.iter().map(|ext| ext.name.as_str()).collect()
+}
+
+#[test]
+fn parse_full_strings() {
+    let isa = ISAString::parse("rv64imafdc_zicsr_zifencei").unwrap();
+    assert_eq!(isa.xlen, Some(64));
+    assert_eq!(isa.base, Some('I'));
+    assert_eq!(names(&isa), ["M", "A", "F", "D", "C", "Zicsr", "Zifencei"]);
+    assert_eq!(isa.to_string(), "rv64imafdc_zicsr_zifencei");
+
+    let isa = ISAString::parse("RV32GC").unwrap();
+    assert_eq!(isa, ISAString::parse("rv32imafdc_zicsr_zifencei").unwrap());
+    assert_eq!(ISAString::parse("rv64g_zicsr").unwrap(), ISAString::parse("rv64g").unwrap());
+
+    let isa = ISAString::parse("rv32e").unwrap();
+    assert_eq!((isa.xlen, isa.base), (Some(32), Some('E')));
+    assert!(isa.extensions.is_empty());
+}
+
+#[test]
+fn parse_versions() {
+    let isa = ISAString::parse("rv32i2p1m2_zicsr2p0_zifencei").unwrap();
+    assert_eq!(isa.extensions[0], Extension { name: String::from("M"), version: Some((2, 0)) });
+    assert_eq!(isa.extensions[1], Extension { name: String::from("Zicsr"), version: Some((2, 0)) });
+    assert_eq!(isa.extensions[2], Extension { name: String::from("Zifencei"), version: None });
+    assert_eq!(isa.to_string(), "rv32im2p0_zicsr2p0_zifencei");
+}
+
+#[test]
+fn parse_extension_lists() {
+    let isa = ISAString::parse("SUZicsr").unwrap();
+    assert_eq!((isa.xlen, isa.base), (None, None));
+    assert_eq!(names(&isa), ["S", "U", "Zicsr"]);
+
+    let isa = ISAString::parse("FD").unwrap();
+    assert_eq!(names(&isa), ["F", "D", "Zicsr"]);
+    assert!(isa.has("zicsr"));
+    assert!(!isa.has("I"));
+
+    assert!(ISAString::parse("").unwrap().extensions.is_empty());
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(ISAString::parse("rv128i"), Err(ISAStringError::InvalidXlen(String::from("128"))));
+    assert_eq!(ISAString::parse("rv64mafd"), Err(ISAStringError::MissingBase));
+    assert_eq!(ISAString::parse("rv64imm"), Err(ISAStringError::DuplicateExtension(String::from("M"))));
+    assert_eq!(ISAString::parse("rv64imv"), Err(ISAStringError::UnsupportedExtension(String::from("V"))));
+    assert_eq!(ISAString::parse("rv64i_zicrs"), Err(ISAStringError::UnsupportedExtension(String::from("Zicrs"))));
+    assert_eq!(ISAString::parse("rv64i_svinval"), Err(ISAStringError::UnsupportedExtension(String::from("Svinval"))));
+    assert_eq!(ISAString::parse("rv64i_xfoo"), Err(ISAStringError::UnsupportedExtension(String::from("Xfoo"))));
+    assert_eq!(ISAString::parse("rv64ime"), Err(ISAStringError::UnexpectedCharacter('e', 6)));
+    assert_eq!(ISAString::parse("rv64i m"), Err(ISAStringError::UnexpectedCharacter(' ', 5)));
+    assert_eq!(ISAString::parse("rv32emd"), Err(ISAStringError::IncompatibleBase { extension: String::from("D"), base: 'E' }));
+}
+
+#[test]
+fn for_hart() {
+    let isa = ISAString::parse("MZicsr").unwrap().for_hart(32, 16).unwrap();
+    assert_eq!(isa.to_string(), "rv32em_zicsr");
+    assert!(isa.has("E"));
+
+    assert_eq!(ISAString::parse("rv32imac").unwrap().for_hart(64, 32), Err(ISAStringError::XlenMismatch { isa: 32, hart: 64 }));
+    assert_eq!(ISAString::parse("rv64e").unwrap().for_hart(64, 32), Err(ISAStringError::BaseMismatch { base: 'E', registers: 32 }));
+    assert_eq!(ISAString::parse("rv32i").unwrap().for_hart(32, 16), Err(ISAStringError::BaseMismatch { base: 'I', registers: 16 }));
+    assert!(ISAString::parse("rv64gc").unwrap().for_hart(64, 32).is_ok());
+    assert!(ISAString::parse("rv64emafc").unwrap().for_hart(64, 16).is_ok());
+    assert_eq!(ISAString::parse("D").unwrap().for_hart(64, 16), Err(ISAStringError::IncompatibleBase { extension: String::from("D"), base: 'E' }));
+}
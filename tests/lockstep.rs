@@ -0,0 +1,128 @@
+use std::io;
+
+use dyriscvic::bus::Bus;
+use dyriscvic::lockstep::*;
+use dyriscvic::rvi::{*, assembler::*, rvfi::RVFI_PACKET_SIZE};
+
+mod common;
+use common::{bus, config, Buffer};
+
+fn program() -> Bus {
+    bus(&[ADDI(5, 0, 0x100), ADDI(6, 6, 1), SW(6, 5, 0), LW(7, 5, 0), AMOADD_W(8, 5, 6, false, false), EBREAK()])
+}
+
+/// Runs the program on a hart with the given initial registers and returns its commit log.
+fn reference(x: [i32; 32]) -> Vec<u8> {
+    let buffer = Buffer::default();
+    let mut rv32 = RV32I::new(x, 0, config("A"), program());
+    rv32.set_commit_log(buffer.clone(), true);
+    rv32.run(5);
+    buffer.contents()
+}
+
+#[test]
+fn spike_log() {
+    let log = "\
+core   0: 0x0000000000001000 (0x00000297) auipc   t0, 0x0
+core   0: 3 0x00001000 (0x00000297) x5  0x00001000
+core   0: 3 0x80000000 (0x0062a023) mem 0x80001000 0x0000abcd
+core   0: 1 0x0000000080000004 (0x4501) x 10 0x0000000000000000 c1_fflags 0x00000000
+core   0: exception trap_breakpoint, epc 0x0000000080000006
+core   0: 3 0x80000008 (0x0002a303) x6  0x0000abcd mem 0x80001000
+core   0: 3 0x8000000c (0x00000053) f0  0x3f800000
+";
+    let commits = SpikeLog::new(log.as_bytes()).collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(commits, [
+        Commit { pc: 0x1000, insn: Some(0x297), rd: Some((Register::X(5), 0x1000)), ..Commit::default() },
+        Commit { pc: 0x8000_0000, insn: Some(0x0062_a023), stores: vec![Store { addr: 0x8000_1000, size: 4, data: 0xabcd }], ..Commit::default() },
+        Commit { pc: 0x8000_0004, insn: Some(0x4501), rd: Some((Register::X(10), 0)), ..Commit::default() },
+        Commit { pc: 0x8000_0008, insn: Some(0x0002_a303), rd: Some((Register::X(6), 0xabcd)), ..Commit::default() },
+        Commit { pc: 0x8000_000c, insn: Some(0x53), rd: Some((Register::F(0), 0x3f80_0000)), ..Commit::default() },
+    ]);
+
+    assert!(SpikeLog::new("core   0: 3 0x80000000 (0x0062a023) y1 0x0\n".as_bytes()).next().unwrap().is_err());
+}
+
+#[test]
+fn rvfi_log() {
+    let mut packet = [0; RVFI_PACKET_SIZE];
+    packet[8..16].copy_from_slice(&0x8000_0000u64.to_le_bytes());
+    packet[24..32].copy_from_slice(&0x0062_a023u64.to_le_bytes());
+    packet[56..64].copy_from_slice(&0x8000_1000u64.to_le_bytes());
+    packet[72..80].copy_from_slice(&0xabcd_0000u64.to_le_bytes());
+    packet[81] = 0b1100;
+    let mut stream = packet.to_vec();
+    packet[81] = 0;
+    packet[84] = 6;
+    packet[48..56].copy_from_slice(&7u64.to_le_bytes());
+    packet[85] = 1;
+    stream.extend_from_slice(&packet);
+    packet[86] = 1;
+    stream.extend_from_slice(&packet);
+    stream.extend_from_slice(&[0; RVFI_PACKET_SIZE]);
+
+    let commits = RvfiLog::new(stream.as_slice()).collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(commits, [
+        Commit { pc: 0x8000_0000, insn: Some(0x0062_a023), stores: vec![Store { addr: 0x8000_1002, size: 2, data: 0xabcd }], ..Commit::default() },
+        Commit { pc: 0x8000_0000, insn: Some(0x0062_a023), rd: Some((Register::X(6), 7)), trap: true, ..Commit::default() },
+    ]);
+}
+
+#[test]
+fn lockstep() {
+    let log = reference([0; 32]);
+    let mut rv32 = RV32I::new([0; 32], 0, config("A"), program());
+    let mut lockstep = Lockstep::new(SpikeLog::new(log.as_slice()), 4);
+    assert_eq!(lockstep.run(&mut rv32, 2).unwrap(), 2);
+    assert_eq!(lockstep.run(&mut rv32, 100).unwrap(), 5);
+    assert_eq!(lockstep.checked(), 5);
+
+    // The reference started with x6 = 1.
+    let mut x = [0; 32];
+    x[6] = 1;
+    let log = reference(x);
+    let mut rv32 = RV32I::new([0; 32], 0, config("A"), program());
+    let divergence = match Lockstep::new(SpikeLog::new(log.as_slice()), 4).run(&mut rv32, 100) {
+        Err(LockstepError::Divergence(divergence)) => divergence,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(divergence.index, 1);
+    assert_eq!(divergence.mismatch, Mismatch::Register);
+    assert_eq!(divergence.expected.rd, Some((Register::X(6), 2)));
+    assert_eq!(divergence.actual.rd, Some((Register::X(6), 1)));
+    assert!(divergence.report.contains("*  t1 0x0000000000000001"));
+    assert!(divergence.report.contains("Last 2 instructions of the hart:"));
+    assert!(divergence.report.contains("addi t1, t1, 1"));
+
+    // Wrong store only.
+    let log = String::from_utf8(reference([0; 32])).unwrap().replace("mem 0x00000100 0x00000001", "mem 0x00000100 0x00000002");
+    let mut rv32 = RV32I::new([0; 32], 0, config("A"), program());
+    let divergence = match Lockstep::new(SpikeLog::new(log.as_bytes()), 1).run(&mut rv32, 100) {
+        Err(LockstepError::Divergence(divergence)) => divergence,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!(divergence.index, 2);
+    assert_eq!(divergence.mismatch, Mismatch::MemoryWrite);
+    assert!(divergence.report.contains("Last 1 instructions of the hart:"));
+
+    // Wrong control flow, with instructions of a boot ROM to skip.
+    let log = "core   0: 3 0x00001000 (0x00000297) x5  0x00001000\ncore   0: 3 0x00000000 (0x10000293) x5  0x00000100\ncore   0: 3 0x00000008 (0x00000013)\n";
+    let mut rv32 = RV32I::new([0; 32], 0, config("A"), program());
+    match Lockstep::new(SpikeLog::new(log.as_bytes()), 0).run(&mut rv32, 100) {
+        Err(LockstepError::Divergence(divergence)) => assert_eq!((divergence.index, divergence.mismatch), (1, Mismatch::Pc)),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn trap_loop() {
+    // The illegal instruction at 0 is also the trap handler.
+    let log = "core   0: 3 0x00000000 (0x00000013)\n";
+    let mut rv32 = RV32I::new([0; 32], 0, RVConfig { privileged: true, ..config("") }, bus(&[]));
+    let divergence = match Lockstep::new(SpikeLog::new(log.as_bytes()), 2).run(&mut rv32, 100) {
+        Err(LockstepError::Divergence(divergence)) => divergence,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert_eq!((divergence.index, divergence.mismatch), (0, Mismatch::Trap));
+    assert!(divergence.actual.trap);
+}
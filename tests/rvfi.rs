@@ -0,0 +1,76 @@
+use std::convert::TryInto;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use dyriscvic::lockstep::{Commit, Register, RvfiLog, Store};
+use dyriscvic::rvi::{*, assembler::*, rvfi::*};
+
+mod common;
+use common::{bus, config, Buffer};
+
+#[test]
+fn rvfi_records() {
+    let program = [
+        ADDI(5, 0, 0x100),
+        SH(6, 5, 2),
+        LW(7, 5, 0),
+        AMOADD_W(8, 5, 6, false, false),
+        FMV_W_X(1, 6),
+        EBREAK(),
+    ];
+    let mut x = [0; 32];
+    x[6] = -2;
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let mut rv32 = RV32I::new(x, 0, config("AF"), bus(&program));
+    let shared = records.clone();
+    rv32.set_rvfi_callback(move |record| shared.lock().unwrap().push(*record));
+    rv32.run(6);
+
+    let base = RvfiRecord { mode: 3, ixl: 1, ..RvfiRecord::default() };
+    assert_eq!(*records.lock().unwrap(), [
+        RvfiRecord { order: 0, insn: ADDI(5, 0, 0x100) as u64, rd_addr: 5, rd_wdata: 0x100, pc_rdata: 0, pc_wdata: 4, ..base },
+        RvfiRecord {
+            order: 1, insn: SH(6, 5, 2) as u64, rs1_addr: 5, rs1_rdata: 0x100, rs2_addr: 6, rs2_rdata: 0xFFFF_FFFE,
+            pc_rdata: 4, pc_wdata: 8, mem_addr: 0x102, mem_wmask: 0b11, mem_wdata: 0xFFFE, ..base
+        },
+        RvfiRecord {
+            order: 2, insn: LW(7, 5, 0) as u64, rs1_addr: 5, rs1_rdata: 0x100, rd_addr: 7, rd_wdata: 0xFFFE_0000,
+            pc_rdata: 8, pc_wdata: 12, mem_addr: 0x100, mem_rmask: 0b1111, mem_rdata: 0xFFFE_0000, ..base
+        },
+        RvfiRecord {
+            order: 3, insn: AMOADD_W(8, 5, 6, false, false) as u64, rs1_addr: 5, rs1_rdata: 0x100, rs2_addr: 6, rs2_rdata: 0xFFFF_FFFE,
+            rd_addr: 8, rd_wdata: 0xFFFE_0000, pc_rdata: 12, pc_wdata: 16,
+            mem_addr: 0x100, mem_rmask: 0b1111, mem_rdata: 0xFFFE_0000, mem_wmask: 0b1111, mem_wdata: 0xFFFD_FFFE, ..base
+        },
+        RvfiRecord { order: 4, insn: FMV_W_X(1, 6) as u64, rs1_addr: 6, rs1_rdata: 0xFFFF_FFFE, pc_rdata: 16, pc_wdata: 20, ..base },
+        RvfiRecord { order: 5, insn: EBREAK() as u64, trap: true, pc_rdata: 20, pc_wdata: 24, ..base },
+    ]);
+
+    rv32.clear_rvfi_callback();
+    rv32.pc = 0;
+    rv32.single_step();
+    assert_eq!(records.lock().unwrap().len(), 6);
+}
+
+#[test]
+fn rvfi_dump() {
+    let program = [ADDI(5, 0, -1i32 as u32), SD(0, 5, 0x100)];
+    let buffer = Buffer::default();
+    let mut rv64 = RV64I::new([0; 32], 0, config(""), bus(&program));
+    rv64.set_rvfi_dump(buffer.clone());
+    rv64.run(2);
+    rv64.clear_rvfi_callback();
+
+    let dump = buffer.contents();
+    assert_eq!(dump.len(), 3 * RVFI_PACKET_SIZE);
+    let record = RvfiRecord::from_packet(dump[..RVFI_PACKET_SIZE].try_into().unwrap());
+    assert_eq!(record, RvfiRecord { insn: ADDI(5, 0, -1i32 as u32) as u64, rd_addr: 5, rd_wdata: u64::MAX, pc_wdata: 4, ..RvfiRecord::default() });
+    assert_eq!(record.to_packet()[..], dump[..RVFI_PACKET_SIZE]);
+    assert!(RvfiRecord::from_packet(dump[2 * RVFI_PACKET_SIZE..].try_into().unwrap()).halt);
+
+    let commits = RvfiLog::new(dump.as_slice()).collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(commits, [
+        Commit { pc: 0, insn: Some(ADDI(5, 0, -1i32 as u32)), rd: Some((Register::X(5), u64::MAX)), ..Commit::default() },
+        Commit { pc: 4, insn: Some(SD(0, 5, 0x100)), stores: vec![Store { addr: 0x100, size: 8, data: u64::MAX }], ..Commit::default() },
+    ]);
+}
@@ -0,0 +1,149 @@
+use dyriscvic::common::softfloat::*;
+
+const S: Format = Format::SINGLE;
+const D: Format = Format::DOUBLE;
+
+fn env(rm: RoundingMode) -> Softfloat {
+    Softfloat::new(rm)
+}
+
+#[test]
+fn rounding_modes() {
+    // 1 + 2^-24 is a tie between 1 and 1 + 2^-23.
+    let one = 0x3F80_0000;
+    let half_ulp = 0x3380_0000;
+    let expected = [
+        (RoundingMode::NearestEven, 0x3F80_0000),
+        (RoundingMode::TowardZero, 0x3F80_0000),
+        (RoundingMode::Down, 0x3F80_0000),
+        (RoundingMode::Up, 0x3F80_0001),
+        (RoundingMode::NearestMaxMagnitude, 0x3F80_0001),
+    ];
+    for (rm, result) in expected {
+        let mut fp = env(rm);
+        assert_eq!(fp.add(S, one, half_ulp), result, "{:?}", rm);
+        assert_eq!(fp.flags, FLAG_NX, "{:?}", rm);
+    }
+
+    // Negative values round the other way for directed modes.
+    let mut fp = env(RoundingMode::Down);
+    assert_eq!(fp.sub(S, one | 1 << 31, half_ulp), 0xBF80_0001);
+    let mut fp = env(RoundingMode::Up);
+    assert_eq!(fp.sub(S, one | 1 << 31, half_ulp), 0xBF80_0000);
+}
+
+#[test]
+fn exact_zero_sign() {
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.sub(D, 0x4000_0000_0000_0000, 0x4000_0000_0000_0000), 0);
+    let mut fp = env(RoundingMode::Down);
+    assert_eq!(fp.sub(D, 0x4000_0000_0000_0000, 0x4000_0000_0000_0000), 1 << 63);
+    assert_eq!(fp.fused_mul_add(S, 0x8000_0000, 0x3F80_0000, 0), 0x8000_0000);
+    assert_eq!(fp.flags, 0);
+}
+
+#[test]
+fn overflow_and_underflow() {
+    let max = 0x7F7F_FFFF;
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.mul(S, max, 0x4000_0000), 0x7F80_0000);
+    assert_eq!(fp.flags, FLAG_OF | FLAG_NX);
+
+    let mut fp = env(RoundingMode::TowardZero);
+    assert_eq!(fp.mul(S, max, 0x4000_0000), max);
+    assert_eq!(fp.flags, FLAG_OF | FLAG_NX);
+
+    // Smallest normal / 2 is an exact subnormal: no underflow.
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.mul(S, 0x0080_0000, 0x3F00_0000), 0x0040_0000);
+    assert_eq!(fp.flags, 0);
+
+    // Tininess is detected after rounding: 2^-126 * (1 - 2^-46) rounds to 2^-126 and does not underflow...
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.mul(S, 0x3F7F_FFFE, 0x0080_0001), 0x0080_0000);
+    assert_eq!(fp.flags, FLAG_NX);
+
+    // ...but 2^-126 - 2^-150 is tiny even though it also rounds to 2^-126.
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.mul(S, 0x3F7F_FFFF, 0x0080_0000), 0x0080_0000);
+    assert_eq!(fp.flags, FLAG_UF | FLAG_NX);
+}
+
+#[test]
+fn invalid_operations() {
+    let snan = 0x7F80_0001;
+    let qnan = 0x7FC0_0001;
+    let inf = 0x7F80_0000;
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.add(S, qnan, 0), S.canonical_nan());
+    assert_eq!(fp.flags, 0);
+    assert_eq!(fp.add(S, snan, 0), S.canonical_nan());
+    assert_eq!(fp.flags, FLAG_NV);
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.fused_mul_add(S, inf, 0, qnan), S.canonical_nan());
+    assert_eq!(fp.flags, FLAG_NV);
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.div(S, 0x3F80_0000, 0), inf);
+    assert_eq!(fp.flags, FLAG_DZ);
+    assert_eq!(fp.sqrt(S, 0x8000_0000), 0x8000_0000);
+    assert_eq!(fp.flags, FLAG_DZ);
+
+    // min/max return the non-NaN operand, -0 is smaller than +0.
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.min(S, 0, 0x8000_0000), 0x8000_0000);
+    assert_eq!(fp.max(S, 0x8000_0000, 0), 0);
+    assert_eq!(fp.max(S, qnan, 0x3F80_0000), 0x3F80_0000);
+    assert_eq!(fp.flags, 0);
+    assert_eq!(fp.min(S, snan, snan), S.canonical_nan());
+    assert_eq!(fp.flags, FLAG_NV);
+
+    // Quiet and signaling comparisons.
+    let mut fp = env(RoundingMode::NearestEven);
+    assert!(!fp.eq(S, qnan, qnan));
+    assert_eq!(fp.flags, 0);
+    assert!(!fp.le(S, qnan, qnan));
+    assert_eq!(fp.flags, FLAG_NV);
+}
+
+#[test]
+fn integer_conversions() {
+    let mut fp = env(RoundingMode::TowardZero);
+    assert_eq!(fp.to_int(S, 0xBF8C_CCCD, true, 32), -1i32 as u32 as u64); // -1.1
+    assert_eq!(fp.flags, FLAG_NX);
+
+    let mut fp = env(RoundingMode::TowardZero);
+    assert_eq!(fp.to_int(S, 0xBF8C_CCCD, false, 32), 0);
+    assert_eq!(fp.flags, FLAG_NV);
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.to_int(S, 0xBE99_999A, false, 32), 0); // -0.3 rounds to 0
+    assert_eq!(fp.flags, FLAG_NX);
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.to_int(D, D.canonical_nan(), true, 64), i64::MAX as u64);
+    assert_eq!(fp.to_int(D, 0xC3E0_0000_0000_0000, true, 64), i64::MIN as u64); // -2^63
+    assert_eq!(fp.to_int(D, 0x43F0_0000_0000_0000, false, 64), u64::MAX); // 2^64
+    assert_eq!(fp.flags, FLAG_NV);
+
+    let mut fp = env(RoundingMode::NearestEven);
+    assert_eq!(fp.from_int(S, 0x8000_0001, true, 32), 0xCEFF_FFFF + 1); // -2^31 + 1 rounds to -2^31
+    assert_eq!(fp.from_int(D, u64::MAX, true, 64), 0xBFF0_0000_0000_0000);
+    assert_eq!(fp.flags, FLAG_NX);
+}
+
+#[test]
+fn classify() {
+    assert_eq!(S.classify(0xFF80_0000), 1 << 0);
+    assert_eq!(S.classify(0xBF80_0000), 1 << 1);
+    assert_eq!(S.classify(0x8000_0001), 1 << 2);
+    assert_eq!(S.classify(0x8000_0000), 1 << 3);
+    assert_eq!(S.classify(0x0000_0000), 1 << 4);
+    assert_eq!(S.classify(0x0000_0001), 1 << 5);
+    assert_eq!(S.classify(0x3F80_0000), 1 << 6);
+    assert_eq!(S.classify(0x7F80_0000), 1 << 7);
+    assert_eq!(D.classify(0x7FF0_0000_0000_0001), 1 << 8);
+    assert_eq!(D.classify(0x7FF8_0000_0000_0000), 1 << 9);
+}
@@ -0,0 +1,73 @@
+use dyriscvic::rvi::{*, assembler::*};
+
+mod common;
+use common::{bus, config, Buffer};
+
+#[test]
+fn commit_log_rv32() {
+    let program = [
+        ADDI(5, 0, 0x100),
+        SW(5, 5, 2),
+        LW(6, 5, 2),
+        C_LI(7, -1i32 as u32) as u32 | (C_NOP() as u32) << 16,
+        CSRRW(0, 0x340, 5),
+        AMOSWAP_W(8, 5, 0, false, false),
+        ADDI(0, 0, 1),
+        EBREAK(),
+    ];
+    let buffer = Buffer::default();
+    let mut rv32 = RV32I::new([0; 32], 0, config("ACZicsr"), bus(&program));
+    rv32.set_commit_log(buffer.clone(), false);
+    assert!(rv32.run(10).is_some());
+
+    let expected = [
+        format!("core   0: 3 0x00000000 (0x{:08x}) x5  0x00000100", ADDI(5, 0, 0x100)),
+        format!("core   0: 3 0x00000004 (0x{:08x}) mem 0x00000102 0x00000100", SW(5, 5, 2)),
+        format!("core   0: 3 0x00000008 (0x{:08x}) x6  0x00000100 mem 0x00000102", LW(6, 5, 2)),
+        format!("core   0: 3 0x0000000c (0x{:04x}) x7  0xffffffff", C_LI(7, -1i32 as u32)),
+        format!("core   0: 3 0x0000000e (0x{:04x})", C_NOP()),
+        format!("core   0: 3 0x00000010 (0x{:08x}) c832_mscratch 0x00000100", CSRRW(0, 0x340, 5)),
+        format!("core   0: 3 0x00000014 (0x{:08x}) x8  0x01000000 mem 0x00000100 mem 0x00000100 0x00000000", AMOSWAP_W(8, 5, 0, false, false)),
+        format!("core   0: 3 0x00000018 (0x{:08x})", ADDI(0, 0, 1)),
+    ];
+    assert_eq!(buffer.take(), expected.join("\n") + "\n");
+
+    rv32.set_commit_log(buffer.clone(), true);
+    rv32.pc = 0;
+    rv32.single_step();
+    rv32.pc = 0x1C;
+    rv32.single_step();
+    rv32.pc = 0x2000;
+    rv32.single_step();
+    let expected = [
+        String::from("core   0: 0x0000000000000000 (0x10000293) addi t0, zero, 256"),
+        String::from("core   0: 3 0x00000000 (0x10000293) x5  0x00000100"),
+        format!("core   0: 0x000000000000001c (0x{:08x}) ebreak", EBREAK()),
+        String::from("core   0: exception trap_breakpoint, epc 0x000000000000001c"),
+        String::from("core   0:           tval 0x000000000000001c"),
+        String::from("core   0: exception trap_instruction_access_fault, epc 0x0000000000002000"),
+        String::from("core   0:           tval 0x0000000000002000"),
+    ];
+    assert_eq!(buffer.take(), expected.join("\n") + "\n");
+    assert!(rv32.take_commit_log().is_some());
+    rv32.pc = 0;
+    rv32.single_step();
+    assert_eq!(buffer.take(), "");
+}
+
+#[test]
+fn commit_log_rv64() {
+    let program = [ADDI(5, 0, -1i32 as u32), FMV_D_X(1, 5), FMV_W_X(2, 5), SD(0, 5, 0x100)];
+    let buffer = Buffer::default();
+    let mut rv64 = RV64I::new([0; 32], 0, config("D"), bus(&program));
+    rv64.set_commit_log(buffer.clone(), false);
+    rv64.run(4);
+
+    let expected = [
+        format!("core   0: 3 0x0000000000000000 (0x{:08x}) x5  0xffffffffffffffff", ADDI(5, 0, -1i32 as u32)),
+        format!("core   0: 3 0x0000000000000004 (0x{:08x}) f1  0xffffffffffffffff", FMV_D_X(1, 5)),
+        format!("core   0: 3 0x0000000000000008 (0x{:08x}) f2  0xffffffffffffffff", FMV_W_X(2, 5)),
+        format!("core   0: 3 0x000000000000000c (0x{:08x}) mem 0x0000000000000100 0xffffffffffffffff", SD(0, 5, 0x100)),
+    ];
+    assert_eq!(buffer.take(), expected.join("\n") + "\n");
+}